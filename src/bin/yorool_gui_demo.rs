@@ -1,117 +1,81 @@
 extern crate yorool_gui;
 
 use ggez::conf::{WindowMode, WindowSetup};
-use ggez::event::{self, EventHandler, MouseButton};
-use ggez::graphics::{self, Color, Rect};
+use ggez::event::{self, EventHandler, KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Color};
 use ggez::{Context, ContextBuilder, GameResult};
 
-use yorool_gui::gui;
-use yorool_gui::gui::checkbox::CheckboxBuilder;
-use yorool_gui::gui::radio_group::RadioGroupBuilder;
+use yorool_gui::gui::demo::RadioPanel;
+use yorool_gui::gui::recording::Recorder;
 use yorool_gui::gui::window_manager::WindowManager;
 
-struct GuiDemoState<'a> {
-    window_manager: WindowManager<'a>,
-    _radio_panel: RadioPanel,
+// Events go to the window manager, or to the `Recorder` wrapping it if
+// a file is given with `--record <file>`
+struct GuiDemoState<'a, H: EventHandler> {
+    handler: H,
+    _radio_panel: RadioPanel<'a>,
 }
 
-struct RadioPanel {}
-
-impl<'a> RadioPanel {
-    fn new(wm: &mut WindowManager<'a>) -> Self {
-        let radio_a = CheckboxBuilder::new().build();
-        let radio_b = CheckboxBuilder::new().build();
-        let radio_c = CheckboxBuilder::new().build();
-
-        let radio_group = RadioGroupBuilder::new()
-            .add_widget(radio_a.clone())
-            .add_widget(radio_b.clone())
-            .add_widget(radio_c.clone())
-            .build();
-
-        let radio_ribbon = gui::row()
-            .add_widget(radio_a)
-            .add_widget(radio_b)
-            .add_widget(radio_c)
-            .build();
-
-        let add_radio = {
-            let radio_group = radio_group.clone();
-            let radio_ribbon = radio_ribbon.clone();
-            move |_| {
-                let radio = CheckboxBuilder::new().build();
-                radio_group.borrow_mut().add_widget(radio.clone());
-                radio_ribbon.borrow_mut().add_widget(radio.clone());
-            }
-        };
-
-        let remove_radio = {
-            let radio_group = radio_group.clone();
-            let radio_ribbon = radio_ribbon.clone();
-            move |_| {
-                let radio = radio_group.borrow().radios().last().map(|r| r.clone());
-                if let Some(radio) = radio {
-                    radio_group.borrow_mut().remove_widget(radio.clone());
-                    radio_ribbon.borrow_mut().remove_widget(radio.clone());
-                }
-            }
-        };
-
-        let add = gui::button().set_label("Add").on_click(add_radio).build();
-        let remove = gui::button()
-            .set_label("Remove")
-            .on_click(remove_radio)
-            .build();
-
-        let panel = gui::panel()
-            .add_widget(
-                gui::column()
-                    .add_widget(radio_ribbon.clone())
-                    .add_widget(gui::row().add_widget(add).add_widget(remove).build())
-                    .build(),
-            )
-            .build();
-
-        wm.add_window(panel, Rect::zero(), true);
-
-        Self {}
-    }
-}
-
-impl GuiDemoState<'_> {
-    fn new() -> Self {
+impl<'a, H: EventHandler> GuiDemoState<'a, H> {
+    fn new(wrap: impl FnOnce(WindowManager<'a>) -> H) -> Self {
         let mut window_manager = WindowManager::new();
         let _radio_panel = RadioPanel::new(&mut window_manager);
         Self {
-            window_manager,
+            handler: wrap(window_manager),
             _radio_panel,
         }
     }
 }
 
-impl EventHandler for GuiDemoState<'_> {
+impl<H: EventHandler> EventHandler for GuiDemoState<'_, H> {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        self.window_manager.update(ctx)
+        EventHandler::update(&mut self.handler, ctx)
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         graphics::clear(ctx, Color::new(0., 0., 0., 0.));
-        self.window_manager.draw(ctx)?;
+        EventHandler::draw(&mut self.handler, ctx)?;
         graphics::present(ctx)
     }
 
     fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
-        self.window_manager
-            .mouse_button_down_event(ctx, button, x, y)
+        EventHandler::mouse_button_down_event(&mut self.handler, ctx, button, x, y)
     }
 
     fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
-        self.window_manager.mouse_button_up_event(ctx, button, x, y)
+        EventHandler::mouse_button_up_event(&mut self.handler, ctx, button, x, y)
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        EventHandler::mouse_motion_event(&mut self.handler, ctx, x, y, dx, dy)
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        EventHandler::mouse_wheel_event(&mut self.handler, ctx, x, y)
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        EventHandler::key_down_event(&mut self.handler, ctx, keycode, keymods, repeat)
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods) {
+        EventHandler::key_up_event(&mut self.handler, ctx, keycode, keymods)
+    }
+
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) {
+        EventHandler::text_input_event(&mut self.handler, ctx, character)
     }
 
     fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
         let new_rect = graphics::Rect::new(0., 0., width, height);
         graphics::set_screen_coordinates(ctx, new_rect).unwrap();
+        EventHandler::resize_event(&mut self.handler, ctx, width, height)
     }
 }
 
@@ -120,7 +84,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .window_setup(WindowSetup::default().title("Yorool GUI demo"))
         .window_mode(WindowMode::default().resizable(true));
     let (ctx, event_loop) = &mut cb.build()?;
-    let state = &mut GuiDemoState::new();
-    event::run(ctx, event_loop, state)?;
+    let record = {
+        let args: Vec<_> = std::env::args().collect();
+        args.iter()
+            .position(|a| a == "--record")
+            .and_then(|i| args.get(i + 1).cloned())
+    };
+    match record {
+        Some(path) => {
+            let state = &mut GuiDemoState::new(Recorder::new);
+            event::run(ctx, event_loop, state)?;
+            state.handler.save(path)?;
+        }
+        None => event::run(ctx, event_loop, &mut GuiDemoState::new(|wm| wm))?,
+    }
     Ok(())
 }
@@ -72,7 +72,10 @@ impl<'a> EventHandler for Frontend<'a> {
         }
     }
 
-    fn mouse_button_up_event(&mut self, _ctx: &mut Context, _button: MouseButton, x: f32, y: f32) {
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button != MouseButton::Left {
+            return;
+        }
         let mut rcback = self.rcback.borrow_mut();
         if rcback.is_touched() && self.rect.contains([x, y]) {
             rcback.set_touched(false);
@@ -1,17 +1,56 @@
 pub mod button;
+pub mod canvas;
+pub mod capture;
 pub mod checkbox;
+pub mod color_picker;
+pub mod combobox;
+pub mod demo;
+pub mod drag_drop;
+pub mod gestures;
+pub mod group_box;
+pub mod harness;
+pub mod image;
+pub mod list_view;
+pub mod menu;
+pub mod navigation;
+pub mod painter;
 pub mod panel;
+pub mod progress_bar;
 pub mod radio_group;
+pub mod raster;
+pub mod recording;
 pub mod ribbon;
+pub mod scrollbar;
+pub mod selection;
+pub mod shortcuts;
+pub mod spin_box;
+pub mod spinner;
+pub mod splitter;
+pub mod table;
+pub mod tabs;
+pub mod text_area;
+pub mod text_buffer;
+pub mod toasts;
+pub mod tooltips;
+pub mod touch;
+pub mod tree_view;
 pub mod window_manager;
 
-use ggez::event::EventHandler;
-use ggez::graphics::Rect;
+use crate::gui::painter::Painter;
+use crate::gui::touch::TouchPhase;
+use ggez::event::{Axis, Button, KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Color, Rect, Text};
+use ggez::input::keyboard;
+use ggez::nalgebra::Point2;
+use ggez::{timer, Context, GameResult};
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
+use std::time::Duration;
 
 pub type Handler<'a, T> = Rc<dyn Fn(Rc<RefCell<T>>) + 'a>;
 
+pub type IndexHandler<'a, T> = Rc<dyn Fn(Rc<RefCell<T>>, usize) + 'a>;
+
 pub type HandlerId = u64;
 
 pub fn handler_id<'a, T: ?Sized>(h: Handler<'a, T>) -> HandlerId {
@@ -51,10 +90,136 @@ pub trait IActions<'a> {
 pub trait ILayout {
     fn set_rect(&mut self, rect: Rect);
     fn get_rect(&self) -> Rect;
+    /// Width and height the widget wants to have. `None` means the widget
+    /// takes an equal share of the space left by the fixed ones.
+    fn get_preferred_size(&self) -> (Option<f32>, Option<f32>) {
+        (None, None)
+    }
+    /// Returns true once after the preferred size has changed, so that the
+    /// parent can lay out its children again
+    fn take_layout_changed(&mut self) -> bool {
+        false
+    }
+}
+
+/// Popups which should be drawn above all windows, e.g. open dropdown lists.
+/// Overlays are collected by `WindowManager` on each update, get pointer
+/// events before the windows and are drawn after them. The widget which
+/// returns an overlay stays responsible for its layout and actions.
+pub trait IOverlays<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>;
+    /// Descendants which get the pointer at the point, outermost first.
+    /// Containers add their child under the point and what it reports, so
+    /// hidden pages and covered widgets are left out, e.g. of tooltips.
+    fn collect_hovered(
+        &self,
+        _x: f32,
+        _y: f32,
+        _hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    ) {
+    }
+}
+
+/// What widgets take from the frame while handling events: time, keyboard
+/// modifiers and text metrics. Implemented by the ggez `Context` and by
+/// `harness::FakeContext`, so that widgets can be driven without a window.
+pub trait IContext {
+    fn time_since_start(&self) -> Duration;
+    /// Time since the previous update
+    fn delta(&self) -> Duration;
+    fn keymods(&self) -> KeyMods;
+    fn drawable_size(&self) -> (f32, f32);
+    fn text_width(&mut self, text: &Text) -> f32;
+    fn text_height(&mut self, text: &Text) -> f32;
+}
+
+impl IContext for Context {
+    fn time_since_start(&self) -> Duration {
+        timer::time_since_start(self)
+    }
+    fn delta(&self) -> Duration {
+        timer::delta(self)
+    }
+    fn keymods(&self) -> KeyMods {
+        keyboard::active_mods(self)
+    }
+    fn drawable_size(&self) -> (f32, f32) {
+        graphics::drawable_size(self)
+    }
+    fn text_width(&mut self, text: &Text) -> f32 {
+        text.width(self) as f32
+    }
+    fn text_height(&mut self, text: &Text) -> f32 {
+        text.height(self) as f32
+    }
+}
+
+/// Events of ggez `EventHandler` which take `IContext` instead of the ggez
+/// `Context`. Only drawing needs the real one. Unlike in `EventHandler` the
+/// default `key_down_event` does nothing, it doesn't quit on Escape.
+pub trait IEvents {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult;
+    fn draw(&mut self, ctx: &mut Context) -> GameResult;
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+    }
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+    }
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _x: f32,
+        _y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) {
+    }
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, _y: f32) {}
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+    }
+    fn key_up_event(&mut self, _ctx: &mut dyn IContext, _keycode: KeyCode, _keymods: KeyMods) {}
+    fn text_input_event(&mut self, _ctx: &mut dyn IContext, _character: char) {}
+    fn resize_event(&mut self, _ctx: &mut dyn IContext, _width: f32, _height: f32) {}
+    /// Gamepad events come without the gamepad id, all gamepads are the same
+    fn gamepad_button_down_event(&mut self, _ctx: &mut dyn IContext, _btn: Button) {}
+    fn gamepad_axis_event(&mut self, _ctx: &mut dyn IContext, _axis: Axis, _value: f32) {}
+    /// Touch of the finger with the id. ggez doesn't deliver touch events,
+    /// so they come from a custom event loop or are synthesized, e.g. by tests.
+    fn touch_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _id: u64,
+        _phase: TouchPhase,
+        _x: f32,
+        _y: f32,
+    ) {
+    }
+    /// Records what `draw` draws as commands in screen coordinates, for
+    /// drawing without GPU by `raster`. Widgets which don't record their
+    /// drawing are left out of the picture.
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, _painter: &mut Painter) {}
 }
-pub trait Widget<'a>: EventHandler + ILayout + IActions<'a> {}
 
-impl<'a, W> Widget<'a> for W where W: EventHandler + ILayout + IActions<'a> {}
+pub trait Widget<'a>: IEvents + ILayout + IActions<'a> + IOverlays<'a> {}
+
+impl<'a, W> Widget<'a> for W where W: IEvents + ILayout + IActions<'a> + IOverlays<'a> {}
 
 pub trait ICheckbox<'a> {
     fn get_state(&self) -> bool;
@@ -75,6 +240,83 @@ pub fn button<'a>() -> ButtonBuilder<'a> {
     ButtonBuilder::new()
 }
 
+pub type ProgressBarBuilder<'a> = progress_bar::Builder<
+    'a,
+    progress_bar::Backend,
+    progress_bar::Frontend<'a, progress_bar::Backend>,
+>;
+
+pub fn progress_bar<'a>() -> ProgressBarBuilder<'a> {
+    ProgressBarBuilder::new()
+}
+
+pub type SpinnerBuilder<'a> =
+    spinner::Builder<'a, spinner::Backend, spinner::Frontend<'a, spinner::Backend>>;
+
+pub fn spinner<'a>() -> SpinnerBuilder<'a> {
+    SpinnerBuilder::new()
+}
+
+pub type SpinBoxBuilder<'a> = spin_box::Builder<'a>;
+
+pub fn spin_box<'a>() -> SpinBoxBuilder<'a> {
+    SpinBoxBuilder::new()
+}
+
+pub type SplitterBuilder<'a> = splitter::Builder<'a>;
+
+pub fn splitter<'a>() -> SplitterBuilder<'a> {
+    SplitterBuilder::new()
+}
+
+pub type CanvasBuilder<'a> = canvas::Builder<'a>;
+
+pub fn canvas<'a>() -> CanvasBuilder<'a> {
+    CanvasBuilder::new()
+}
+
+pub type ColorPickerBuilder<'a> = color_picker::Builder<'a>;
+
+pub fn color_picker<'a>() -> ColorPickerBuilder<'a> {
+    ColorPickerBuilder::new()
+}
+
+pub type GroupBoxBuilder<'a> = group_box::Builder<'a>;
+
+pub fn group_box<'a>() -> GroupBoxBuilder<'a> {
+    GroupBoxBuilder::new()
+}
+
+/// Radio group for `GroupBox` sections: expanding one collapses the
+/// others, and all sections may be collapsed
+pub fn accordion<'a>() -> radio_group::RadioGroupBuilder<'a> {
+    radio_group::RadioGroupBuilder::new().set_allow_none(true)
+}
+
+pub type TextAreaBuilder<'a> = text_area::Builder<'a>;
+
+pub fn text_area<'a>() -> TextAreaBuilder<'a> {
+    TextAreaBuilder::new()
+}
+
+pub type TableBuilder<'a> = table::Builder<'a>;
+
+pub fn table<'a>() -> TableBuilder<'a> {
+    TableBuilder::new()
+}
+
+pub type TabsBuilder<'a> = tabs::Builder<'a>;
+
+pub fn tabs<'a>() -> TabsBuilder<'a> {
+    TabsBuilder::new()
+}
+
+pub type TreeViewBuilder<'a> = tree_view::Builder<'a>;
+
+pub fn tree_view<'a>() -> TreeViewBuilder<'a> {
+    TreeViewBuilder::new()
+}
+
 pub type RibbonBuilder<'a> = ribbon::Builder<'a>;
 
 pub fn ribbon<'a>() -> RibbonBuilder<'a> {
@@ -89,6 +331,49 @@ pub fn column<'a>() -> RibbonBuilder<'a> {
     RibbonBuilder::new().set_horizontal(false)
 }
 
+pub type ComboBoxBuilder<'a> = combobox::Builder<'a>;
+
+pub fn combobox<'a>() -> ComboBoxBuilder<'a> {
+    ComboBoxBuilder::new()
+}
+
+pub type ImageBuilder = self::image::Builder;
+
+pub fn image() -> ImageBuilder {
+    ImageBuilder::new()
+}
+
+pub type ListViewBuilder<'a> = list_view::Builder<'a>;
+
+pub fn list_view<'a>() -> ListViewBuilder<'a> {
+    ListViewBuilder::new()
+}
+
+pub type MenuBuilder<'a> = menu::Builder<'a>;
+
+pub fn menu<'a>() -> MenuBuilder<'a> {
+    MenuBuilder::new()
+}
+
+pub type MenuBarBuilder<'a> = menu::MenuBarBuilder<'a>;
+
+pub fn menu_bar<'a>() -> MenuBarBuilder<'a> {
+    MenuBarBuilder::new()
+}
+
+pub fn context_menu<'a>(
+    widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+    menu: Rc<RefCell<menu::Menu<'a>>>,
+) -> Rc<RefCell<menu::ContextMenu<'a>>> {
+    menu::ContextMenu::new(widget, menu)
+}
+
+pub fn gesture_area<'a>(
+    widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+) -> Rc<RefCell<gestures::GestureArea<'a>>> {
+    gestures::GestureArea::new(widget)
+}
+
 pub type PanelBuilder<'a> = panel::Builder<'a>;
 
 pub fn panel<'a>() -> PanelBuilder<'a> {
@@ -105,3 +390,77 @@ pub fn is_same<A: ?Sized, B: ?Sized>(a: &Rc<A>, b: &Rc<B>) -> bool {
     let pdb = b.as_ref() as *const _ as *const ();
     return pda == pdb;
 }
+
+/// `collect_hovered` of containers: the topmost of the widgets under the
+/// point, i.e. the last one drawn, and what it reports
+pub(crate) fn collect_hovered_child<'a, 'w>(
+    widgets: impl Iterator<Item = &'w Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    x: f32,
+    y: f32,
+    hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+) where
+    'a: 'w,
+{
+    let top = widgets
+        .filter(|w| w.borrow().get_rect().contains([x, y]))
+        .last();
+    if let Some(w) = top {
+        hovered.push(w.clone());
+        w.borrow().collect_hovered(x, y, hovered);
+    }
+}
+
+pub(crate) const HIGHLIGHT_COLOR: Color = Color {
+    r: 0.7,
+    g: 0.7,
+    b: 0.7,
+    a: 1.,
+};
+
+/// Space `draw_text` leaves between the text and the left and right sides
+/// of its rect
+pub(crate) const TEXT_MARGIN: f32 = 5.;
+
+/// Rect which gives text the whole width of `rect` when passed to
+/// `draw_text` or `Painter::text`, which keep `TEXT_MARGIN` inside
+pub(crate) fn without_text_margin(rect: Rect) -> Rect {
+    Rect::new(
+        rect.x - TEXT_MARGIN,
+        rect.y,
+        rect.w + TEXT_MARGIN * 2.,
+        rect.h,
+    )
+}
+
+/// Moves the popup rect as little as possible to be inside the screen of
+/// the size. Popups larger than the screen stick to its top left corner.
+pub(crate) fn clamp_to_screen(rect: Rect, (width, height): (f32, f32)) -> Rect {
+    Rect::new(
+        rect.x.min(width - rect.w).max(0.),
+        rect.y.min(height - rect.h).max(0.),
+        rect.w,
+        rect.h,
+    )
+}
+
+/// Draws single line of black text vertically centered in the rect
+pub(crate) fn draw_label(ctx: &mut Context, rect: Rect, label: &str, align: Align) -> GameResult {
+    draw_text(ctx, rect, label, align, graphics::BLACK)
+}
+
+pub(crate) fn draw_text(
+    ctx: &mut Context,
+    rect: Rect,
+    label: &str,
+    align: Align,
+    color: Color,
+) -> GameResult {
+    let mut text = Text::new(label);
+    text.set_bounds([rect.w - TEXT_MARGIN * 2., rect.h], align);
+    let tdh = (rect.h - text.height(ctx) as f32) / 2.;
+    graphics::draw(
+        ctx,
+        &text,
+        (Point2::new(rect.x + TEXT_MARGIN, rect.y + tdh), color),
+    )
+}
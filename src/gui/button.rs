@@ -1,9 +1,12 @@
+use crate::gui::image::{Fit, ImageData};
+use crate::gui::painter::Painter;
+use crate::gui::shortcuts::parse_mnemonic;
 use crate::gui::{collect_fired_actions, handler_id, Handler, HandlerId, THandlers, TRcSelf};
-use crate::gui::{IActions, ILabel, ILayout};
-use ggez::event::EventHandler;
-use ggez::graphics::{self, Align, DrawMode, DrawParam, MeshBuilder, Rect, Text};
+use crate::gui::{
+    without_text_margin, IActions, IContext, IEvents, ILabel, ILayout, IOverlays, Widget,
+};
+use ggez::graphics::{self, Align, Rect, Text};
 use ggez::input::mouse::MouseButton;
-use ggez::nalgebra::Point2;
 use ggez::{Context, GameResult};
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -19,6 +22,8 @@ pub trait TBackend<'a>: THandlers<'a> + ILabel<'a> {
 
 pub trait TFrontend<'a, BE: TBackend<'a>>: TRcSelf + ILayout + IActions<'a> {
     fn backend(&self) -> Rc<RefCell<BE>>;
+    /// Icon is drawn left of the label, or centered if the label is empty
+    fn set_icon(&mut self, icon: Option<Rc<ImageData>>);
 }
 
 pub trait IButton<'a> {
@@ -141,6 +146,10 @@ where
             .on_click(handler_id(rh.clone()), rh.clone());
         self
     }
+    pub fn set_icon(self, icon: Rc<ImageData>) -> Self {
+        self.rcfront.borrow_mut().set_icon(Some(icon));
+        self
+    }
     pub fn build(self) -> Rc<RefCell<FE>> {
         self.rcfront
     }
@@ -148,6 +157,7 @@ where
 
 pub struct Frontend<'a, BE: TBackend<'a>> {
     rcback: Rc<RefCell<BE>>,
+    icon: Option<Rc<ImageData>>,
     rect: Rect,
     rcself: Option<Weak<RefCell<Self>>>,
 }
@@ -159,6 +169,7 @@ where
     fn create() -> Rc<RefCell<Self>> {
         let v = Rc::new(RefCell::new(Self {
             rcback: BE::create(),
+            icon: None,
             rect: Rect::zero(),
             rcself: None, //            phantom: PhantomData,
         }));
@@ -176,17 +187,26 @@ where
     fn backend(&self) -> Rc<RefCell<BE>> {
         self.rcback.clone()
     }
+    fn set_icon(&mut self, icon: Option<Rc<ImageData>>) {
+        self.icon = icon;
+    }
 }
 
-impl<'a, T> EventHandler for Frontend<'a, T>
+impl<'a, T> IEvents for Frontend<'a, T>
 where
     T: TBackend<'a>,
 {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
         let mut rect = self.rect;
         let margin = 5.;
         let press_offset = 10.;
@@ -199,27 +219,65 @@ where
         rect.y += margin + dxy;
         rect.w -= margin * 2. + press_offset;
         rect.h -= margin * 2. + press_offset;
-        let mesh = MeshBuilder::new()
-            .rectangle(DrawMode::fill(), rect, graphics::WHITE)
-            .build(ctx)?;
-        graphics::draw(ctx, &mesh, DrawParam::default())?;
-        let mut text = Text::new(self.rcback.borrow_mut().get_label());
-        text.set_bounds([rect.w, rect.h], Align::Center);
-        let tdh = (rect.h - text.height(ctx) as f32) / 2.;
-        graphics::draw(
-            ctx,
-            &text,
-            (Point2::new(rect.x, rect.y + tdh), graphics::BLACK),
-        )
-    }
-
-    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        painter.fill_rect(rect, graphics::WHITE);
+        let label = self.rcback.borrow_mut().get_label();
+        if let Some(icon) = &self.icon {
+            if label.is_empty() {
+                painter.image(rect, icon.clone(), Fit::Contain);
+                return;
+            }
+            // Icon column is as wide as the button is high, but never wider
+            // than the button
+            let side = rect.h.min(rect.w);
+            let icon_rect = Rect::new(rect.x, rect.y, side, rect.h);
+            painter.image(icon_rect, icon.clone(), Fit::Contain);
+            rect.x += side;
+            rect.w -= side;
+        }
+        let (label, mnemonic) = parse_mnemonic(&label);
+        // The label is centered in the rect right of the icon
+        painter.text(
+            without_text_margin(rect),
+            label.as_str(),
+            Align::Center,
+            graphics::BLACK,
+        );
+        if let Some(index) = mnemonic {
+            // Underline is placed for a single line label
+            let prefix: String = label.chars().take(index).collect();
+            let letter: String = label.chars().skip(index).take(1).collect();
+            let width = |ctx: &mut dyn IContext, s: &str| ctx.text_width(&Text::new(s));
+            let text_height = ctx.text_height(&Text::new(label.as_str()));
+            let tdh = (rect.h - text_height) / 2.;
+            let x = rect.x + (rect.w - width(ctx, &label)) / 2. + width(ctx, &prefix);
+            let y = rect.y + tdh + text_height - 1.;
+            let w = width(ctx, &letter);
+            painter.fill_rect(Rect::new(x, y, w, 1.), graphics::BLACK);
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
         if button == MouseButton::Left && self.rect.contains([x, y]) {
             self.rcback.borrow_mut().set_touched(true);
         }
     }
 
-    fn mouse_button_up_event(&mut self, _ctx: &mut Context, _button: MouseButton, x: f32, y: f32) {
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button != MouseButton::Left {
+            return;
+        }
         let mut rcback = self.rcback.borrow_mut();
         if rcback.is_touched() && self.rect.contains([x, y]) {
             rcback.set_touched(false);
@@ -250,3 +308,12 @@ where
         collect_fired_actions(&mut *self.rcback.borrow_mut())
     }
 }
+
+impl<'a, BE> IOverlays<'a> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,278 @@
+use crate::gui::painter::Painter;
+use crate::gui::{is_same, IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::event::MouseButton;
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+pub type DrawHandler<'a> = Rc<dyn Fn(&mut Painter, Rect) + 'a>;
+
+pub type ButtonHandler<'a, T> = Rc<dyn Fn(Rc<RefCell<T>>, MouseButton, f32, f32) + 'a>;
+
+pub type PointHandler<'a, T> = Rc<dyn Fn(Rc<RefCell<T>>, f32, f32) + 'a>;
+
+/// Widget drawn by a user closure. The closure gets the rect of the canvas
+/// in local coordinates, i.e. starting at (0, 0), and a painter which
+/// translates them to the screen. Pointer handlers get local coordinates
+/// too. Moves and releases are reported while a button pressed inside
+/// the canvas is held, even outside of it.
+pub struct Canvas<'a> {
+    draw_handler: Option<DrawHandler<'a>>,
+    rect: Rect,
+    hovered: bool,
+    // Button which started the stroke
+    pressed: Option<MouseButton>,
+    on_mouse_down_handlers: Vec<ButtonHandler<'a, Self>>,
+    on_mouse_up_handlers: Vec<ButtonHandler<'a, Self>>,
+    on_mouse_move_handlers: Vec<PointHandler<'a, Self>>,
+    on_wheel_handlers: Vec<PointHandler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> Canvas<'a> {
+    fn new() -> Self {
+        Self {
+            draw_handler: None,
+            rect: Rect::zero(),
+            hovered: false,
+            pressed: None,
+            on_mouse_down_handlers: Vec::new(),
+            on_mouse_up_handlers: Vec::new(),
+            on_mouse_move_handlers: Vec::new(),
+            on_wheel_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn set_draw(&mut self, handler: impl Fn(&mut Painter, Rect) + 'a) -> &mut Self {
+        self.draw_handler = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn on_mouse_down(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, MouseButton, f32, f32) + 'a,
+    ) -> ButtonHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_mouse_down_handlers.push(rc.clone());
+        rc
+    }
+    pub fn on_mouse_up(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, MouseButton, f32, f32) + 'a,
+    ) -> ButtonHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_mouse_up_handlers.push(rc.clone());
+        rc
+    }
+    pub fn on_mouse_move(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, f32, f32) + 'a,
+    ) -> PointHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_mouse_move_handlers.push(rc.clone());
+        rc
+    }
+    /// Handler gets horizontal and vertical scroll amounts
+    pub fn on_wheel(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, f32, f32) + 'a,
+    ) -> PointHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_wheel_handlers.push(rc.clone());
+        rc
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_mouse_down_handlers
+            .drain_filter(|h| is_same(h, &handler))
+            .count();
+        self.on_mouse_up_handlers
+            .drain_filter(|h| is_same(h, &handler))
+            .count();
+        self.on_mouse_move_handlers
+            .drain_filter(|h| is_same(h, &handler))
+            .count();
+        self.on_wheel_handlers
+            .drain_filter(|h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_button(&mut self, down: bool, button: MouseButton, x: f32, y: f32) {
+        let handlers = if down {
+            &self.on_mouse_down_handlers
+        } else {
+            &self.on_mouse_up_handlers
+        };
+        for h in handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone(), button, x, y)));
+        }
+    }
+
+    fn fire_point(&mut self, wheel: bool, x: f32, y: f32) {
+        let handlers = if wheel {
+            &self.on_wheel_handlers
+        } else {
+            &self.on_mouse_move_handlers
+        };
+        for h in handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone(), x, y)));
+        }
+    }
+
+    fn local(&self, x: f32, y: f32) -> (f32, f32) {
+        (x - self.rect.x, y - self.rect.y)
+    }
+
+    /// Records what the draw closure paints, without drawing it
+    pub fn paint(&self) -> Painter {
+        let mut painter = Painter::new([self.rect.x, self.rect.y]);
+        if let Some(h) = &self.draw_handler {
+            h(&mut painter, Rect::new(0., 0., self.rect.w, self.rect.h));
+        }
+        painter
+    }
+}
+
+impl IEvents for Canvas<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.paint().render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        painter.extend(self.paint().take_commands());
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if self.rect.contains([x, y]) {
+            self.pressed.get_or_insert(button);
+            let (lx, ly) = self.local(x, y);
+            self.fire_button(true, button, lx, ly);
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if self.pressed == Some(button) || self.rect.contains([x, y]) {
+            if self.pressed == Some(button) {
+                self.pressed = None;
+            }
+            let (lx, ly) = self.local(x, y);
+            self.fire_button(false, button, lx, ly);
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+        if self.pressed.is_some() || self.hovered {
+            let (lx, ly) = self.local(x, y);
+            self.fire_point(false, lx, ly);
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32) {
+        if self.hovered {
+            self.fire_point(true, x, y);
+        }
+    }
+}
+
+impl ILayout for Canvas<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Canvas<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for Canvas<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    canvas: Canvas<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            canvas: Canvas::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<Canvas<'a>>> {
+        let rc = Rc::new(RefCell::new(self.canvas));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_draw(mut self, handler: impl Fn(&mut Painter, Rect) + 'a) -> Self {
+        self.canvas.set_draw(handler);
+        self
+    }
+
+    pub fn on_mouse_down(
+        mut self,
+        handler: impl Fn(Rc<RefCell<Canvas<'a>>>, MouseButton, f32, f32) + 'a,
+    ) -> Self {
+        self.canvas.on_mouse_down(handler);
+        self
+    }
+
+    pub fn on_mouse_up(
+        mut self,
+        handler: impl Fn(Rc<RefCell<Canvas<'a>>>, MouseButton, f32, f32) + 'a,
+    ) -> Self {
+        self.canvas.on_mouse_up(handler);
+        self
+    }
+
+    pub fn on_mouse_move(
+        mut self,
+        handler: impl Fn(Rc<RefCell<Canvas<'a>>>, f32, f32) + 'a,
+    ) -> Self {
+        self.canvas.on_mouse_move(handler);
+        self
+    }
+
+    pub fn on_wheel(mut self, handler: impl Fn(Rc<RefCell<Canvas<'a>>>, f32, f32) + 'a) -> Self {
+        self.canvas.on_wheel(handler);
+        self
+    }
+}
@@ -0,0 +1,116 @@
+use crate::gui::Widget;
+use ggez::graphics::Rect;
+use ggez::input::mouse::MouseButton;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// Children under the mouse press. While the button is held containers send
+/// pointer motion and the release only to them, so the pressed widget keeps
+/// getting events when the pointer leaves its rect and other widgets don't
+/// see the release. Presses are still sent to all children, since widgets
+/// use presses outside to close popups and drop focus.
+pub struct PointerCapture {
+    button: Option<MouseButton>,
+    point: [f32; 2],
+    children: Vec<usize>,
+}
+
+impl PointerCapture {
+    pub fn new() -> Self {
+        Self {
+            button: None,
+            point: [0., 0.],
+            children: Vec::new(),
+        }
+    }
+
+    /// Captures children whose rects contain the point. Presses of other
+    /// buttons while one is held don't change the capture.
+    pub fn press<I: IntoIterator<Item = Rect>>(
+        &mut self,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        rects: I,
+    ) {
+        if self.button.is_some() {
+            return;
+        }
+        self.button = Some(button);
+        self.point = [x, y];
+        self.children = rects
+            .into_iter()
+            .enumerate()
+            .filter(|(_, r)| r.contains([x, y]))
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    /// Ends the capture if the button is the pressed one
+    pub fn release(&mut self, button: MouseButton) {
+        if self.button == Some(button) {
+            self.cancel();
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.button = None;
+        self.children.clear();
+    }
+
+    /// Button which holds the capture
+    pub fn button(&self) -> Option<MouseButton> {
+        self.button
+    }
+
+    /// Point of the press which holds the capture
+    pub fn point(&self) -> Option<[f32; 2]> {
+        self.button.map(|_| self.point)
+    }
+
+    pub fn is_captured(&self) -> bool {
+        self.button.is_some()
+    }
+
+    /// Whether the child gets pointer motion and releases. All children get
+    /// them if nothing is pressed.
+    pub fn routes_to(&self, index: usize) -> bool {
+        self.button.is_none() || self.children.contains(&index)
+    }
+}
+
+pub(crate) enum CaptureRequest<'a> {
+    Capture(Weak<RefCell<dyn Widget<'a> + 'a>>),
+    Release,
+}
+
+/// Requests of widgets to take the pointer or give it back. `WindowManager`
+/// owns it and applies the last request before each mouse event and in
+/// `update`, as `capture_pointer` and `release_pointer` do. Widgets keep
+/// the handle returned by `WindowManager::capture_requests`.
+pub struct CaptureRequests<'a> {
+    request: Option<CaptureRequest<'a>>,
+}
+
+impl<'a> CaptureRequests<'a> {
+    pub(crate) fn new() -> Self {
+        Self { request: None }
+    }
+
+    pub fn capture(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        let widget: Rc<RefCell<dyn Widget<'a> + 'a>> = widget;
+        self.request = Some(CaptureRequest::Capture(Rc::downgrade(&widget)));
+    }
+
+    pub fn release(&mut self) {
+        self.request = Some(CaptureRequest::Release);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.request.is_some()
+    }
+
+    pub(crate) fn take(&mut self) -> Option<CaptureRequest<'a>> {
+        self.request.take()
+    }
+}
@@ -1,6 +1,7 @@
-use crate::gui::{is_same, IActions, ICheckbox, ILayout};
-use ggez::event::{EventHandler, MouseButton};
-use ggez::graphics::{self, DrawMode, DrawParam, MeshBuilder, Rect};
+use crate::gui::painter::Painter;
+use crate::gui::{is_same, IActions, ICheckbox, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::event::MouseButton;
+use ggez::graphics::{self, Rect};
 use ggez::{Context, GameResult};
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
@@ -82,39 +83,53 @@ impl<'a> ICheckbox<'a> for Checkbox<'a> {
     }
 }
 
-impl EventHandler for Checkbox<'_> {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
+impl IEvents for Checkbox<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
         let mut rect = self.rect;
         let dsz = if self.touched { 10. } else { 5. };
         rect.x += dsz;
         rect.y += dsz;
         rect.w -= dsz * 2.;
         rect.h -= dsz * 2.;
-        let mesh = MeshBuilder::new()
-            .rectangle(
-                if self.state {
-                    DrawMode::fill()
-                } else {
-                    DrawMode::stroke(1.)
-                },
-                rect,
-                graphics::WHITE,
-            )
-            .build(ctx)?;
-        graphics::draw(ctx, &mesh, DrawParam::default())
-    }
-
-    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if self.state {
+            painter.fill_rect(rect, graphics::WHITE);
+        } else {
+            painter.stroke_rect(rect, 1., graphics::WHITE);
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
         if button == MouseButton::Left && self.rect.contains([x, y]) {
             self.touched = true;
         }
     }
 
-    fn mouse_button_up_event(&mut self, _ctx: &mut Context, _button: MouseButton, x: f32, y: f32) {
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button != MouseButton::Left {
+            return;
+        }
         if self.touched && self.rect.contains([x, y]) {
             self.state = !self.state;
             self.touched = false;
@@ -140,6 +155,12 @@ impl<'a> IActions<'a> for Checkbox<'a> {
     }
 }
 
+impl<'a> IOverlays<'a> for Checkbox<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
 pub struct CheckboxBuilder<'a> {
     ribbon: Checkbox<'a>,
 }
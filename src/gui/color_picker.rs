@@ -0,0 +1,635 @@
+use crate::gui::painter::Painter;
+use crate::gui::{is_same, Handler, IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Color, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+const PADDING: f32 = 4.;
+const HUE_WIDTH: f32 = 16.;
+const ALPHA_HEIGHT: f32 = 14.;
+const ENTRY_HEIGHT: f32 = 24.;
+const SWATCH_SIZE: f32 = 16.;
+const CHECKER_SIZE: f32 = 7.;
+
+/// Converts hue in degrees, saturation and value in 0..1 to RGB in 0..1
+pub fn hsv_to_rgb(hue: f32, sat: f32, val: f32) -> (f32, f32, f32) {
+    let h = hue.rem_euclid(360.) / 60.;
+    let c = val * sat;
+    let x = c * (1. - (h % 2. - 1.).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.),
+        1 => (x, c, 0.),
+        2 => (0., c, x),
+        3 => (0., x, c),
+        4 => (x, 0., c),
+        _ => (c, 0., x),
+    };
+    let m = val - c;
+    (r + m, g + m, b + m)
+}
+
+/// Converts RGB in 0..1 to hue in degrees, saturation and value in 0..1.
+/// Hue of grays is 0.
+pub fn rgb_to_hsv(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let d = max - min;
+    let hue = if d <= 0. {
+        0.
+    } else if max == r {
+        60. * ((g - b) / d).rem_euclid(6.)
+    } else if max == g {
+        60. * ((b - r) / d + 2.)
+    } else {
+        60. * ((r - g) / d + 4.)
+    };
+    let sat = if max <= 0. { 0. } else { d / max };
+    (hue, sat, max)
+}
+
+/// Formats color as "#rrggbb", or "#rrggbbaa" if it is not opaque
+pub fn to_hex(color: Color) -> String {
+    let (r, g, b, a) = color.to_rgba();
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+/// Parses "#rgb", "#rrggbb", "#rrggbbaa" (the '#' may be omitted) or
+/// decimal components 0..255 like "255, 128, 0" or "rgba(255 128 0 64)"
+pub fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+    if let Some(hex) = parse_hex(text.trim_start_matches('#')) {
+        return Some(hex);
+    }
+    let text = text
+        .trim_start_matches("rgba")
+        .trim_start_matches("rgb")
+        .trim_start_matches('(')
+        .trim_end_matches(')');
+    let parts = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match parts[..] {
+        [r, g, b] => Some(Color::from_rgb(r, g, b)),
+        [r, g, b, a] => Some(Color::from_rgba(r, g, b, a)),
+        _ => None,
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => {
+            let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).ok().map(|d| d * 17);
+            Some(Color::from_rgb(digit(0)?, digit(1)?, digit(2)?))
+        }
+        6 => Some(Color::from_rgb(byte(0)?, byte(2)?, byte(4)?)),
+        8 => Some(Color::from_rgba(byte(0)?, byte(2)?, byte(4)?, byte(6)?)),
+        _ => None,
+    }
+}
+
+fn default_swatches() -> Vec<Color> {
+    [
+        0x000000, 0x808080, 0xc0c0c0, 0xffffff, 0xff0000, 0xff8000, 0xffff00, 0x00ff00, 0x00ffff,
+        0x0000ff, 0x8000ff, 0xff00ff,
+    ]
+    .iter()
+    .map(|rgb| Color::from_rgb_u32(*rgb))
+    .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Drag {
+    None,
+    Square,
+    Hue,
+    Alpha,
+}
+
+/// Color selector: saturation/value square with hue strip, alpha slider,
+/// text entry accepting hex or decimal RGB, and a palette of swatches.
+/// Color is kept in HSV so that hue survives when saturation or value
+/// drop to zero.
+pub struct ColorPicker<'a> {
+    hue: f32,
+    sat: f32,
+    val: f32,
+    alpha: f32,
+    swatches: Vec<Color>,
+    editing: Option<String>,
+    drag: Drag,
+    rect: Rect,
+    focused: bool,
+    on_color_changed_handlers: Vec<Handler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> ColorPicker<'a> {
+    fn new() -> Self {
+        Self {
+            hue: 0.,
+            sat: 0.,
+            val: 1.,
+            alpha: 1.,
+            swatches: default_swatches(),
+            editing: None,
+            drag: Drag::None,
+            rect: Rect::zero(),
+            focused: false,
+            on_color_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_color_changed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>) + 'a,
+    ) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_color_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_color_changed_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_color_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_color_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_color_changed(&mut self) {
+        for h in &self.on_color_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    pub fn get_color(&self) -> Color {
+        let (r, g, b) = hsv_to_rgb(self.hue, self.sat, self.val);
+        Color::new(r, g, b, self.alpha)
+    }
+
+    /// Sets color without firing handlers. Hue is kept for grays.
+    pub fn set_color(&mut self, color: Color) -> &mut Self {
+        let (hue, sat, val) = rgb_to_hsv(color.r, color.g, color.b);
+        if sat > 0. && val > 0. {
+            self.hue = hue;
+        }
+        if val > 0. {
+            self.sat = sat;
+        }
+        self.val = val.clamp(0., 1.);
+        self.alpha = color.a.clamp(0., 1.);
+        self
+    }
+
+    /// Hue in degrees, saturation and value in 0..1
+    pub fn get_hsv(&self) -> (f32, f32, f32) {
+        (self.hue, self.sat, self.val)
+    }
+
+    /// Sets hue, saturation and value without firing handlers
+    pub fn set_hsv(&mut self, hue: f32, sat: f32, val: f32) -> &mut Self {
+        self.hue = hue.rem_euclid(360.);
+        self.sat = sat.clamp(0., 1.);
+        self.val = val.clamp(0., 1.);
+        self
+    }
+
+    pub fn get_swatches(&self) -> &[Color] {
+        &self.swatches
+    }
+
+    pub fn set_swatches(&mut self, swatches: Vec<Color>) -> &mut Self {
+        self.swatches = swatches;
+        self
+    }
+
+    pub fn add_swatch(&mut self, color: Color) -> &mut Self {
+        self.swatches.push(color);
+        self
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    fn change_hsva(&mut self, hue: f32, sat: f32, val: f32, alpha: f32) {
+        let old = (self.hue, self.sat, self.val, self.alpha);
+        self.set_hsv(hue, sat, val);
+        self.alpha = alpha.clamp(0., 1.);
+        if old != (self.hue, self.sat, self.val, self.alpha) {
+            self.fire_on_color_changed();
+        }
+    }
+
+    fn change_color(&mut self, color: Color) {
+        let old = (self.hue, self.sat, self.val, self.alpha);
+        self.set_color(color);
+        if old != (self.hue, self.sat, self.val, self.alpha) {
+            self.fire_on_color_changed();
+        }
+    }
+
+    /// Applies the typed text; invalid text is dropped
+    pub fn commit(&mut self) {
+        if let Some(text) = self.editing.take() {
+            if let Some(color) = parse_color(&text) {
+                self.change_color(color);
+            }
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.editing = None;
+    }
+
+    fn swatch_rows(&self) -> usize {
+        match self.swatches.len() {
+            0 => 0,
+            n => (n - 1) / self.swatches_per_row() + 1,
+        }
+    }
+
+    fn swatches_per_row(&self) -> usize {
+        ((self.rect.w / SWATCH_SIZE) as usize).max(1)
+    }
+
+    fn swatches_rect(&self) -> Rect {
+        let h = self.swatch_rows() as f32 * SWATCH_SIZE;
+        Rect::new(self.rect.x, self.rect.y + self.rect.h - h, self.rect.w, h)
+    }
+
+    fn entry_row(&self) -> Rect {
+        let bottom = self.swatches_rect().y;
+        let gap = if self.swatches.is_empty() {
+            0.
+        } else {
+            PADDING
+        };
+        Rect::new(
+            self.rect.x,
+            bottom - gap - ENTRY_HEIGHT,
+            self.rect.w,
+            ENTRY_HEIGHT,
+        )
+    }
+
+    fn preview_rect(&self) -> Rect {
+        let row = self.entry_row();
+        Rect::new(row.x, row.y, ENTRY_HEIGHT, ENTRY_HEIGHT)
+    }
+
+    fn entry_rect(&self) -> Rect {
+        let row = self.entry_row();
+        let w = ENTRY_HEIGHT + PADDING;
+        Rect::new(row.x + w, row.y, (row.w - w).max(0.), row.h)
+    }
+
+    fn alpha_rect(&self) -> Rect {
+        let y = self.entry_row().y - PADDING - ALPHA_HEIGHT;
+        Rect::new(self.rect.x, y, self.rect.w, ALPHA_HEIGHT)
+    }
+
+    fn square_rect(&self) -> Rect {
+        let h = (self.alpha_rect().y - PADDING - self.rect.y).max(0.);
+        let w = (self.rect.w - HUE_WIDTH - PADDING).max(0.);
+        Rect::new(self.rect.x, self.rect.y, w, h)
+    }
+
+    fn hue_rect(&self) -> Rect {
+        let square = self.square_rect();
+        Rect::new(
+            self.rect.x + self.rect.w - HUE_WIDTH,
+            square.y,
+            HUE_WIDTH,
+            square.h,
+        )
+    }
+
+    fn swatch_at(&self, x: f32, y: f32) -> Option<usize> {
+        let rect = self.swatches_rect();
+        if !rect.contains([x, y]) {
+            return None;
+        }
+        let col = ((x - rect.x) / SWATCH_SIZE) as usize;
+        let row = ((y - rect.y) / SWATCH_SIZE) as usize;
+        let per_row = self.swatches_per_row();
+        if col >= per_row {
+            return None;
+        }
+        Some(row * per_row + col).filter(|i| *i < self.swatches.len())
+    }
+
+    fn drag_to(&mut self, x: f32, y: f32) {
+        let ratio = |v: f32, start: f32, len: f32| {
+            if len > 0. {
+                ((v - start) / len).clamp(0., 1.)
+            } else {
+                0.
+            }
+        };
+        match self.drag {
+            Drag::None => {}
+            Drag::Square => {
+                let r = self.square_rect();
+                let sat = ratio(x, r.x, r.w);
+                let val = 1. - ratio(y, r.y, r.h);
+                self.change_hsva(self.hue, sat, val, self.alpha);
+            }
+            Drag::Hue => {
+                let r = self.hue_rect();
+                // Keep 360 from wrapping to 0 at the bottom of the strip
+                let hue = (ratio(y, r.y, r.h) * 360.).min(359.99);
+                self.change_hsva(hue, self.sat, self.val, self.alpha);
+            }
+            Drag::Alpha => {
+                let r = self.alpha_rect();
+                let alpha = ratio(x, r.x, r.w);
+                self.change_hsva(self.hue, self.sat, self.val, alpha);
+            }
+        }
+    }
+
+    fn key_down(&mut self, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Return => self.commit(),
+            KeyCode::Escape => self.cancel(),
+            KeyCode::Back => {
+                let text = to_hex(self.get_color());
+                self.editing.get_or_insert(text).pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn paint(&self, painter: &mut Painter) {
+        let color = self.get_color();
+        let (r, g, b) = hsv_to_rgb(self.hue, 1., 1.);
+        let pure = Color::new(r, g, b, 1.);
+        let transparent_black = Color::new(0., 0., 0., 0.);
+
+        // Saturation goes from left to right, value from top to bottom
+        let square = self.square_rect();
+        painter.gradient(square, graphics::WHITE, pure, true);
+        painter.gradient(square, transparent_black, graphics::BLACK, false);
+        let (cx, cy) = (
+            square.x + self.sat * square.w,
+            square.y + (1. - self.val) * square.h,
+        );
+        let marker = if self.val > 0.5 {
+            graphics::BLACK
+        } else {
+            graphics::WHITE
+        };
+        painter.stroke_circle([cx, cy], 4., 1.5, marker);
+
+        let strip = self.hue_rect();
+        let segment = strip.h / 6.;
+        for i in 0..6 {
+            let (r0, g0, b0) = hsv_to_rgb(i as f32 * 60., 1., 1.);
+            let (r1, g1, b1) = hsv_to_rgb((i + 1) as f32 * 60., 1., 1.);
+            let rect = Rect::new(strip.x, strip.y + i as f32 * segment, strip.w, segment);
+            painter.gradient(
+                rect,
+                Color::new(r0, g0, b0, 1.),
+                Color::new(r1, g1, b1, 1.),
+                false,
+            );
+        }
+        let hy = strip.y + self.hue / 360. * strip.h;
+        painter.stroke_rect(
+            Rect::new(strip.x - 1., hy - 2., strip.w + 2., 4.),
+            2.,
+            graphics::BLACK,
+        );
+
+        let alpha = self.alpha_rect();
+        checker(painter, alpha);
+        let opaque = Color::new(color.r, color.g, color.b, 1.);
+        let clear = Color::new(color.r, color.g, color.b, 0.);
+        painter.gradient(alpha, clear, opaque, true);
+        let ax = alpha.x + self.alpha * alpha.w;
+        painter.stroke_rect(
+            Rect::new(ax - 2., alpha.y - 1., 4., alpha.h + 2.),
+            2.,
+            graphics::BLACK,
+        );
+
+        let preview = self.preview_rect();
+        checker(painter, preview);
+        painter.fill_rect(preview, color);
+        painter.stroke_rect(preview, 1., graphics::BLACK);
+
+        let entry = self.entry_rect();
+        painter.fill_rect(entry, graphics::WHITE);
+        if self.focused {
+            painter.stroke_rect(entry, 2., graphics::BLACK);
+        }
+
+        let swatches = self.swatches_rect();
+        let per_row = self.swatches_per_row();
+        for (i, swatch) in self.swatches.iter().enumerate() {
+            let rect = Rect::new(
+                swatches.x + (i % per_row) as f32 * SWATCH_SIZE,
+                swatches.y + (i / per_row) as f32 * SWATCH_SIZE,
+                SWATCH_SIZE,
+                SWATCH_SIZE,
+            );
+            painter.fill_rect(rect, *swatch);
+            painter.stroke_rect(rect, 1., graphics::WHITE);
+        }
+    }
+}
+
+// Gray checkerboard shown behind transparent colors
+fn checker(painter: &mut Painter, rect: Rect) {
+    if rect.w <= 0. || rect.h <= 0. {
+        return;
+    }
+    painter.fill_rect(rect, graphics::WHITE);
+    let gray = Color::new(0.75, 0.75, 0.75, 1.);
+    let mut y = 0;
+    while y as f32 * CHECKER_SIZE < rect.h {
+        let mut x = y % 2;
+        while x as f32 * CHECKER_SIZE < rect.w {
+            let cx = x as f32 * CHECKER_SIZE;
+            let cy = y as f32 * CHECKER_SIZE;
+            let cell = Rect::new(
+                rect.x + cx,
+                rect.y + cy,
+                CHECKER_SIZE.min(rect.w - cx),
+                CHECKER_SIZE.min(rect.h - cy),
+            );
+            painter.fill_rect(cell, gray);
+            x += 2;
+        }
+        y += 1;
+    }
+}
+
+impl IEvents for ColorPicker<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.paint(painter);
+        let text = match self.editing {
+            Some(ref edit) => format!("{}|", edit),
+            None => to_hex(self.get_color()),
+        };
+        painter.text(self.entry_rect(), text, Align::Left, graphics::BLACK);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if !self.rect.contains([x, y]) {
+            if self.focused {
+                self.commit();
+                self.focused = false;
+            }
+            return;
+        }
+        self.focused = true;
+        if button != MouseButton::Left {
+            return;
+        }
+        let p = [x, y];
+        self.drag = if self.square_rect().contains(p) {
+            Drag::Square
+        } else if self.hue_rect().contains(p) {
+            Drag::Hue
+        } else if self.alpha_rect().contains(p) {
+            Drag::Alpha
+        } else {
+            Drag::None
+        };
+        if self.drag != Drag::None {
+            self.commit();
+            self.drag_to(x, y);
+        } else if let Some(index) = self.swatch_at(x, y) {
+            self.cancel();
+            self.change_color(self.swatches[index]);
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        if button == MouseButton::Left {
+            self.drag = Drag::None;
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.drag_to(x, y);
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(keycode)
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut dyn IContext, character: char) {
+        if self.focused && !character.is_control() {
+            self.editing.get_or_insert_with(String::new).push(character);
+        }
+    }
+}
+
+impl ILayout for ColorPicker<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for ColorPicker<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for ColorPicker<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    color_picker: ColorPicker<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            color_picker: ColorPicker::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<ColorPicker<'a>>> {
+        let rc = Rc::new(RefCell::new(self.color_picker));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_color(mut self, color: Color) -> Self {
+        self.color_picker.set_color(color);
+        self
+    }
+
+    pub fn set_swatches(mut self, swatches: Vec<Color>) -> Self {
+        self.color_picker.set_swatches(swatches);
+        self
+    }
+
+    pub fn on_color_changed(mut self, handler: impl Fn(Rc<RefCell<ColorPicker<'a>>>) + 'a) -> Self {
+        self.color_picker.on_color_changed(handler);
+        self
+    }
+}
@@ -0,0 +1,574 @@
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    clamp_to_screen, is_same, IActions, IContext, IEvents, ILayout, IOverlays, IndexHandler, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+// Typed characters are joined into one search string if they come faster than this
+const SEARCH_TIMEOUT: Duration = Duration::from_millis(1000);
+
+struct Popup {
+    items: Vec<String>,
+    highlighted: Option<usize>,
+    first_visible: usize,
+    visible_count: usize,
+    item_height: f32,
+    hovered: bool,
+    chosen: Option<usize>,
+    anchor: Rect,
+    screen: Option<(f32, f32)>,
+    rect: Rect,
+}
+
+impl Popup {
+    fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            highlighted: None,
+            first_visible: 0,
+            visible_count: 0,
+            item_height: 0.,
+            hovered: false,
+            chosen: None,
+            anchor: Rect::zero(),
+            screen: None,
+            rect: Rect::zero(),
+        }
+    }
+
+    fn open(&mut self, items: Vec<String>, selected: Option<usize>, max_visible: usize) {
+        self.items = items;
+        self.visible_count = self.items.len().min(max_visible);
+        self.first_visible = 0;
+        self.chosen = None;
+        self.highlighted = None;
+        if let Some(index) = selected {
+            self.highlight(index);
+        }
+    }
+
+    // Place popup just under the widget it belongs to, or above it if
+    // there is no room on the screen below
+    fn place(&mut self, anchor: Rect) {
+        self.anchor = anchor;
+        self.item_height = anchor.h;
+        let h = anchor.h * self.visible_count as f32;
+        let mut rect = Rect::new(anchor.x, anchor.y + anchor.h, anchor.w, h);
+        if let Some(screen) = self.screen {
+            if rect.bottom() > screen.1 && anchor.y >= h {
+                rect.y = anchor.y - h;
+            }
+            rect = clamp_to_screen(rect, screen);
+        }
+        self.rect = rect;
+    }
+
+    fn set_screen(&mut self, screen: (f32, f32)) {
+        if self.screen != Some(screen) {
+            self.screen = Some(screen);
+            self.place(self.anchor);
+        }
+    }
+
+    fn item_at(&self, x: f32, y: f32) -> Option<usize> {
+        if !self.rect.contains([x, y]) || self.item_height <= 0. {
+            return None;
+        }
+        let index = self.first_visible + ((y - self.rect.y) / self.item_height) as usize;
+        if index < self.items.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    fn highlight(&mut self, index: usize) {
+        if index >= self.items.len() {
+            return;
+        }
+        self.highlighted = Some(index);
+        if index < self.first_visible {
+            self.first_visible = index;
+        } else if index >= self.first_visible + self.visible_count {
+            self.first_visible = index + 1 - self.visible_count;
+        }
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        let max_first = self.items.len() - self.visible_count;
+        let first = self.first_visible as isize + delta;
+        self.first_visible = first.max(0).min(max_first as isize) as usize;
+    }
+
+    fn take_chosen(&mut self) -> Option<usize> {
+        self.chosen.take()
+    }
+}
+
+impl IEvents for Popup {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        painter.fill_rect(self.rect, graphics::WHITE);
+        if let Some(index) = self.highlighted {
+            if index >= self.first_visible && index < self.first_visible + self.visible_count {
+                let y = self.rect.y + (index - self.first_visible) as f32 * self.item_height;
+                painter.fill_rect(
+                    Rect::new(self.rect.x, y, self.rect.w, self.item_height),
+                    HIGHLIGHT_COLOR,
+                );
+            }
+        }
+        painter.stroke_rect(self.rect, 1., graphics::BLACK);
+        let visible = self.first_visible..self.first_visible + self.visible_count;
+        for (n, index) in visible.enumerate() {
+            let y = self.rect.y + n as f32 * self.item_height;
+            let rect = Rect::new(self.rect.x, y, self.rect.w, self.item_height);
+            painter.text(
+                rect,
+                self.items[index].as_str(),
+                Align::Left,
+                graphics::BLACK,
+            );
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button == MouseButton::Left {
+            if let Some(index) = self.item_at(x, y) {
+                self.highlight(index);
+            }
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button == MouseButton::Left {
+            if let Some(index) = self.item_at(x, y) {
+                self.chosen = Some(index);
+            }
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+        if let Some(index) = self.item_at(x, y) {
+            self.highlight(index);
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, y: f32) {
+        if self.hovered && y != 0. {
+            self.scroll(if y > 0. { -1 } else { 1 });
+        }
+    }
+}
+
+impl ILayout for Popup {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Popup {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a> IOverlays<'a> for Popup {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+/// Shows the selected item and opens the list of all items in a popup
+/// above other widgets. Selection can be changed by mouse, by arrow keys
+/// or by typing the beginning of the item text.
+pub struct ComboBox<'a> {
+    items: Vec<String>,
+    selected: Option<usize>,
+    max_visible: usize,
+    rect: Rect,
+    focused: bool,
+    open: bool,
+    popup: Rc<RefCell<Popup>>,
+    search: String,
+    last_search: Duration,
+    on_selected_handlers: Vec<IndexHandler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> ComboBox<'a> {
+    fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            selected: None,
+            max_visible: 8,
+            rect: Rect::zero(),
+            focused: false,
+            open: false,
+            popup: Rc::new(RefCell::new(Popup::new())),
+            search: String::new(),
+            last_search: Duration::from_secs(0),
+            on_selected_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_selected(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, usize) + 'a,
+    ) -> IndexHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_selected_rc(rc.clone());
+        rc
+    }
+    pub fn on_selected_rc(&mut self, handler: IndexHandler<'a, Self>) {
+        self.on_selected_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_selected_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_selected(&mut self, index: usize) {
+        for h in &self.on_selected_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone(), index)));
+        }
+    }
+
+    pub fn items(&self) -> &[String] {
+        self.items.as_slice()
+    }
+
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected = self.selected.filter(|s| *s < self.items.len());
+        self.close();
+    }
+
+    pub fn add_item<S: Into<String>>(&mut self, item: S) -> &mut Self {
+        self.items.push(item.into());
+        self
+    }
+
+    pub fn get_selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<&str> {
+        self.selected.map(|s| self.items[s].as_str())
+    }
+
+    /// Changes selection without firing `on_selected` handlers
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|s| *s < self.items.len());
+    }
+
+    pub fn set_max_visible(&mut self, max_visible: usize) -> &mut Self {
+        self.max_visible = max_visible.max(1);
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        let mut popup = self.popup.borrow_mut();
+        popup.open(self.items.clone(), self.selected, self.max_visible);
+        popup.place(self.rect);
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    fn choose(&mut self, index: usize) {
+        if index < self.items.len() && self.selected != Some(index) {
+            self.selected = Some(index);
+            self.fire_on_selected(index);
+        }
+    }
+
+    // Index of the item which keyboard navigation moves from
+    fn current(&self) -> Option<usize> {
+        if self.open {
+            self.popup.borrow().highlighted
+        } else {
+            self.selected
+        }
+    }
+
+    fn go_to(&mut self, index: usize) {
+        if self.open {
+            self.popup.borrow_mut().highlight(index);
+        } else {
+            self.choose(index);
+        }
+    }
+
+    fn step(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let last = self.items.len() as isize - 1;
+        let index = match self.current() {
+            Some(current) => (current as isize + delta).max(0).min(last),
+            None if delta > 0 => 0,
+            None => last,
+        };
+        self.go_to(index as usize);
+    }
+
+    fn key_down(&mut self, keycode: KeyCode) {
+        let page = self.max_visible as isize;
+        match keycode {
+            KeyCode::Up => self.step(-1),
+            KeyCode::Down => self.step(1),
+            KeyCode::PageUp => self.step(-page),
+            KeyCode::PageDown => self.step(page),
+            KeyCode::Home => self.step(-(self.items.len() as isize)),
+            KeyCode::End => self.step(self.items.len() as isize),
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space if self.open => {
+                let highlighted = self.popup.borrow().highlighted;
+                if let Some(index) = highlighted {
+                    self.choose(index);
+                }
+                self.close();
+            }
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => self.open(),
+            KeyCode::Escape => self.close(),
+            _ => {}
+        }
+    }
+
+    // Type-to-search: jump to the first item starting with the typed text,
+    // looking from the current item so that repeating one letter cycles
+    // through all items beginning with it
+    fn type_char(&mut self, c: char, now: Duration) {
+        if c.is_control() || (c == ' ' && self.search.is_empty()) {
+            return;
+        }
+        if now.checked_sub(self.last_search).unwrap_or_default() > SEARCH_TIMEOUT {
+            self.search.clear();
+        }
+        self.last_search = now;
+        self.search.extend(c.to_lowercase());
+        let repeated = self.search.chars().all(|sc| self.search.starts_with(sc));
+        let pattern = if repeated {
+            self.search.chars().take(1).collect()
+        } else {
+            self.search.clone()
+        };
+        let count = self.items.len();
+        let start = match self.current() {
+            Some(current) if repeated => current + 1,
+            Some(current) => current,
+            None => 0,
+        };
+        let found = (0..count)
+            .map(|n| (start + n) % count)
+            .find(|i| self.items[*i].to_lowercase().starts_with(&pattern));
+        if let Some(index) = found {
+            self.go_to(index);
+        }
+    }
+}
+
+impl IEvents for ComboBox<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.popup.borrow_mut().set_screen(ctx.drawable_size());
+        let chosen = self.popup.borrow_mut().take_chosen();
+        if let Some(index) = chosen {
+            self.choose(index);
+            self.close();
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        let margin = 5.;
+        let mut rect = self.rect;
+        rect.x += margin;
+        rect.y += margin;
+        rect.w -= margin * 2.;
+        rect.h -= margin * 2.;
+        let arrow = (rect.h / 2.).min(rect.w / 4.);
+        let ax = rect.x + rect.w - arrow * 1.5;
+        let ay = rect.y + (rect.h - arrow / 2.) / 2.;
+        painter.fill_rect(rect, graphics::WHITE);
+        if self.focused {
+            painter.stroke_rect(rect, 2., HIGHLIGHT_COLOR);
+        }
+        if arrow > 0. {
+            painter.fill_polygon(
+                &[
+                    [ax, ay],
+                    [ax + arrow, ay],
+                    [ax + arrow / 2., ay + arrow / 2.],
+                ],
+                graphics::BLACK,
+            );
+        }
+        if let Some(label) = self.selected_item() {
+            rect.w -= arrow * 2.;
+            painter.text(rect, label, Align::Left, graphics::BLACK);
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        // Clicks inside the open popup don't come here: WindowManager
+        // passes them only to overlays
+        if self.rect.contains([x, y]) {
+            self.focused = true;
+            if button == MouseButton::Left {
+                if self.open {
+                    self.close()
+                } else {
+                    self.open()
+                }
+            }
+        } else {
+            self.focused = false;
+            self.close();
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(keycode)
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        if self.focused {
+            self.type_char(character, ctx.time_since_start())
+        }
+    }
+}
+
+impl ILayout for ComboBox<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.popup.borrow_mut().place(rect);
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for ComboBox<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for ComboBox<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        if self.open {
+            vec![self.popup.clone()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct Builder<'a> {
+    combobox: ComboBox<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            combobox: ComboBox::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<ComboBox<'a>>> {
+        let rc = Rc::new(RefCell::new(self.combobox));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn add_item<S: Into<String>>(mut self, item: S) -> Self {
+        self.combobox.add_item(item);
+        self
+    }
+
+    pub fn set_selected(mut self, selected: Option<usize>) -> Self {
+        self.combobox.set_selected(selected);
+        self
+    }
+
+    pub fn set_max_visible(mut self, max_visible: usize) -> Self {
+        self.combobox.set_max_visible(max_visible);
+        self
+    }
+
+    pub fn on_selected(mut self, handler: impl Fn(Rc<RefCell<ComboBox<'a>>>, usize) + 'a) -> Self {
+        self.combobox.on_selected(handler);
+        self
+    }
+}
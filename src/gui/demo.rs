@@ -0,0 +1,108 @@
+use crate::gui;
+use crate::gui::button::{Backend, Frontend};
+use crate::gui::checkbox::CheckboxBuilder;
+use crate::gui::panel::Panel;
+use crate::gui::radio_group::{RadioGroup, RadioGroupBuilder};
+use crate::gui::window_manager::WindowManager;
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Button<'a> = Frontend<'a, Backend<'a>>;
+
+/// Row of radios with buttons which add and remove them. The demo binary
+/// shows it and UI tests drive it through `Harness`.
+pub struct RadioPanel<'a> {
+    panel: Rc<RefCell<Panel<'a>>>,
+    radio_group: Rc<RefCell<RadioGroup<'a>>>,
+    add: Rc<RefCell<Button<'a>>>,
+    remove: Rc<RefCell<Button<'a>>>,
+}
+
+impl<'a> RadioPanel<'a> {
+    /// Builds the panel with three radios and adds it as a full screen window
+    pub fn new(wm: &mut WindowManager<'a>) -> Self {
+        let radio_a = CheckboxBuilder::new().build();
+        let radio_b = CheckboxBuilder::new().build();
+        let radio_c = CheckboxBuilder::new().build();
+
+        let radio_group = RadioGroupBuilder::new()
+            .add_widget(radio_a.clone())
+            .add_widget(radio_b.clone())
+            .add_widget(radio_c.clone())
+            .build();
+
+        let radio_ribbon = gui::row()
+            .add_widget(radio_a)
+            .add_widget(radio_b)
+            .add_widget(radio_c)
+            .build();
+
+        let add_radio = {
+            let radio_group = radio_group.clone();
+            let radio_ribbon = radio_ribbon.clone();
+            move |_| {
+                let radio = CheckboxBuilder::new().build();
+                radio_group.borrow_mut().add_widget(radio.clone());
+                radio_ribbon.borrow_mut().add_widget(radio.clone());
+            }
+        };
+
+        let remove_radio = {
+            let radio_group = radio_group.clone();
+            let radio_ribbon = radio_ribbon.clone();
+            move |_| {
+                let radio = radio_group.borrow().radios().last().cloned();
+                if let Some(radio) = radio {
+                    radio_group.borrow_mut().remove_widget(radio.clone());
+                    radio_ribbon.borrow_mut().remove_widget(radio.clone());
+                }
+            }
+        };
+
+        let add = gui::button().set_label("Add").on_click(add_radio).build();
+        let remove = gui::button()
+            .set_label("Remove")
+            .on_click(remove_radio)
+            .build();
+
+        let panel = gui::panel()
+            .add_widget(
+                gui::column()
+                    .add_widget(radio_ribbon.clone())
+                    .add_widget(
+                        gui::row()
+                            .add_widget(add.clone())
+                            .add_widget(remove.clone())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        wm.add_window(panel.clone(), Rect::zero(), true);
+
+        Self {
+            panel,
+            radio_group,
+            add,
+            remove,
+        }
+    }
+
+    pub fn panel(&self) -> Rc<RefCell<Panel<'a>>> {
+        self.panel.clone()
+    }
+
+    pub fn radio_group(&self) -> Rc<RefCell<RadioGroup<'a>>> {
+        self.radio_group.clone()
+    }
+
+    pub fn add_button(&self) -> Rc<RefCell<Button<'a>>> {
+        self.add.clone()
+    }
+
+    pub fn remove_button(&self) -> Rc<RefCell<Button<'a>>> {
+        self.remove.clone()
+    }
+}
@@ -0,0 +1,352 @@
+use crate::gui::{draw_label, IActions, Widget};
+use ggez::graphics::{self, Align, Color, DrawMode, DrawParam, MeshBuilder, Rect, Text};
+use ggez::{Context, GameResult};
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+const PADDING: f32 = 5.;
+// Preview is placed below and right of the pointer so that the cursor
+// doesn't cover it
+const POINTER_OFFSET: f32 = 12.;
+const ACCEPT_COLOR: Color = Color {
+    r: 0.2,
+    g: 0.6,
+    b: 0.2,
+    a: 1.,
+};
+
+/// Value carried by a drag. Clones share the value.
+#[derive(Clone)]
+pub struct Payload {
+    data: Rc<dyn Any>,
+}
+
+impl Payload {
+    pub fn new<T: Any>(value: T) -> Self {
+        Self {
+            data: Rc::new(value),
+        }
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.data.is::<T>()
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.data.downcast_ref::<T>()
+    }
+}
+
+/// What is drawn under the pointer during a drag
+pub enum Preview<'a> {
+    None,
+    Label(String),
+    /// Widget drawn with the given width and height. It shouldn't belong to
+    /// any window since its rect is set by the preview.
+    Widget(Rc<RefCell<dyn Widget<'a> + 'a>>, f32, f32),
+}
+
+pub type AcceptHandler<'a> = Rc<dyn Fn(&Payload) -> bool + 'a>;
+
+pub type DropHandler<'a> = Rc<dyn Fn(&Payload, f32, f32) + 'a>;
+
+/// Callbacks of a widget which takes drops. Targets which don't accept the
+/// payload are ignored during the drag, they get no callbacks at all.
+#[derive(Clone)]
+pub struct DropTarget<'a> {
+    accept: Option<AcceptHandler<'a>>,
+    on_enter: Option<DropHandler<'a>>,
+    on_over: Option<DropHandler<'a>>,
+    on_leave: Option<DropHandler<'a>>,
+    on_drop: Option<DropHandler<'a>>,
+}
+
+impl<'a> DropTarget<'a> {
+    /// Target which accepts any payload
+    pub fn new() -> Self {
+        Self {
+            accept: None,
+            on_enter: None,
+            on_over: None,
+            on_leave: None,
+            on_drop: None,
+        }
+    }
+
+    /// Accepts only payloads of type `T`
+    pub fn accept<T: Any>(self) -> Self {
+        self.accept_if(|payload| payload.is::<T>())
+    }
+
+    pub fn accept_if(mut self, accept: impl Fn(&Payload) -> bool + 'a) -> Self {
+        self.accept = Some(Rc::new(accept));
+        self
+    }
+
+    pub fn on_enter(mut self, handler: impl Fn(&Payload, f32, f32) + 'a) -> Self {
+        self.on_enter = Some(Rc::new(handler));
+        self
+    }
+
+    /// Called on each pointer motion over the target, including the first one
+    pub fn on_over(mut self, handler: impl Fn(&Payload, f32, f32) + 'a) -> Self {
+        self.on_over = Some(Rc::new(handler));
+        self
+    }
+
+    /// Called when the pointer leaves the target or the drag is cancelled
+    pub fn on_leave(mut self, handler: impl Fn(&Payload, f32, f32) + 'a) -> Self {
+        self.on_leave = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn on_drop(mut self, handler: impl Fn(&Payload, f32, f32) + 'a) -> Self {
+        self.on_drop = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn accepts(&self, payload: &Payload) -> bool {
+        match self.accept {
+            Some(ref accept) => accept(payload),
+            None => true,
+        }
+    }
+}
+
+struct Entry<'a> {
+    widget: Weak<RefCell<dyn Widget<'a> + 'a>>,
+    target: DropTarget<'a>,
+}
+
+struct Drag<'a> {
+    payload: Payload,
+    preview: Preview<'a>,
+    hovered: Option<usize>,
+}
+
+/// Drop targets of widgets and the drag in progress. `WindowManager` owns
+/// it, passes pointer events to it and draws the preview above everything.
+/// Widgets start drags through the shared handle returned by
+/// `WindowManager::drag_drop`. Callbacks are fired through `IActions`.
+pub struct DragDrop<'a> {
+    entries: Vec<Entry<'a>>,
+    drag: Option<Drag<'a>>,
+    pointer: [f32; 2],
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+}
+
+impl<'a> DragDrop<'a> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            drag: None,
+            pointer: [0., 0.],
+            pending_handlers: Vec::new(),
+        }
+    }
+
+    /// Replaces the target if the widget already has one. Only a weak
+    /// reference is kept, so targets of dropped widgets disappear.
+    pub fn set_drop_target(
+        &mut self,
+        widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+        target: DropTarget<'a>,
+    ) {
+        match self.find(&widget) {
+            Some(index) => self.entries[index].target = target,
+            None => self.entries.push(Entry {
+                widget: Rc::downgrade(&widget),
+                target,
+            }),
+        }
+    }
+
+    pub fn remove_drop_target(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) {
+        let index = match self.find(&widget) {
+            Some(index) => index,
+            None => return,
+        };
+        let hovered = self.drag.as_ref().and_then(|d| d.hovered);
+        if hovered == Some(index) {
+            self.leave();
+        }
+        self.entries.remove(index);
+        // Later entries moved down by one
+        if let Some(ref mut drag) = self.drag {
+            if let Some(ref mut hovered) = drag.hovered {
+                if *hovered > index {
+                    *hovered -= 1;
+                }
+            }
+        }
+    }
+
+    fn find(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.widget.as_ptr() as *const () == Rc::as_ptr(widget) as *const ())
+    }
+
+    /// Starts drag at the current pointer position, a drag in progress
+    /// is cancelled
+    pub fn start_drag(&mut self, payload: Payload, preview: Preview<'a>) {
+        self.cancel_drag();
+        self.drag = Some(Drag {
+            payload,
+            preview,
+            hovered: None,
+        });
+        let [x, y] = self.pointer;
+        self.pointer_moved(x, y, false);
+    }
+
+    pub fn cancel_drag(&mut self) {
+        self.leave();
+        self.drag = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    pub fn payload(&self) -> Option<&Payload> {
+        self.drag.as_ref().map(|d| &d.payload)
+    }
+
+    /// Widget which would take the drop at the current pointer position
+    pub fn target(&self) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.drag
+            .as_ref()
+            .and_then(|d| d.hovered)
+            .and_then(|index| self.entries[index].widget.upgrade())
+    }
+
+    // The innermost target under the point which accepts the payload
+    fn entry_at(&self, payload: &Payload, point: [f32; 2]) -> Option<usize> {
+        let area = |r: Rect| r.w * r.h;
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.target.accepts(payload))
+            .filter_map(|(index, e)| e.widget.upgrade().map(|w| (index, w.borrow().get_rect())))
+            .filter(|(_, rect)| rect.contains(point))
+            .min_by(|(_, a), (_, b)| area(*a).total_cmp(&area(*b)))
+            .map(|(index, _)| index)
+    }
+
+    fn fire(&mut self, handler: &Option<DropHandler<'a>>, payload: &Payload) {
+        if let Some(ref handler) = handler {
+            let handler = handler.clone();
+            let payload = payload.clone();
+            let [x, y] = self.pointer;
+            self.pending_handlers
+                .push(Rc::new(move || handler(&payload, x, y)));
+        }
+    }
+
+    fn leave(&mut self) {
+        let (index, payload) = match self.drag {
+            Some(ref mut drag) => match drag.hovered.take() {
+                Some(index) => (index, drag.payload.clone()),
+                None => return,
+            },
+            None => return,
+        };
+        let handler = self.entries[index].target.on_leave.clone();
+        self.fire(&handler, &payload);
+    }
+
+    /// Moves the drag to the target under the pointer. Pointer over an
+    /// overlay is `blocked` from the targets below it.
+    pub fn pointer_moved(&mut self, x: f32, y: f32, blocked: bool) {
+        self.pointer = [x, y];
+        let count = self.entries.len();
+        self.entries.retain(|e| e.widget.strong_count() > 0);
+        if self.entries.len() != count {
+            if let Some(ref mut drag) = self.drag {
+                drag.hovered = None;
+            }
+        }
+        let (payload, hovered) = match self.drag {
+            Some(ref drag) => (drag.payload.clone(), drag.hovered),
+            None => return,
+        };
+        let target = if blocked {
+            None
+        } else {
+            self.entry_at(&payload, [x, y])
+        };
+        if target != hovered {
+            self.leave();
+            if let Some(index) = target {
+                let handler = self.entries[index].target.on_enter.clone();
+                self.fire(&handler, &payload);
+            }
+            if let Some(ref mut drag) = self.drag {
+                drag.hovered = target;
+            }
+        }
+        if let Some(index) = target {
+            let handler = self.entries[index].target.on_over.clone();
+            self.fire(&handler, &payload);
+        }
+    }
+
+    /// Drops the payload on the target under the pointer. Returns false if
+    /// there is no drag in progress.
+    pub fn pointer_released(&mut self, x: f32, y: f32, blocked: bool) -> bool {
+        if self.drag.is_none() {
+            return false;
+        }
+        self.pointer_moved(x, y, blocked);
+        if let Some(drag) = self.drag.take() {
+            if let Some(index) = drag.hovered {
+                let handler = self.entries[index].target.on_drop.clone();
+                self.fire(&handler, &drag.payload);
+            }
+        }
+        true
+    }
+
+    /// Draws the preview near the pointer
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let drag = match self.drag {
+            Some(ref drag) => drag,
+            None => return Ok(()),
+        };
+        let x = self.pointer[0] + POINTER_OFFSET;
+        let y = self.pointer[1] + POINTER_OFFSET;
+        match drag.preview {
+            Preview::None => Ok(()),
+            Preview::Label(ref label) => {
+                let text = Text::new(label.as_str());
+                let w = text.width(ctx) as f32 + PADDING * 4.;
+                let h = text.height(ctx) as f32 + PADDING * 2.;
+                let rect = Rect::new(x, y, w, h);
+                let border = if drag.hovered.is_some() {
+                    ACCEPT_COLOR
+                } else {
+                    graphics::BLACK
+                };
+                let mut mb = MeshBuilder::new();
+                mb.rectangle(DrawMode::fill(), rect, graphics::WHITE);
+                mb.rectangle(DrawMode::stroke(2.), rect, border);
+                let mesh = mb.build(ctx)?;
+                graphics::draw(ctx, &mesh, DrawParam::default())?;
+                draw_label(ctx, rect, label, Align::Center)
+            }
+            Preview::Widget(ref widget, w, h) => {
+                let mut widget = widget.borrow_mut();
+                widget.set_rect(Rect::new(x, y, w, h));
+                widget.draw(ctx)
+            }
+        }
+    }
+}
+
+impl<'a> IActions<'a> for DragDrop<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
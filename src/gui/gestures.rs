@@ -0,0 +1,534 @@
+use crate::gui::capture::PointerCapture;
+use crate::gui::painter::Painter;
+use crate::gui::{
+    collect_hovered_child, is_same, IActions, IContext, IEvents, ILayout, IOverlays, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::iter;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+/// Pointer gesture recognized from raw mouse events. Points are in
+/// screen coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// Press and release without moving, left or middle button
+    Click {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// Second click of the same button in place and in time. The first
+    /// click is reported as `Click` before it.
+    DoubleClick {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// Press and release of the right button without moving
+    RightClick {
+        x: f32,
+        y: f32,
+    },
+    /// Left button held without moving. No click follows on release.
+    LongPress {
+        x: f32,
+        y: f32,
+    },
+    /// Pointer moved with the button held; the point is where it was pressed
+    DragStart {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// Pointer position and offset from the drag start
+    DragMove {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    },
+    DragEnd {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// Wheel scroll at the pointer position
+    Wheel {
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    },
+}
+
+/// Timing and distance thresholds of the recognizer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureSettings {
+    pub double_click_time: Duration,
+    pub double_click_distance: f32,
+    pub long_press_time: Duration,
+    /// Pointer moved farther than this while pressed starts a drag
+    pub drag_threshold: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            double_click_time: Duration::from_millis(400),
+            double_click_distance: 4.,
+            long_press_time: Duration::from_millis(600),
+            drag_threshold: 4.,
+        }
+    }
+}
+
+struct Press {
+    button: MouseButton,
+    x: f32,
+    y: f32,
+    at: Duration,
+    dragging: bool,
+    long_pressed: bool,
+}
+
+struct LastClick {
+    button: MouseButton,
+    x: f32,
+    y: f32,
+    at: Duration,
+}
+
+fn distance(x0: f32, y0: f32, x1: f32, y1: f32) -> f32 {
+    ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt()
+}
+
+/// Turns mouse events into gestures. Time is passed in, so the recognizer
+/// doesn't need the ggez context. Only one button is tracked at a time,
+/// presses of other buttons while it is held are ignored.
+pub struct GestureRecognizer {
+    settings: GestureSettings,
+    press: Option<Press>,
+    last_click: Option<LastClick>,
+    pointer: [f32; 2],
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            settings: GestureSettings::default(),
+            press: None,
+            last_click: None,
+            pointer: [0., 0.],
+        }
+    }
+
+    pub fn get_settings(&self) -> GestureSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: GestureSettings) {
+        self.settings = settings;
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.press.is_some()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        match self.press {
+            Some(ref press) => press.dragging,
+            None => false,
+        }
+    }
+
+    /// Forgets the pressed button; a drag in progress is ended
+    pub fn cancel(&mut self) -> Vec<Gesture> {
+        match self.press.take() {
+            Some(ref press) if press.dragging => vec![Gesture::DragEnd {
+                button: press.button,
+                x: self.pointer[0],
+                y: self.pointer[1],
+            }],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn mouse_down(
+        &mut self,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+        now: Duration,
+    ) -> Vec<Gesture> {
+        self.pointer = [x, y];
+        if self.press.is_none() {
+            self.press = Some(Press {
+                button,
+                x,
+                y,
+                at: now,
+                dragging: false,
+                long_pressed: false,
+            });
+        }
+        Vec::new()
+    }
+
+    pub fn mouse_motion(&mut self, x: f32, y: f32) -> Vec<Gesture> {
+        self.pointer = [x, y];
+        let threshold = self.settings.drag_threshold;
+        let press = match self.press {
+            Some(ref mut press) => press,
+            None => return Vec::new(),
+        };
+        let mut gestures = Vec::new();
+        if !press.dragging && distance(press.x, press.y, x, y) > threshold {
+            press.dragging = true;
+            gestures.push(Gesture::DragStart {
+                button: press.button,
+                x: press.x,
+                y: press.y,
+            });
+        }
+        if press.dragging {
+            gestures.push(Gesture::DragMove {
+                button: press.button,
+                x,
+                y,
+                dx: x - press.x,
+                dy: y - press.y,
+            });
+        }
+        gestures
+    }
+
+    pub fn mouse_up(&mut self, button: MouseButton, x: f32, y: f32, now: Duration) -> Vec<Gesture> {
+        self.pointer = [x, y];
+        let press = match self.press.take() {
+            Some(press) if press.button == button => press,
+            other => {
+                self.press = other;
+                return Vec::new();
+            }
+        };
+        if press.dragging {
+            self.last_click = None;
+            return vec![Gesture::DragEnd { button, x, y }];
+        }
+        if press.long_pressed {
+            self.last_click = None;
+            return Vec::new();
+        }
+        if button == MouseButton::Right {
+            self.last_click = None;
+            return vec![Gesture::RightClick { x, y }];
+        }
+        let settings = self.settings;
+        let double = match self.last_click {
+            Some(ref last) => {
+                last.button == button
+                    && now.checked_sub(last.at).unwrap_or_default() <= settings.double_click_time
+                    && distance(last.x, last.y, x, y) <= settings.double_click_distance
+            }
+            None => false,
+        };
+        if double {
+            self.last_click = None;
+            vec![Gesture::DoubleClick { button, x, y }]
+        } else {
+            self.last_click = Some(LastClick {
+                button,
+                x,
+                y,
+                at: now,
+            });
+            vec![Gesture::Click { button, x, y }]
+        }
+    }
+
+    pub fn wheel(&mut self, dx: f32, dy: f32) -> Vec<Gesture> {
+        vec![Gesture::Wheel {
+            x: self.pointer[0],
+            y: self.pointer[1],
+            dx,
+            dy,
+        }]
+    }
+
+    /// Reports long press when the left button is held long enough
+    pub fn update(&mut self, now: Duration) -> Vec<Gesture> {
+        let long_press_time = self.settings.long_press_time;
+        match self.press {
+            Some(ref mut press)
+                if press.button == MouseButton::Left
+                    && !press.dragging
+                    && !press.long_pressed
+                    && now.checked_sub(press.at).unwrap_or_default() >= long_press_time =>
+            {
+                press.long_pressed = true;
+                vec![Gesture::LongPress {
+                    x: press.x,
+                    y: press.y,
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub type GestureHandler<'a, T> = Rc<dyn Fn(Rc<RefCell<T>>, Gesture) + 'a>;
+
+/// Wraps a widget and reports gestures which start on it. Events are
+/// passed to the wrapped widget unchanged.
+pub struct GestureArea<'a> {
+    widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+    recognizer: GestureRecognizer,
+    hovered: bool,
+    // Wrapped widget under the press, it is the only child
+    capture: PointerCapture,
+    on_gesture_handlers: Vec<GestureHandler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> GestureArea<'a> {
+    pub fn new(widget: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Rc<RefCell<Self>> {
+        let rc = Rc::new(RefCell::new(Self {
+            widget,
+            recognizer: GestureRecognizer::new(),
+            hovered: false,
+            capture: PointerCapture::new(),
+            on_gesture_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn get_widget(&self) -> Rc<RefCell<dyn Widget<'a> + 'a>> {
+        self.widget.clone()
+    }
+
+    pub fn recognizer(&self) -> &GestureRecognizer {
+        &self.recognizer
+    }
+
+    pub fn set_settings(&mut self, settings: GestureSettings) -> &mut Self {
+        self.recognizer.set_settings(settings);
+        self
+    }
+
+    /// Handler gets all gestures
+    pub fn on_gesture(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, Gesture) + 'a,
+    ) -> GestureHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_gesture_rc(rc.clone());
+        rc
+    }
+    pub fn on_gesture_rc(&mut self, handler: GestureHandler<'a, Self>) {
+        self.on_gesture_handlers.push(handler);
+    }
+    pub fn on_click(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, f32, f32) + 'a,
+    ) -> GestureHandler<'a, Self> {
+        self.on_gesture(move |rcself, gesture| {
+            if let Gesture::Click {
+                button: MouseButton::Left,
+                x,
+                y,
+            } = gesture
+            {
+                handler(rcself, x, y)
+            }
+        })
+    }
+    pub fn on_double_click(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, f32, f32) + 'a,
+    ) -> GestureHandler<'a, Self> {
+        self.on_gesture(move |rcself, gesture| {
+            if let Gesture::DoubleClick {
+                button: MouseButton::Left,
+                x,
+                y,
+            } = gesture
+            {
+                handler(rcself, x, y)
+            }
+        })
+    }
+    pub fn on_right_click(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, f32, f32) + 'a,
+    ) -> GestureHandler<'a, Self> {
+        self.on_gesture(move |rcself, gesture| {
+            if let Gesture::RightClick { x, y } = gesture {
+                handler(rcself, x, y)
+            }
+        })
+    }
+    pub fn on_long_press(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, f32, f32) + 'a,
+    ) -> GestureHandler<'a, Self> {
+        self.on_gesture(move |rcself, gesture| {
+            if let Gesture::LongPress { x, y } = gesture {
+                handler(rcself, x, y)
+            }
+        })
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_gesture_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_gestures(&mut self, gestures: Vec<Gesture>) {
+        for gesture in gestures {
+            for h in &self.on_gesture_handlers {
+                let rcself = self.rcself();
+                let hc = h.clone();
+                self.pending_handlers
+                    .push(Rc::new(move || hc(rcself.clone(), gesture)));
+            }
+        }
+    }
+}
+
+impl IEvents for GestureArea<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        let gestures = self.recognizer.update(ctx.time_since_start());
+        self.fire_gestures(gestures);
+        self.widget.borrow_mut().update(ctx)
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.widget.borrow_mut().draw(ctx)
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.widget.borrow_mut().paint_into(ctx, painter)
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if self.get_rect().contains([x, y]) {
+            let now = ctx.time_since_start();
+            let gestures = self.recognizer.mouse_down(button, x, y, now);
+            self.fire_gestures(gestures);
+        }
+        let rect = self.widget.borrow().get_rect();
+        self.capture.press(button, x, y, Some(rect));
+        self.widget
+            .borrow_mut()
+            .mouse_button_down_event(ctx, button, x, y)
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        let now = ctx.time_since_start();
+        let gestures = self.recognizer.mouse_up(button, x, y, now);
+        self.fire_gestures(gestures);
+        if self.capture.routes_to(0) {
+            self.widget
+                .borrow_mut()
+                .mouse_button_up_event(ctx, button, x, y)
+        }
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        self.hovered = self.get_rect().contains([x, y]);
+        let gestures = self.recognizer.mouse_motion(x, y);
+        self.fire_gestures(gestures);
+        if self.capture.routes_to(0) {
+            self.widget
+                .borrow_mut()
+                .mouse_motion_event(ctx, x, y, dx, dy)
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        if self.hovered {
+            let gestures = self.recognizer.wheel(x, y);
+            self.fire_gestures(gestures);
+        }
+        self.widget.borrow_mut().mouse_wheel_event(ctx, x, y)
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        self.widget
+            .borrow_mut()
+            .key_down_event(ctx, keycode, keymods, repeat)
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        self.widget.borrow_mut().key_up_event(ctx, keycode, keymods)
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        self.widget.borrow_mut().text_input_event(ctx, character)
+    }
+}
+
+impl ILayout for GestureArea<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.widget.borrow_mut().set_rect(rect)
+    }
+    fn get_rect(&self) -> Rect {
+        self.widget.borrow().get_rect()
+    }
+    fn get_preferred_size(&self) -> (Option<f32>, Option<f32>) {
+        self.widget.borrow().get_preferred_size()
+    }
+    fn take_layout_changed(&mut self) -> bool {
+        self.widget.borrow_mut().take_layout_changed()
+    }
+}
+
+impl<'a> IActions<'a> for GestureArea<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        let mut v: Vec<_> = self.pending_handlers.drain(..).collect();
+        v.append(&mut self.widget.borrow_mut().collect_fired());
+        v
+    }
+}
+
+impl<'a> IOverlays<'a> for GestureArea<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.widget.borrow_mut().collect_overlays()
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(iter::once(&self.widget), x, y, hovered)
+    }
+}
@@ -0,0 +1,419 @@
+use crate::gui::capture::PointerCapture;
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    collect_hovered_child, is_same, Handler, IActions, ICheckbox, IContext, IEvents, ILayout,
+    IOverlays, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+const HEADER_HEIGHT: f32 = 24.;
+
+/// Titled section around a single content widget. Clicking the header
+/// collapses the section to the header alone; the changed preferred
+/// height is reported to the parent `Ribbon`, which lays out again.
+/// As `ICheckbox` (checked when expanded) group boxes can be put into
+/// a `RadioGroup` to make an accordion.
+pub struct GroupBox<'a> {
+    title: String,
+    content: Option<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    content_height: Option<f32>,
+    collapsed: bool,
+    collapsible: bool,
+    touched: bool,
+    // Content under the press, it is the only child
+    capture: PointerCapture,
+    layout_changed: bool,
+    rect: Rect,
+    on_toggled_handlers: Vec<Handler<'a, Self>>,
+    on_changed_icheckbox_handlers: Vec<Rc<dyn Fn(Rc<RefCell<dyn ICheckbox<'a> + 'a>>) + 'a>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> GroupBox<'a> {
+    fn new() -> Self {
+        Self {
+            title: String::new(),
+            content: None,
+            content_height: None,
+            collapsed: false,
+            collapsible: true,
+            touched: false,
+            capture: PointerCapture::new(),
+            layout_changed: false,
+            rect: Rect::zero(),
+            on_toggled_handlers: Vec::new(),
+            on_changed_icheckbox_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    /// Handler is called when the user collapses or expands the section
+    pub fn on_toggled(&mut self, handler: impl Fn(Rc<RefCell<Self>>) + 'a) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_toggled_rc(rc.clone());
+        rc
+    }
+    pub fn on_toggled_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_toggled_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_toggled_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_toggled(&mut self) {
+        for h in &self.on_toggled_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+        for h in &self.on_changed_icheckbox_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    pub fn get_title(&self) -> String {
+        self.title.clone()
+    }
+
+    pub fn set_title<S: Into<String>>(&mut self, title: S) -> &mut Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn get_content(&self) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.content.clone()
+    }
+
+    pub fn set_content(&mut self, content: Option<Rc<RefCell<dyn Widget<'a> + 'a>>>) -> &mut Self {
+        self.content = content;
+        self.set_rect(self.rect);
+        self
+    }
+
+    /// Preferred height of the content when expanded. With `None` the
+    /// expanded section takes a share of the free space in the ribbon.
+    pub fn set_content_height(&mut self, height: Option<f32>) -> &mut Self {
+        self.content_height = height;
+        self.layout_changed = true;
+        self
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Collapses or expands without firing handlers
+    pub fn set_collapsed(&mut self, collapsed: bool) -> &mut Self {
+        if self.collapsed != collapsed {
+            self.collapsed = collapsed;
+            self.layout_changed = true;
+        }
+        self
+    }
+
+    pub fn is_collapsible(&self) -> bool {
+        self.collapsible
+    }
+
+    /// Non-collapsible group box is a static titled frame
+    pub fn set_collapsible(&mut self, collapsible: bool) -> &mut Self {
+        self.collapsible = collapsible;
+        if !collapsible {
+            self.set_collapsed(false);
+        }
+        self
+    }
+
+    pub fn toggle(&mut self) {
+        if self.collapsible {
+            self.set_collapsed(!self.collapsed);
+            self.fire_on_toggled();
+        }
+    }
+
+    fn header_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x,
+            self.rect.y,
+            self.rect.w,
+            HEADER_HEIGHT.min(self.rect.h),
+        )
+    }
+
+    // Header, border and title, the content draws itself
+    fn paint_frame(&self, painter: &mut Painter) {
+        let header = self.header_rect();
+        painter.fill_rect(header, HIGHLIGHT_COLOR);
+        painter.stroke_rect(self.rect, 1., graphics::WHITE);
+        if self.collapsible {
+            let cx = header.x + HEADER_HEIGHT / 2.;
+            let cy = header.y + header.h / 2.;
+            let points = if self.collapsed {
+                [[cx - 3., cy - 5.], [cx + 4., cy], [cx - 3., cy + 5.]]
+            } else {
+                [[cx - 5., cy - 3.], [cx + 5., cy - 3.], [cx, cy + 4.]]
+            };
+            painter.fill_polygon(&points, graphics::BLACK);
+        }
+        let mut title_rect = header;
+        if self.collapsible {
+            title_rect.x += HEADER_HEIGHT;
+            title_rect.w = (title_rect.w - HEADER_HEIGHT).max(0.);
+        }
+        painter.text(
+            title_rect,
+            self.title.as_str(),
+            Align::Left,
+            graphics::BLACK,
+        );
+    }
+
+    fn content_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x,
+            self.rect.y + HEADER_HEIGHT,
+            self.rect.w,
+            (self.rect.h - HEADER_HEIGHT).max(0.),
+        )
+    }
+
+    // Content gets events only when it is visible
+    fn visible_content(&self) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        if self.collapsed {
+            None
+        } else {
+            self.content.clone()
+        }
+    }
+}
+
+impl<'a> ICheckbox<'a> for GroupBox<'a> {
+    fn get_state(&self) -> bool {
+        !self.collapsed
+    }
+    fn set_state(&mut self, state: bool) {
+        self.set_collapsed(!state);
+    }
+    fn on_changed_rc(&mut self, handler: Rc<dyn Fn(Rc<RefCell<dyn ICheckbox<'a> + 'a>>) + 'a>) {
+        self.on_changed_icheckbox_handlers.push(handler);
+    }
+    fn remove_handler(&mut self, handler: Rc<dyn Fn(Rc<RefCell<dyn ICheckbox<'a> + 'a>>) + 'a>) {
+        self.on_changed_icheckbox_handlers
+            .drain_filter(move |w| is_same(w, &handler))
+            .count();
+    }
+}
+
+impl IEvents for GroupBox<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        match self.content {
+            Some(ref content) => content.borrow_mut().update(ctx),
+            None => Ok(()),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_frame(&mut painter);
+        painter.render(ctx)?;
+        match self.visible_content() {
+            Some(content) => content.borrow_mut().draw(ctx),
+            None => Ok(()),
+        }
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.paint_frame(painter);
+        if let Some(content) = self.visible_content() {
+            content.borrow_mut().paint_into(ctx, painter)
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button == MouseButton::Left && self.header_rect().contains([x, y]) {
+            self.touched = true;
+        }
+        let rects = self.visible_content().map(|c| c.borrow().get_rect());
+        self.capture.press(button, x, y, rects);
+        if let Some(content) = self.visible_content() {
+            content
+                .borrow_mut()
+                .mouse_button_down_event(ctx, button, x, y)
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button == MouseButton::Left {
+            if self.touched && self.header_rect().contains([x, y]) {
+                self.toggle();
+            }
+            self.touched = false;
+        }
+        if let Some(content) = self.visible_content().filter(|_| self.capture.routes_to(0)) {
+            content
+                .borrow_mut()
+                .mouse_button_up_event(ctx, button, x, y)
+        }
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        if let Some(content) = self.visible_content().filter(|_| self.capture.routes_to(0)) {
+            content.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy)
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        if let Some(content) = self.visible_content() {
+            content.borrow_mut().mouse_wheel_event(ctx, x, y)
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        if let Some(content) = self.visible_content() {
+            content
+                .borrow_mut()
+                .key_down_event(ctx, keycode, keymods, repeat)
+        }
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        if let Some(content) = self.visible_content() {
+            content.borrow_mut().key_up_event(ctx, keycode, keymods)
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        if let Some(content) = self.visible_content() {
+            content.borrow_mut().text_input_event(ctx, character)
+        }
+    }
+}
+
+impl ILayout for GroupBox<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        if let Some(ref content) = self.content {
+            content.borrow_mut().set_rect(self.content_rect());
+        }
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+    fn get_preferred_size(&self) -> (Option<f32>, Option<f32>) {
+        if self.collapsed {
+            (None, Some(HEADER_HEIGHT))
+        } else {
+            (None, self.content_height.map(|h| h + HEADER_HEIGHT))
+        }
+    }
+    fn take_layout_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.layout_changed, false)
+    }
+}
+
+impl<'a> IActions<'a> for GroupBox<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        let mut v: Vec<_> = self.pending_handlers.drain(..).collect();
+        if let Some(ref content) = self.content {
+            v.append(&mut content.borrow_mut().collect_fired());
+        }
+        v
+    }
+}
+
+impl<'a> IOverlays<'a> for GroupBox<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        match self.visible_content() {
+            Some(content) => content.borrow_mut().collect_overlays(),
+            None => Vec::new(),
+        }
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(self.visible_content().iter(), x, y, hovered)
+    }
+}
+
+pub struct Builder<'a> {
+    group_box: GroupBox<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            group_box: GroupBox::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<GroupBox<'a>>> {
+        let rc = Rc::new(RefCell::new(self.group_box));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_title<S: Into<String>>(mut self, title: S) -> Self {
+        self.group_box.set_title(title);
+        self
+    }
+
+    pub fn set_content(mut self, content: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Self {
+        self.group_box.set_content(Some(content));
+        self
+    }
+
+    pub fn set_content_height(mut self, height: f32) -> Self {
+        self.group_box.set_content_height(Some(height));
+        self
+    }
+
+    pub fn set_collapsed(mut self, collapsed: bool) -> Self {
+        self.group_box.set_collapsed(collapsed);
+        self
+    }
+
+    pub fn set_collapsible(mut self, collapsible: bool) -> Self {
+        self.group_box.set_collapsible(collapsible);
+        self
+    }
+
+    pub fn on_toggled(mut self, handler: impl Fn(Rc<RefCell<GroupBox<'a>>>) + 'a) -> Self {
+        self.group_box.on_toggled(handler);
+        self
+    }
+}
@@ -0,0 +1,281 @@
+use crate::gui::raster::{self, Raster};
+use crate::gui::recording::Player;
+use crate::gui::touch::TouchPhase;
+use crate::gui::window_manager::WindowManager;
+use crate::gui::{IContext, IEvents, ILayout, Widget};
+use ggez::event::{Axis, Button, KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{Color, Rect, Text};
+use ggez::GameResult;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+/// Advance of each char in text measured by `FakeContext`
+pub const CHAR_WIDTH: f32 = 8.;
+/// Height of each line in text measured by `FakeContext`
+pub const LINE_HEIGHT: f32 = 16.;
+
+/// `IContext` without a window. Time only moves when advanced and text is
+/// measured with a fixed advance per char, so results don't depend on fonts.
+pub struct FakeContext {
+    now: Duration,
+    delta: Duration,
+    keymods: KeyMods,
+    size: (f32, f32),
+}
+
+impl FakeContext {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            now: Duration::from_secs(0),
+            delta: Duration::from_secs(0),
+            keymods: KeyMods::NONE,
+            size: (width, height),
+        }
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.now += delta;
+        self.delta = delta;
+    }
+
+    /// Sets the clock, e.g. to the time of a recorded event
+    pub fn set_time(&mut self, now: Duration, delta: Duration) {
+        self.now = now;
+        self.delta = delta;
+    }
+
+    /// Modifiers reported as held, e.g. for Ctrl+click
+    pub fn set_keymods(&mut self, keymods: KeyMods) {
+        self.keymods = keymods;
+    }
+
+    pub fn set_drawable_size(&mut self, width: f32, height: f32) {
+        self.size = (width, height);
+    }
+}
+
+impl IContext for FakeContext {
+    fn time_since_start(&self) -> Duration {
+        self.now
+    }
+    fn delta(&self) -> Duration {
+        self.delta
+    }
+    fn keymods(&self) -> KeyMods {
+        self.keymods
+    }
+    fn drawable_size(&self) -> (f32, f32) {
+        self.size
+    }
+    fn text_width(&mut self, text: &Text) -> f32 {
+        let contents = text.contents();
+        let longest = contents.lines().map(|l| l.chars().count()).max();
+        longest.unwrap_or(0) as f32 * CHAR_WIDTH
+    }
+    fn text_height(&mut self, text: &Text) -> f32 {
+        text.contents().lines().count().max(1) as f32 * LINE_HEIGHT
+    }
+}
+
+/// Runs a widget tree in a `WindowManager` against `FakeContext`, so that
+/// widgets are tested without a window. Input is injected as high level
+/// actions, `frame` runs `update` and the fired handlers. Widgets are found
+/// by the ids given to them with `set_id`.
+///
+/// Actions don't advance frames by themselves: handlers fired by a click
+/// run on the next `frame`.
+pub struct Harness<'a> {
+    window_manager: WindowManager<'a>,
+    ctx: FakeContext,
+    ids: HashMap<String, Weak<RefCell<dyn Widget<'a> + 'a>>>,
+    pointer: [f32; 2],
+}
+
+impl<'a> Harness<'a> {
+    pub fn new(width: f32, height: f32) -> Self {
+        let mut window_manager = WindowManager::new();
+        window_manager.set_rect(Rect::new(0., 0., width, height));
+        Self {
+            window_manager,
+            ctx: FakeContext::new(width, height),
+            ids: HashMap::new(),
+            pointer: [0., 0.],
+        }
+    }
+
+    pub fn window_manager(&mut self) -> &mut WindowManager<'a> {
+        &mut self.window_manager
+    }
+
+    pub fn context(&mut self) -> &mut FakeContext {
+        &mut self.ctx
+    }
+
+    /// Adds the widget as a full screen window
+    pub fn add_window(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        let (width, height) = self.ctx.drawable_size();
+        self.window_manager
+            .add_window(widget, Rect::new(0., 0., width, height), true);
+    }
+
+    /// Names the widget for `click_widget` and `widget`. Only a weak
+    /// reference is kept.
+    pub fn set_id<S: Into<String>>(&mut self, id: S, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        let widget: Rc<RefCell<dyn Widget<'a> + 'a>> = widget;
+        self.ids.insert(id.into(), Rc::downgrade(&widget));
+    }
+
+    pub fn widget(&self, id: &str) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.ids.get(id).and_then(|w| w.upgrade())
+    }
+
+    pub fn rect_of(&self, id: &str) -> Option<Rect> {
+        self.widget(id).map(|w| w.borrow().get_rect())
+    }
+
+    /// Advances time by one frame and runs `update`, which lays out the
+    /// windows and calls the fired handlers
+    pub fn frame(&mut self) -> GameResult {
+        self.ctx.advance(FRAME);
+        self.window_manager.update(&mut self.ctx)
+    }
+
+    pub fn frames(&mut self, count: usize) -> GameResult {
+        for _ in 0..count {
+            self.frame()?
+        }
+        Ok(())
+    }
+
+    /// Runs frames until the time has passed
+    pub fn advance(&mut self, duration: Duration) -> GameResult {
+        let end = self.ctx.time_since_start() + duration;
+        while self.ctx.time_since_start() < end {
+            self.frame()?
+        }
+        Ok(())
+    }
+
+    pub fn pointer(&self) -> [f32; 2] {
+        self.pointer
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        let [px, py] = self.pointer;
+        self.pointer = [x, y];
+        self.window_manager
+            .mouse_motion_event(&mut self.ctx, x, y, x - px, y - py);
+    }
+
+    pub fn press(&mut self, button: MouseButton) {
+        let [x, y] = self.pointer;
+        self.window_manager
+            .mouse_button_down_event(&mut self.ctx, button, x, y);
+    }
+
+    pub fn release(&mut self, button: MouseButton) {
+        let [x, y] = self.pointer;
+        self.window_manager
+            .mouse_button_up_event(&mut self.ctx, button, x, y);
+    }
+
+    pub fn click_at(&mut self, x: f32, y: f32) {
+        self.move_to(x, y);
+        self.press(MouseButton::Left);
+        self.release(MouseButton::Left);
+    }
+
+    /// Presses at one point and releases at another, moving in `steps`
+    pub fn drag(&mut self, from: [f32; 2], to: [f32; 2], steps: usize) {
+        self.move_to(from[0], from[1]);
+        self.press(MouseButton::Left);
+        let steps = steps.max(1);
+        for i in 1..=steps {
+            let t = i as f32 / steps as f32;
+            self.move_to(
+                from[0] + (to[0] - from[0]) * t,
+                from[1] + (to[1] - from[1]) * t,
+            );
+        }
+        self.release(MouseButton::Left);
+    }
+
+    /// Clicks the middle of the widget. Returns false if there is no live
+    /// widget with the id.
+    pub fn click_widget(&mut self, id: &str) -> bool {
+        match self.rect_of(id) {
+            Some(r) => {
+                self.click_at(r.x + r.w / 2., r.y + r.h / 2.);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn scroll(&mut self, dx: f32, dy: f32) {
+        self.window_manager.mouse_wheel_event(&mut self.ctx, dx, dy);
+    }
+
+    /// Presses and releases the key. The modifiers are held while it's down.
+    pub fn press_key(&mut self, keycode: KeyCode, keymods: KeyMods) {
+        self.ctx.set_keymods(keymods);
+        self.window_manager
+            .key_down_event(&mut self.ctx, keycode, keymods, false);
+        self.window_manager
+            .key_up_event(&mut self.ctx, keycode, keymods);
+        self.ctx.set_keymods(KeyMods::NONE);
+    }
+
+    /// Sends the chars as text input, newlines as the Return key
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                self.press_key(KeyCode::Return, KeyMods::NONE);
+            } else {
+                self.window_manager.text_input_event(&mut self.ctx, c);
+            }
+        }
+    }
+
+    /// Passes a touch of the finger at the current time, see
+    /// `IEvents::touch_event`
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, x: f32, y: f32) {
+        self.window_manager
+            .touch_event(&mut self.ctx, id, phase, x, y);
+    }
+
+    /// Presses the gamepad button
+    pub fn gamepad_button(&mut self, btn: Button) {
+        self.window_manager
+            .gamepad_button_down_event(&mut self.ctx, btn);
+    }
+
+    pub fn gamepad_axis(&mut self, axis: Axis, value: f32) {
+        self.window_manager
+            .gamepad_axis_event(&mut self.ctx, axis, value);
+    }
+
+    /// Replays the rest of the recording into the window manager. The
+    /// pointer is left where the recording left it.
+    pub fn play(&mut self, player: &mut Player) -> GameResult {
+        player.play(&mut self.window_manager, &mut self.ctx)?;
+        self.pointer = player.pointer();
+        Ok(())
+    }
+
+    /// Draws the windows on the CPU, transparent where nothing is drawn.
+    /// See `Raster` for what the picture looks like.
+    pub fn render(&mut self) -> Raster {
+        let (width, height) = self.ctx.drawable_size();
+        raster::render(
+            &mut self.window_manager,
+            &mut self.ctx,
+            width as u32,
+            height as u32,
+            Color::new(0., 0., 0., 0.),
+        )
+    }
+}
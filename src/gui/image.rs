@@ -0,0 +1,237 @@
+use crate::gui::painter::Painter;
+use crate::gui::{IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::graphics::{self, DrawParam, Rect};
+use ggez::nalgebra::{Point2, Vector2};
+use ggez::{Context, GameError, GameResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// How the image is placed inside the widget rect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fit {
+    /// Fills the rect ignoring aspect ratio
+    Stretch,
+    /// Largest size which fits entirely, aspect ratio kept
+    Contain,
+    /// Smallest size which covers the rect, the rest is cropped
+    Cover,
+    /// Original size in the middle, cropped if larger than the rect
+    Center,
+}
+
+impl Fit {
+    /// Part of the image to draw (in 0..1 texture coordinates) and the
+    /// rect it is drawn to
+    pub fn place(self, width: f32, height: f32, rect: Rect) -> (Rect, Rect) {
+        let full = Rect::new(0., 0., 1., 1.);
+        if width <= 0. || height <= 0. {
+            return (full, Rect::new(rect.x, rect.y, 0., 0.));
+        }
+        let (w, h) = match self {
+            Fit::Stretch => return (full, rect),
+            Fit::Contain => {
+                let scale = (rect.w / width).min(rect.h / height);
+                (width * scale, height * scale)
+            }
+            Fit::Cover => {
+                let scale = (rect.w / width).max(rect.h / height);
+                (width * scale, height * scale)
+            }
+            Fit::Center => (width, height),
+        };
+        // Centered rect of (w, h), cropped by the widget rect
+        let dw = w.min(rect.w);
+        let dh = h.min(rect.h);
+        let dest = Rect::new(
+            rect.x + (rect.w - dw) / 2.,
+            rect.y + (rect.h - dh) / 2.,
+            dw,
+            dh,
+        );
+        let src = Rect::new((1. - dw / w) / 2., (1. - dh / h) / 2., dw / w, dh / h);
+        (src, dest)
+    }
+}
+
+/// Decoded RGBA pixels. Decoding doesn't need the graphics context; the
+/// texture is created on the first draw and shared by all users of the data.
+pub struct ImageData {
+    width: u16,
+    height: u16,
+    rgba: Vec<u8>,
+    texture: RefCell<Option<graphics::Image>>,
+}
+
+impl ImageData {
+    pub fn from_rgba8(width: u16, height: u16, rgba: Vec<u8>) -> GameResult<Self> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(GameError::ResourceLoadError(format!(
+                "expected {} bytes of RGBA data for {}x{} image, got {}",
+                width as usize * height as usize * 4,
+                width,
+                height,
+                rgba.len()
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            rgba,
+            texture: RefCell::new(None),
+        })
+    }
+
+    pub fn from_png_bytes(bytes: &[u8]) -> GameResult<Self> {
+        let decoded = ::image::load_from_memory_with_format(bytes, ::image::ImageFormat::PNG)
+            .map_err(|e| GameError::ResourceLoadError(e.to_string()))?
+            .to_rgba();
+        let (width, height) = decoded.dimensions();
+        if width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(GameError::ResourceLoadError(format!(
+                "image {}x{} is too large",
+                width, height
+            )));
+        }
+        Self::from_rgba8(width as u16, height as u16, decoded.into_raw())
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    fn texture(&self, ctx: &mut Context) -> GameResult<graphics::Image> {
+        let mut texture = self.texture.borrow_mut();
+        if texture.is_none() {
+            *texture = Some(graphics::Image::from_rgba8(
+                ctx,
+                self.width,
+                self.height,
+                &self.rgba,
+            )?);
+        }
+        Ok(texture.as_ref().unwrap().clone())
+    }
+
+    /// Draws the image placed into the rect by the fit mode
+    pub fn draw(&self, ctx: &mut Context, rect: Rect, fit: Fit) -> GameResult {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let (src, dest) = fit.place(w, h, rect);
+        if dest.w <= 0. || dest.h <= 0. {
+            return Ok(());
+        }
+        let texture = self.texture(ctx)?;
+        let param = DrawParam::default()
+            .src(src)
+            .dest(Point2::new(dest.x, dest.y))
+            .scale(Vector2::new(dest.w / (w * src.w), dest.h / (h * src.h)));
+        graphics::draw(ctx, &texture, param)
+    }
+}
+
+pub struct Image {
+    data: Option<Rc<ImageData>>,
+    fit: Fit,
+    rect: Rect,
+}
+
+impl Image {
+    fn new() -> Self {
+        Self {
+            data: None,
+            fit: Fit::Contain,
+            rect: Rect::zero(),
+        }
+    }
+
+    pub fn get_data(&self) -> Option<Rc<ImageData>> {
+        self.data.clone()
+    }
+
+    pub fn set_data(&mut self, data: Option<Rc<ImageData>>) -> &mut Self {
+        self.data = data;
+        self
+    }
+
+    pub fn get_fit(&self) -> Fit {
+        self.fit
+    }
+
+    pub fn set_fit(&mut self, fit: Fit) -> &mut Self {
+        self.fit = fit;
+        self
+    }
+}
+
+impl IEvents for Image {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        match self.data {
+            Some(ref data) => data.draw(ctx, self.rect, self.fit),
+            None => Ok(()),
+        }
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        if let Some(ref data) = self.data {
+            painter.image(self.rect, data.clone(), self.fit);
+        }
+    }
+}
+
+impl ILayout for Image {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Image {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a> IOverlays<'a> for Image {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder {
+    image: Image,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            image: Image::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<Image>> {
+        Rc::new(RefCell::new(self.image))
+    }
+
+    pub fn set_data(mut self, data: Rc<ImageData>) -> Self {
+        self.image.set_data(Some(data));
+        self
+    }
+
+    pub fn set_fit(mut self, fit: Fit) -> Self {
+        self.image.set_fit(fit);
+        self
+    }
+}
@@ -0,0 +1,415 @@
+use crate::gui::painter::Painter;
+use crate::gui::scrollbar::ScrollBar;
+use crate::gui::selection::{Selection, SelectionMode};
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    draw_label, is_same, Handler, IActions, IContext, IEvents, ILayout, IOverlays, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// Data shown by `ListView`. Only rows which are currently visible are
+/// requested from the model, so it may be arbitrary long.
+pub trait IListModel {
+    fn item_count(&self) -> usize;
+    fn item_label(&self, index: usize) -> String;
+    /// Draws the item inside its row. Selection background is already drawn.
+    fn draw_item(&self, ctx: &mut Context, index: usize, rect: Rect) -> GameResult {
+        draw_label(ctx, rect, &self.item_label(index), Align::Left)
+    }
+    /// Records the item for `paint_into`. Models which override `draw_item`
+    /// should record the same drawing here.
+    fn paint_item(&self, painter: &mut Painter, index: usize, rect: Rect) {
+        painter.text(rect, self.item_label(index), Align::Left, graphics::BLACK);
+    }
+}
+
+impl IListModel for Vec<String> {
+    fn item_count(&self) -> usize {
+        self.len()
+    }
+    fn item_label(&self, index: usize) -> String {
+        self[index].clone()
+    }
+}
+
+pub struct ListView<'a> {
+    model: Rc<RefCell<dyn IListModel + 'a>>,
+    selection: Selection,
+    scrollbar: ScrollBar,
+    row_height: f32,
+    rect: Rect,
+    focused: bool,
+    hovered: bool,
+    drop_indicator: Option<usize>,
+    on_selection_changed_handlers: Vec<Handler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> ListView<'a> {
+    fn new() -> Self {
+        Self {
+            model: Rc::new(RefCell::new(Vec::<String>::new())),
+            selection: Selection::new(SelectionMode::Single),
+            scrollbar: ScrollBar::new(),
+            row_height: 30.,
+            rect: Rect::zero(),
+            focused: false,
+            hovered: false,
+            drop_indicator: None,
+            on_selection_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_selection_changed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>) + 'a,
+    ) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_selection_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_selection_changed_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_selection_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_selection_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_selection_changed(&mut self) {
+        for h in &self.on_selection_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    pub fn model(&self) -> Rc<RefCell<dyn IListModel + 'a>> {
+        self.model.clone()
+    }
+
+    pub fn set_model(&mut self, model: Rc<RefCell<dyn IListModel + 'a>>) {
+        self.model = model;
+        self.selection.clear();
+        self.selection.set_current(None);
+        self.scrollbar.set_first(0);
+        self.sync_model();
+    }
+
+    /// Selection is changed directly, without firing handlers
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    pub fn selection_mut(&mut self) -> &mut Selection {
+        &mut self.selection
+    }
+
+    pub fn set_selection_mode(&mut self, mode: SelectionMode) -> &mut Self {
+        self.selection.set_mode(mode);
+        self
+    }
+
+    pub fn set_row_height(&mut self, row_height: f32) -> &mut Self {
+        self.row_height = row_height;
+        self.layout();
+        self
+    }
+
+    pub fn first_visible(&self) -> usize {
+        self.scrollbar.first()
+    }
+
+    pub fn scroll_to(&mut self, index: usize) {
+        self.scrollbar.scroll_to(index)
+    }
+
+    /// Index of the visible item under the point
+    pub fn item_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.row_at(x, y)
+    }
+
+    /// Position an item dropped at the point would be inserted at: before
+    /// the row if the point is in its upper half, after it otherwise
+    pub fn insert_index_at(&self, x: f32, y: f32) -> usize {
+        match self.row_at(x, y) {
+            Some(index) => {
+                let row = self.row_rect(index);
+                if y < row.y + row.h / 2. {
+                    index
+                } else {
+                    index + 1
+                }
+            }
+            None if y < self.rect.y => self.scrollbar.first(),
+            None => self.scrollbar.rows().end,
+        }
+    }
+
+    /// Line drawn before the item at the index, e.g. while a drag is over
+    /// the list. Index equal to the item count puts it after the last one.
+    pub fn set_drop_indicator(&mut self, index: Option<usize>) {
+        self.drop_indicator = index;
+    }
+
+    pub fn get_drop_indicator(&self) -> Option<usize> {
+        self.drop_indicator
+    }
+
+    fn rows_rect(&self) -> Rect {
+        let mut rect = self.rect;
+        if self.scrollbar.is_needed() {
+            rect.w -= ScrollBar::WIDTH;
+        }
+        rect
+    }
+
+    // Frame, row backgrounds, the drop indicator and the scrollbar, the
+    // model draws the items
+    fn paint_rows(&self, painter: &mut Painter) {
+        painter.stroke_rect(self.rect, 1., graphics::WHITE);
+        for index in self.scrollbar.rows() {
+            let row = self.row_rect(index);
+            if self.selection.is_selected(index) {
+                painter.fill_rect(row, HIGHLIGHT_COLOR);
+            } else {
+                painter.fill_rect(row, graphics::WHITE);
+            }
+            if self.focused && self.selection.current() == Some(index) {
+                painter.stroke_rect(row, 2., graphics::BLACK);
+            }
+        }
+        if let Some(index) = self.drop_indicator {
+            let rows = self.scrollbar.rows();
+            if index >= rows.start && index <= rows.end {
+                let rect = self.rows_rect();
+                let y = rect.y + (index - rows.start) as f32 * self.row_height;
+                painter.fill_rect(Rect::new(rect.x, y - 1., rect.w, 2.), graphics::BLACK);
+            }
+        }
+        self.scrollbar.paint(painter);
+    }
+
+    fn row_rect(&self, index: usize) -> Rect {
+        let rows = self.rows_rect();
+        let n = index - self.scrollbar.first();
+        Rect::new(
+            rows.x,
+            rows.y + n as f32 * self.row_height,
+            rows.w,
+            self.row_height,
+        )
+    }
+
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        let rows = self.rows_rect();
+        if !rows.contains([x, y]) || self.row_height <= 0. {
+            return None;
+        }
+        let index = self.scrollbar.first() + ((y - rows.y) / self.row_height) as usize;
+        if self.scrollbar.rows().contains(&index) {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    fn layout(&mut self) {
+        let visible = if self.row_height > 0. {
+            (self.rect.h / self.row_height) as usize
+        } else {
+            0
+        };
+        let total = self.model.borrow().item_count();
+        self.scrollbar.set_range(total, visible);
+        self.scrollbar.set_rect(Rect::new(
+            self.rect.x + self.rect.w - ScrollBar::WIDTH,
+            self.rect.y,
+            ScrollBar::WIDTH,
+            self.rect.h,
+        ));
+    }
+
+    // Model may be changed outside, so item count is checked on each update
+    fn sync_model(&mut self) {
+        let count = self.model.borrow().item_count();
+        if self.selection.truncate(count) {
+            self.fire_on_selection_changed();
+        }
+        self.layout();
+    }
+
+    pub fn select_all(&mut self) {
+        if self.selection.select_all(self.scrollbar.total()) {
+            self.fire_on_selection_changed();
+        }
+    }
+
+    fn key_down(&mut self, keycode: KeyCode, keymods: KeyMods) {
+        let count = self.scrollbar.total();
+        let page = self.scrollbar.visible();
+        if self.selection.key_down(keycode, keymods, count, page) {
+            self.fire_on_selection_changed();
+        }
+        if let Some(current) = self.selection.current() {
+            self.scrollbar.scroll_to(current);
+        }
+    }
+}
+
+impl IEvents for ListView<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        self.sync_model();
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_rows(&mut painter);
+        painter.render(ctx)?;
+        let model = self.model.borrow();
+        for index in self.scrollbar.rows() {
+            model.draw_item(ctx, index, self.row_rect(index))?;
+        }
+        Ok(())
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.paint_rows(painter);
+        let model = self.model.borrow();
+        for index in self.scrollbar.rows() {
+            model.paint_item(painter, index, self.row_rect(index));
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if !self.rect.contains([x, y]) {
+            self.focused = false;
+            return;
+        }
+        self.focused = true;
+        if button != MouseButton::Left || self.scrollbar.mouse_down(x, y) {
+            return;
+        }
+        if let Some(index) = self.row_at(x, y) {
+            if self.selection.click(index, ctx.keymods()) {
+                self.fire_on_selection_changed();
+            }
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        self.scrollbar.mouse_up();
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+        self.scrollbar.mouse_motion(y);
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, y: f32) {
+        if self.hovered {
+            self.scrollbar.scroll_by((-y * 3.) as isize);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(keycode, keymods)
+        }
+    }
+}
+
+impl ILayout for ListView<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.layout();
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for ListView<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for ListView<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    list_view: ListView<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            list_view: ListView::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<ListView<'a>>> {
+        let rc = Rc::new(RefCell::new(self.list_view));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_model(mut self, model: Rc<RefCell<dyn IListModel + 'a>>) -> Self {
+        self.list_view.set_model(model);
+        self
+    }
+
+    pub fn set_selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.list_view.set_selection_mode(mode);
+        self
+    }
+
+    pub fn set_row_height(mut self, row_height: f32) -> Self {
+        self.list_view.set_row_height(row_height);
+        self
+    }
+
+    pub fn on_selection_changed(
+        mut self,
+        handler: impl Fn(Rc<RefCell<ListView<'a>>>) + 'a,
+    ) -> Self {
+        self.list_view.on_selection_changed(handler);
+        self
+    }
+}
@@ -0,0 +1,1092 @@
+use crate::gui::capture::PointerCapture;
+use crate::gui::painter::Painter;
+use crate::gui::shortcuts::KeyChord;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    clamp_to_screen, collect_hovered_child, is_same, IActions, IContext, IEvents, ILayout,
+    IOverlays, IndexHandler, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Color, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::iter;
+use std::rc::{Rc, Weak};
+
+const ITEM_HEIGHT: f32 = 30.;
+const SEPARATOR_HEIGHT: f32 = 10.;
+// Text can't be measured without context, so popups are sized by the
+// number of characters
+const CHAR_WIDTH: f32 = 10.;
+// Space for check marks on the left and submenu arrows on the right
+const MARK_WIDTH: f32 = 20.;
+const MIN_WIDTH: f32 = 100.;
+
+const DISABLED_COLOR: Color = Color {
+    r: 0.5,
+    g: 0.5,
+    b: 0.5,
+    a: 1.,
+};
+
+enum ItemKind<'a> {
+    Action,
+    Check(bool),
+    Radio { group: u32, checked: bool },
+    Submenu(Rc<RefCell<Menu<'a>>>),
+    Separator,
+}
+
+/// Entry of a `Menu`. Created by one of the constructors and configured
+/// with chained setters before adding to the menu.
+pub struct MenuItem<'a> {
+    label: String,
+    accelerator: Option<KeyChord>,
+    enabled: bool,
+    kind: ItemKind<'a>,
+    on_activated_handlers: Vec<IndexHandler<'a, Menu<'a>>>,
+}
+
+impl<'a> MenuItem<'a> {
+    fn with_kind(label: String, kind: ItemKind<'a>) -> Self {
+        Self {
+            label,
+            accelerator: None,
+            enabled: true,
+            kind,
+            on_activated_handlers: Vec::new(),
+        }
+    }
+
+    pub fn action<S: Into<String>>(label: S) -> Self {
+        Self::with_kind(label.into(), ItemKind::Action)
+    }
+
+    /// Item which toggles its check mark when activated
+    pub fn check<S: Into<String>>(label: S, checked: bool) -> Self {
+        Self::with_kind(label.into(), ItemKind::Check(checked))
+    }
+
+    /// Checking the item unchecks other items of the same group in its menu
+    pub fn radio<S: Into<String>>(label: S, group: u32, checked: bool) -> Self {
+        Self::with_kind(label.into(), ItemKind::Radio { group, checked })
+    }
+
+    pub fn submenu<S: Into<String>>(label: S, menu: Rc<RefCell<Menu<'a>>>) -> Self {
+        Self::with_kind(label.into(), ItemKind::Submenu(menu))
+    }
+
+    pub fn separator() -> Self {
+        Self::with_kind(String::new(), ItemKind::Separator)
+    }
+
+    /// Accepts strings like "Ctrl+S", see `KeyChord::parse`. Panics if the
+    /// string can't be parsed.
+    pub fn set_accelerator(mut self, accelerator: &str) -> Self {
+        self.accelerator = Some(KeyChord::parse(accelerator).expect("invalid accelerator"));
+        self
+    }
+
+    pub fn set_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn on_activated(mut self, handler: impl Fn(Rc<RefCell<Menu<'a>>>, usize) + 'a) -> Self {
+        self.on_activated_handlers.push(Rc::new(handler));
+        self
+    }
+
+    fn height(&self) -> f32 {
+        match self.kind {
+            ItemKind::Separator => SEPARATOR_HEIGHT,
+            _ => ITEM_HEIGHT,
+        }
+    }
+
+    fn is_selectable(&self) -> bool {
+        match self.kind {
+            ItemKind::Separator => false,
+            _ => self.enabled,
+        }
+    }
+}
+
+/// List of items shown in a popup by `MenuBar` or `ContextMenu`. Handlers
+/// get the menu and the index of the activated item.
+pub struct Menu<'a> {
+    items: Vec<MenuItem<'a>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> Menu<'a> {
+    fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_activated(
+        &mut self,
+        index: usize,
+        handler: impl Fn(Rc<RefCell<Self>>, usize) + 'a,
+    ) -> IndexHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_activated_rc(index, rc.clone());
+        rc
+    }
+    pub fn on_activated_rc(&mut self, index: usize, handler: IndexHandler<'a, Self>) {
+        self.items[index].on_activated_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        for item in &mut self.items {
+            item.on_activated_handlers
+                .drain_filter(|h| is_same(h, &handler))
+                .count();
+        }
+    }
+
+    fn fire_on_activated(&mut self, index: usize) {
+        for h in &self.items[index].on_activated_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone(), index)));
+        }
+    }
+
+    pub fn add_item(&mut self, item: MenuItem<'a>) -> &mut Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn insert_item(&mut self, index: usize, item: MenuItem<'a>) -> &mut Self {
+        self.items.insert(index, item);
+        self
+    }
+
+    pub fn remove_item(&mut self, index: usize) -> &mut Self {
+        self.items.remove(index);
+        self
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn get_label(&self, index: usize) -> &str {
+        &self.items[index].label
+    }
+
+    pub fn set_label<S: Into<String>>(&mut self, index: usize, label: S) {
+        self.items[index].label = label.into();
+    }
+
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.items[index].enabled
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.items[index].enabled = enabled;
+    }
+
+    pub fn is_checked(&self, index: usize) -> bool {
+        match self.items[index].kind {
+            ItemKind::Check(checked) | ItemKind::Radio { checked, .. } => checked,
+            _ => false,
+        }
+    }
+
+    /// Changes the check mark without firing handlers. Checking a radio
+    /// item unchecks the rest of its group.
+    pub fn set_checked(&mut self, index: usize, state: bool) {
+        match self.items[index].kind {
+            ItemKind::Check(ref mut checked) => *checked = state,
+            ItemKind::Radio { group, .. } => {
+                for (n, item) in self.items.iter_mut().enumerate() {
+                    if let ItemKind::Radio {
+                        group: g,
+                        ref mut checked,
+                    } = item.kind
+                    {
+                        if n == index {
+                            *checked = state;
+                        } else if g == group && state {
+                            *checked = false;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Does what clicking the item does: toggles check marks and fires
+    /// handlers. Disabled items, separators and submenus are ignored.
+    pub fn activate(&mut self, index: usize) {
+        let item = &self.items[index];
+        if !item.is_selectable() {
+            return;
+        }
+        match item.kind {
+            ItemKind::Submenu(_) => return,
+            ItemKind::Check(checked) => self.set_checked(index, !checked),
+            ItemKind::Radio { .. } => self.set_checked(index, true),
+            _ => {}
+        }
+        self.fire_on_activated(index);
+    }
+
+    /// Items of this menu and its submenus which have accelerators, with
+    /// the menu they are in and their index there
+    pub fn accelerators(&self) -> Vec<(KeyChord, Rc<RefCell<Self>>, usize)> {
+        let mut v = Vec::new();
+        for (index, item) in self.items.iter().enumerate() {
+            if let ItemKind::Submenu(ref menu) = item.kind {
+                v.append(&mut menu.borrow().accelerators());
+            } else if let Some(accelerator) = item.accelerator {
+                v.push((accelerator, self.rcself(), index));
+            }
+        }
+        v
+    }
+
+    fn submenu(&self, index: usize) -> Option<Rc<RefCell<Self>>> {
+        match self.items[index].kind {
+            ItemKind::Submenu(ref menu) => Some(menu.clone()),
+            _ => None,
+        }
+    }
+
+    fn is_selectable(&self, index: usize) -> bool {
+        index < self.items.len() && self.items[index].is_selectable()
+    }
+
+    // Next selectable item after `from` in the direction, wrapping around
+    fn next_selectable(&self, from: Option<usize>, forward: bool) -> Option<usize> {
+        let count = self.items.len();
+        let mut index = from;
+        for _ in 0..count {
+            let next = match index {
+                None if forward => 0,
+                None => count - 1,
+                Some(i) if forward => (i + 1) % count,
+                Some(i) => (i + count - 1) % count,
+            };
+            if self.is_selectable(next) {
+                return Some(next);
+            }
+            index = Some(next);
+        }
+        None
+    }
+
+    fn size(&self) -> (f32, f32) {
+        let chars = self
+            .items
+            .iter()
+            .map(|item| {
+                let accelerator = item.accelerator.map_or(0, |a| a.to_string().len() + 2);
+                item.label.chars().count() + accelerator
+            })
+            .max()
+            .unwrap_or(0);
+        let w = (chars as f32 * CHAR_WIDTH + MARK_WIDTH * 2.).max(MIN_WIDTH);
+        let h = self.items.iter().map(MenuItem::height).sum();
+        (w, h)
+    }
+
+    fn item_offset(&self, index: usize) -> f32 {
+        self.items[..index].iter().map(MenuItem::height).sum()
+    }
+
+    fn item_at(&self, offset: f32) -> Option<usize> {
+        let mut y = 0.;
+        for (index, item) in self.items.iter().enumerate() {
+            y += item.height();
+            if offset < y {
+                return Some(index);
+            }
+        }
+        None
+    }
+
+    fn paint(&self, painter: &mut Painter, rect: Rect, highlighted: Option<usize>) {
+        painter.fill_rect(rect, graphics::WHITE);
+        let mut y = rect.y;
+        for (index, item) in self.items.iter().enumerate() {
+            let item_rect = Rect::new(rect.x, y, rect.w, item.height());
+            let cy = y + item.height() / 2.;
+            if highlighted == Some(index) && item.is_selectable() {
+                painter.fill_rect(item_rect, HIGHLIGHT_COLOR);
+            }
+            match item.kind {
+                ItemKind::Separator => {
+                    painter.line(
+                        [rect.x + 5., cy],
+                        [rect.x + rect.w - 5., cy],
+                        1.,
+                        DISABLED_COLOR,
+                    );
+                }
+                ItemKind::Check(true) => {
+                    let mark = Rect::new(rect.x + MARK_WIDTH / 2. - 4., cy - 4., 8., 8.);
+                    painter.fill_rect(mark, graphics::BLACK);
+                }
+                ItemKind::Radio { checked: true, .. } => {
+                    painter.fill_circle([rect.x + MARK_WIDTH / 2., cy], 4., graphics::BLACK);
+                }
+                ItemKind::Submenu(_) => {
+                    let x = rect.x + rect.w - MARK_WIDTH / 2.;
+                    painter.fill_polygon(
+                        &[[x - 3., cy - 5.], [x + 3., cy], [x - 3., cy + 5.]],
+                        graphics::BLACK,
+                    );
+                }
+                _ => {}
+            }
+            y += item.height();
+        }
+        painter.stroke_rect(rect, 1., graphics::BLACK);
+        let mut y = rect.y;
+        for item in &self.items {
+            if let ItemKind::Separator = item.kind {
+                y += item.height();
+                continue;
+            }
+            let color = if item.enabled {
+                graphics::BLACK
+            } else {
+                DISABLED_COLOR
+            };
+            let text_rect = Rect::new(
+                rect.x + MARK_WIDTH,
+                y,
+                rect.w - MARK_WIDTH * 2.,
+                item.height(),
+            );
+            painter.text(text_rect, item.label.as_str(), Align::Left, color);
+            if let Some(accelerator) = item.accelerator {
+                painter.text(text_rect, accelerator.to_string(), Align::Right, color);
+            }
+            y += item.height();
+        }
+    }
+}
+
+impl<'a> IActions<'a> for Menu<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        let mut v: Vec<_> = self.pending_handlers.drain(..).collect();
+        for item in &self.items {
+            if let ItemKind::Submenu(ref menu) = item.kind {
+                v.append(&mut menu.borrow_mut().collect_fired());
+            }
+        }
+        v
+    }
+}
+
+pub struct Builder<'a> {
+    menu: Menu<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self { menu: Menu::new() }
+    }
+
+    pub fn build(self) -> Rc<RefCell<Menu<'a>>> {
+        let rc = Rc::new(RefCell::new(self.menu));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn add_item(mut self, item: MenuItem<'a>) -> Self {
+        self.menu.add_item(item);
+        self
+    }
+
+    pub fn add_separator(mut self) -> Self {
+        self.menu.add_item(MenuItem::separator());
+        self
+    }
+}
+
+struct Level<'a> {
+    menu: Rc<RefCell<Menu<'a>>>,
+    rect: Rect,
+    highlighted: Option<usize>,
+}
+
+/// Chain of open popups: the root menu and submenus opened from it. The
+/// owner widget passes keys to it, pointer events come through the popup
+/// of the root level.
+struct MenuStack<'a> {
+    levels: Vec<Level<'a>>,
+    screen: Option<(f32, f32)>,
+}
+
+impl<'a> MenuStack<'a> {
+    fn new() -> Self {
+        Self {
+            levels: Vec::new(),
+            screen: None,
+        }
+    }
+
+    fn set_screen(&mut self, screen: (f32, f32)) {
+        self.screen = Some(screen);
+    }
+
+    fn is_open(&self) -> bool {
+        !self.levels.is_empty()
+    }
+
+    fn open(&mut self, menu: Rc<RefCell<Menu<'a>>>, at: [f32; 2], flip_to: [f32; 2]) {
+        self.levels.clear();
+        self.push(menu, at, flip_to);
+    }
+
+    fn close(&mut self) {
+        self.levels.clear();
+    }
+
+    // Popup has its top left corner at `at`. If it doesn't fit on the
+    // screen, its right or bottom edge goes to `flip_to` instead.
+    fn push(&mut self, menu: Rc<RefCell<Menu<'a>>>, at: [f32; 2], flip_to: [f32; 2]) {
+        let (w, h) = menu.borrow().size();
+        let mut rect = Rect::new(at[0], at[1], w, h);
+        if let Some(screen) = self.screen {
+            if rect.right() > screen.0 {
+                rect.x = flip_to[0] - w;
+            }
+            if rect.bottom() > screen.1 {
+                rect.y = flip_to[1] - h;
+            }
+            rect = clamp_to_screen(rect, screen);
+        }
+        self.levels.push(Level {
+            menu,
+            rect,
+            highlighted: None,
+        });
+    }
+
+    fn level_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.levels.iter().rposition(|l| l.rect.contains([x, y]))
+    }
+
+    fn item_at(&self, level: usize, y: f32) -> Option<usize> {
+        let l = &self.levels[level];
+        l.menu.borrow().item_at(y - l.rect.y)
+    }
+
+    // Highlights the item of the level, closes deeper levels and opens
+    // the submenu of the item if it has one
+    fn highlight(&mut self, level: usize, index: Option<usize>) {
+        self.levels.truncate(level + 1);
+        let l = &mut self.levels[level];
+        l.highlighted = index;
+        let submenu = index.and_then(|index| {
+            let menu = l.menu.borrow();
+            if menu.is_selectable(index) {
+                // Submenu goes right of the item or left of the menu, and
+                // its bottom is at the bottom of the item if it flips
+                let y = l.rect.y + menu.item_offset(index);
+                let at = [l.rect.x + l.rect.w, y];
+                let flip_to = [l.rect.x, y + menu.items[index].height()];
+                menu.submenu(index).map(|submenu| (submenu, at, flip_to))
+            } else {
+                None
+            }
+        });
+        if let Some((submenu, at, flip_to)) = submenu {
+            self.push(submenu, at, flip_to);
+        }
+    }
+
+    fn activate(&mut self, level: usize, index: usize) {
+        let menu = self.levels[level].menu.clone();
+        if !menu.borrow().is_selectable(index) {
+            return;
+        }
+        if menu.borrow().submenu(index).is_some() {
+            self.highlight(level, Some(index));
+            self.highlight_first();
+        } else {
+            menu.borrow_mut().activate(index);
+            self.close();
+        }
+    }
+
+    // Highlights the first item of the deepest level
+    fn highlight_first(&mut self) {
+        if let Some(l) = self.levels.last_mut() {
+            l.highlighted = l.menu.borrow().next_selectable(None, true);
+        }
+    }
+
+    fn mouse_down(&mut self, x: f32, y: f32) {
+        if self.level_at(x, y).is_none() {
+            self.close();
+        }
+    }
+
+    fn mouse_up(&mut self, x: f32, y: f32) {
+        if let Some(level) = self.level_at(x, y) {
+            if let Some(index) = self.item_at(level, y) {
+                self.activate(level, index);
+            }
+        }
+    }
+
+    fn mouse_motion(&mut self, x: f32, y: f32) {
+        if let Some(level) = self.level_at(x, y) {
+            let index = self.item_at(level, y);
+            if self.levels[level].highlighted != index {
+                self.highlight(level, index);
+            }
+        }
+    }
+
+    /// Returns false if the key was not used, so that the owner may
+    /// handle it, e.g. switch to the next menu of the bar
+    fn key_down(&mut self, keycode: KeyCode) -> bool {
+        let level = match self.levels.len() {
+            0 => return false,
+            n => n - 1,
+        };
+        let highlighted = self.levels[level].highlighted;
+        match keycode {
+            KeyCode::Escape => {
+                self.levels.pop();
+            }
+            KeyCode::Up | KeyCode::Down => {
+                let menu = self.levels[level].menu.clone();
+                let next = menu
+                    .borrow()
+                    .next_selectable(highlighted, keycode == KeyCode::Down);
+                self.highlight(level, next);
+            }
+            KeyCode::Right => match highlighted {
+                Some(index) if self.levels[level].menu.borrow().submenu(index).is_some() => {
+                    self.activate(level, index)
+                }
+                _ => return false,
+            },
+            KeyCode::Left if level > 0 => {
+                self.levels.pop();
+            }
+            KeyCode::Return | KeyCode::Space => {
+                if let Some(index) = highlighted {
+                    self.activate(level, index);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn paint_level(&self, painter: &mut Painter, level: usize) {
+        if let Some(l) = self.levels.get(level) {
+            l.menu.borrow().paint(painter, l.rect, l.highlighted);
+        }
+    }
+}
+
+/// Overlay showing one level of a `MenuStack`. Pointer events are handled
+/// by the root level only, since all levels get them.
+struct MenuPopup<'a> {
+    stack: Rc<RefCell<MenuStack<'a>>>,
+    level: usize,
+}
+
+impl IEvents for MenuPopup<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.stack.borrow().paint_level(painter, self.level);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if self.level == 0 {
+            self.stack.borrow_mut().mouse_down(x, y);
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if self.level == 0 {
+            self.stack.borrow_mut().mouse_up(x, y);
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        if self.level == 0 {
+            self.stack.borrow_mut().mouse_motion(x, y);
+        }
+    }
+}
+
+impl ILayout for MenuPopup<'_> {
+    fn set_rect(&mut self, _rect: Rect) {}
+    fn get_rect(&self) -> Rect {
+        match self.stack.borrow().levels.get(self.level) {
+            Some(l) => l.rect,
+            None => Rect::zero(),
+        }
+    }
+}
+
+impl<'a> IActions<'a> for MenuPopup<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a> IOverlays<'a> for MenuPopup<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+// Popups for the currently open levels of the stack, reusing ones created before
+fn collect_popups<'a>(
+    stack: &Rc<RefCell<MenuStack<'a>>>,
+    popups: &mut Vec<Rc<RefCell<MenuPopup<'a>>>>,
+) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+    let count = stack.borrow().levels.len();
+    while popups.len() < count {
+        popups.push(Rc::new(RefCell::new(MenuPopup {
+            stack: stack.clone(),
+            level: popups.len(),
+        })));
+    }
+    popups[..count]
+        .iter()
+        .map(|p| p.clone() as Rc<RefCell<dyn Widget<'a> + 'a>>)
+        .collect()
+}
+
+struct TopMenu<'a> {
+    title: String,
+    menu: Rc<RefCell<Menu<'a>>>,
+}
+
+/// Strip of menu titles. Click on a title opens its menu, moving the
+/// pointer along the strip switches between open menus. Accelerators of
+/// the items work once registered by `WindowManager::add_accelerators`.
+pub struct MenuBar<'a> {
+    menus: Vec<TopMenu<'a>>,
+    open_menu: Option<usize>,
+    stack: Rc<RefCell<MenuStack<'a>>>,
+    popups: Vec<Rc<RefCell<MenuPopup<'a>>>>,
+    rect: Rect,
+}
+
+impl<'a> MenuBar<'a> {
+    fn new() -> Self {
+        Self {
+            menus: Vec::new(),
+            open_menu: None,
+            stack: Rc::new(RefCell::new(MenuStack::new())),
+            popups: Vec::new(),
+            rect: Rect::zero(),
+        }
+    }
+
+    pub fn add_menu<S: Into<String>>(
+        &mut self,
+        title: S,
+        menu: Rc<RefCell<Menu<'a>>>,
+    ) -> &mut Self {
+        self.menus.push(TopMenu {
+            title: title.into(),
+            menu,
+        });
+        self
+    }
+
+    pub fn remove_menu(&mut self, index: usize) -> &mut Self {
+        self.close();
+        self.menus.remove(index);
+        self
+    }
+
+    pub fn menu_count(&self) -> usize {
+        self.menus.len()
+    }
+
+    pub fn get_menu(&self, index: usize) -> Rc<RefCell<Menu<'a>>> {
+        self.menus[index].menu.clone()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open_menu.is_some()
+    }
+
+    pub fn open(&mut self, index: usize) {
+        if index >= self.menus.len() {
+            return;
+        }
+        let rect = self.title_rect(index);
+        self.stack.borrow_mut().open(
+            self.menus[index].menu.clone(),
+            [rect.x, rect.y + rect.h],
+            [rect.x + rect.w, rect.y],
+        );
+        self.open_menu = Some(index);
+    }
+
+    pub fn close(&mut self) {
+        self.stack.borrow_mut().close();
+        self.open_menu = None;
+    }
+
+    fn title_rect(&self, index: usize) -> Rect {
+        let title_width = |m: &TopMenu| m.title.chars().count() as f32 * CHAR_WIDTH + MARK_WIDTH;
+        let x: f32 = self.menus[..index].iter().map(title_width).sum();
+        Rect::new(
+            self.rect.x + x,
+            self.rect.y,
+            title_width(&self.menus[index]),
+            self.rect.h,
+        )
+    }
+
+    fn title_at(&self, x: f32, y: f32) -> Option<usize> {
+        (0..self.menus.len()).find(|i| self.title_rect(*i).contains([x, y]))
+    }
+
+    fn key_down(&mut self, keycode: KeyCode) {
+        if let Some(open) = self.open_menu {
+            if self.stack.borrow_mut().key_down(keycode) {
+                return;
+            }
+            let count = self.menus.len();
+            let next = match keycode {
+                KeyCode::Left => (open + count - 1) % count,
+                KeyCode::Right => (open + 1) % count,
+                _ => return,
+            };
+            self.open(next);
+            self.stack.borrow_mut().highlight_first();
+        }
+    }
+}
+
+impl IEvents for MenuBar<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.stack.borrow_mut().set_screen(ctx.drawable_size());
+        // The stack closes itself on outside click or activation
+        if !self.stack.borrow().is_open() {
+            self.open_menu = None;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        painter.fill_rect(self.rect, graphics::WHITE);
+        if let Some(open) = self.open_menu {
+            painter.fill_rect(self.title_rect(open), HIGHLIGHT_COLOR);
+        }
+        for (index, m) in self.menus.iter().enumerate() {
+            painter.text(
+                self.title_rect(index),
+                m.title.as_str(),
+                Align::Center,
+                graphics::BLACK,
+            );
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button != MouseButton::Left {
+            return;
+        }
+        if let Some(index) = self.title_at(x, y) {
+            // The stack has already closed itself on this click, which is
+            // outside of it, so `open_menu` tells what was open before
+            if self.open_menu == Some(index) {
+                self.close();
+            } else {
+                self.open(index);
+            }
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        if self.open_menu.is_some() {
+            if let Some(index) = self.title_at(x, y) {
+                if self.open_menu != Some(index) {
+                    self.open(index);
+                }
+            }
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        self.key_down(keycode)
+    }
+}
+
+impl ILayout for MenuBar<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for MenuBar<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        let mut v = Vec::new();
+        for m in &self.menus {
+            v.append(&mut m.menu.borrow_mut().collect_fired());
+        }
+        v
+    }
+}
+
+impl<'a> IOverlays<'a> for MenuBar<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        collect_popups(&self.stack, &mut self.popups)
+    }
+}
+
+pub struct MenuBarBuilder<'a> {
+    menu_bar: MenuBar<'a>,
+}
+
+impl<'a> MenuBarBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            menu_bar: MenuBar::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<MenuBar<'a>>> {
+        Rc::new(RefCell::new(self.menu_bar))
+    }
+
+    pub fn add_menu<S: Into<String>>(mut self, title: S, menu: Rc<RefCell<Menu<'a>>>) -> Self {
+        self.menu_bar.add_menu(title, menu);
+        self
+    }
+}
+
+/// Wraps any widget and opens the menu under the pointer on right click
+/// inside it. Everything else is passed to the wrapped widget.
+pub struct ContextMenu<'a> {
+    widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+    menu: Rc<RefCell<Menu<'a>>>,
+    stack: Rc<RefCell<MenuStack<'a>>>,
+    popups: Vec<Rc<RefCell<MenuPopup<'a>>>>,
+    // Wrapped widget under the press, it is the only child
+    capture: PointerCapture,
+}
+
+impl<'a> ContextMenu<'a> {
+    pub fn new(
+        widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+        menu: Rc<RefCell<Menu<'a>>>,
+    ) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            widget,
+            menu,
+            stack: Rc::new(RefCell::new(MenuStack::new())),
+            popups: Vec::new(),
+            capture: PointerCapture::new(),
+        }))
+    }
+
+    pub fn get_widget(&self) -> Rc<RefCell<dyn Widget<'a> + 'a>> {
+        self.widget.clone()
+    }
+
+    pub fn get_menu(&self) -> Rc<RefCell<Menu<'a>>> {
+        self.menu.clone()
+    }
+
+    pub fn set_menu(&mut self, menu: Rc<RefCell<Menu<'a>>>) {
+        self.close();
+        self.menu = menu;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.stack.borrow().is_open()
+    }
+
+    /// Opens the menu with its corner at the point
+    pub fn open_at(&mut self, x: f32, y: f32) {
+        // Shifted a bit so that releasing the button which opened
+        // the menu doesn't activate the first item
+        self.stack
+            .borrow_mut()
+            .open(self.menu.clone(), [x + 2., y + 2.], [x - 2., y - 2.]);
+    }
+
+    pub fn close(&mut self) {
+        self.stack.borrow_mut().close();
+    }
+}
+
+impl IEvents for ContextMenu<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.stack.borrow_mut().set_screen(ctx.drawable_size());
+        self.widget.borrow_mut().update(ctx)
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.widget.borrow_mut().draw(ctx)
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.widget.borrow_mut().paint_into(ctx, painter);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        let rect = self.widget.borrow().get_rect();
+        self.capture.press(button, x, y, Some(rect));
+        self.widget
+            .borrow_mut()
+            .mouse_button_down_event(ctx, button, x, y);
+        if button == MouseButton::Right && self.get_rect().contains([x, y]) {
+            self.open_at(x, y);
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if self.capture.routes_to(0) {
+            self.widget
+                .borrow_mut()
+                .mouse_button_up_event(ctx, button, x, y)
+        }
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.capture.routes_to(0) {
+            self.widget
+                .borrow_mut()
+                .mouse_motion_event(ctx, x, y, dx, dy)
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        self.widget.borrow_mut().mouse_wheel_event(ctx, x, y)
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        // Open menu takes the keyboard from the wrapped widget
+        if self.is_open() {
+            self.stack.borrow_mut().key_down(keycode);
+        } else {
+            self.widget
+                .borrow_mut()
+                .key_down_event(ctx, keycode, keymods, repeat)
+        }
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        self.widget.borrow_mut().key_up_event(ctx, keycode, keymods)
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        if !self.is_open() {
+            self.widget.borrow_mut().text_input_event(ctx, character)
+        }
+    }
+}
+
+impl ILayout for ContextMenu<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.widget.borrow_mut().set_rect(rect)
+    }
+    fn get_rect(&self) -> Rect {
+        self.widget.borrow().get_rect()
+    }
+}
+
+impl<'a> IActions<'a> for ContextMenu<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        let mut v = self.widget.borrow_mut().collect_fired();
+        v.append(&mut self.menu.borrow_mut().collect_fired());
+        v
+    }
+}
+
+impl<'a> IOverlays<'a> for ContextMenu<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        let mut v = self.widget.borrow_mut().collect_overlays();
+        v.append(&mut collect_popups(&self.stack, &mut self.popups));
+        v
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(iter::once(&self.widget), x, y, hovered)
+    }
+}
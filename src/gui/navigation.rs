@@ -0,0 +1,292 @@
+use crate::gui::{IActions, Widget};
+use ggez::event::{Axis, Button, KeyCode};
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+// Stick has to pass the press threshold to move the focus once and come
+// back below the release one before it moves it again
+const STICK_PRESS: f32 = 0.5;
+const STICK_RELEASE: f32 = 0.25;
+// Offset across the direction costs more than the distance along it, so
+// that the neighbour in the same row or column wins over a closer diagonal one
+const CROSS_WEIGHT: f32 = 2.;
+const FOCUS_COLOR: Color = Color {
+    r: 0.1,
+    g: 0.4,
+    b: 0.9,
+    a: 1.,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn from_key(keycode: KeyCode) -> Option<Self> {
+        match keycode {
+            KeyCode::Up => Some(Direction::Up),
+            KeyCode::Down => Some(Direction::Down),
+            KeyCode::Left => Some(Direction::Left),
+            KeyCode::Right => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn from_button(button: Button) -> Option<Self> {
+        match button {
+            Button::DPadUp => Some(Direction::Up),
+            Button::DPadDown => Some(Direction::Down),
+            Button::DPadLeft => Some(Direction::Left),
+            Button::DPadRight => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Arrow key of the direction
+    pub fn key(self) -> KeyCode {
+        match self {
+            Direction::Up => KeyCode::Up,
+            Direction::Down => KeyCode::Down,
+            Direction::Left => KeyCode::Left,
+            Direction::Right => KeyCode::Right,
+        }
+    }
+}
+
+/// Gamepad button which clicks the focused widget
+pub fn is_confirm_button(button: Button) -> bool {
+    button == Button::South || button == Button::Start
+}
+
+/// Gamepad button which cancels: closes the popup or fires `on_back`
+pub fn is_back_button(button: Button) -> bool {
+    button == Button::East || button == Button::Select
+}
+
+fn center(r: Rect) -> [f32; 2] {
+    [r.x + r.w / 2., r.y + r.h / 2.]
+}
+
+// Distance between the intervals, zero if they overlap
+fn gap(a0: f32, a1: f32, b0: f32, b1: f32) -> f32 {
+    (b0 - a1).max(a0 - b1).max(0.)
+}
+
+/// Index of the rect nearest to `from` in the direction. Only rects whose
+/// centers lie strictly in the direction are candidates.
+pub fn nearest(from: Rect, direction: Direction, rects: &[Rect]) -> Option<usize> {
+    let [fx, fy] = center(from);
+    let score = |r: &Rect| {
+        let [x, y] = center(*r);
+        let (along, cross) = match direction {
+            Direction::Up => (fy - y, gap(from.x, from.right(), r.x, r.right())),
+            Direction::Down => (y - fy, gap(from.x, from.right(), r.x, r.right())),
+            Direction::Left => (fx - x, gap(from.y, from.bottom(), r.y, r.bottom())),
+            Direction::Right => (x - fx, gap(from.y, from.bottom(), r.y, r.bottom())),
+        };
+        if along > 0. {
+            Some(along + cross * CROSS_WEIGHT)
+        } else {
+            None
+        }
+    };
+    rects
+        .iter()
+        .enumerate()
+        .filter_map(|(index, r)| score(r).map(|s| (index, s)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// Focusable widgets and the focus moved between them by arrow keys or
+/// gamepad d-pad and stick. `WindowManager` owns it, clicks the focused
+/// widget on confirm and draws the focus frame while the navigation is
+/// in use. Widgets are held by weak references and navigated by their
+/// `get_rect`; the caller tells which of them are reachable, e.g. not on
+/// a hidden page.
+pub struct Navigation<'a> {
+    entries: Vec<Weak<RefCell<dyn Widget<'a> + 'a>>>,
+    focused: Option<Weak<RefCell<dyn Widget<'a> + 'a>>>,
+    active: bool,
+    stick: [Option<Direction>; 2],
+    on_back_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+}
+
+impl<'a> Navigation<'a> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            focused: None,
+            active: false,
+            stick: [None, None],
+            on_back_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+        }
+    }
+
+    pub fn add_focusable(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) {
+        if self.find(&widget).is_none() {
+            self.entries.push(Rc::downgrade(&widget));
+        }
+    }
+
+    pub fn remove_focusable(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) {
+        if let Some(index) = self.find(&widget) {
+            self.entries.remove(index);
+        }
+        if self.is_focused(&widget) {
+            self.focused = None;
+        }
+    }
+
+    fn find(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.as_ptr() as *const () == Rc::as_ptr(widget) as *const ())
+    }
+
+    fn is_focused(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> bool {
+        match self.focused {
+            Some(ref f) => f.as_ptr() as *const () == Rc::as_ptr(widget) as *const (),
+            None => false,
+        }
+    }
+
+    /// Focuses the widget, it is made focusable if it isn't
+    pub fn set_focus(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) {
+        self.add_focusable(widget.clone());
+        self.focused = Some(Rc::downgrade(&widget));
+    }
+
+    pub fn clear_focus(&mut self) {
+        self.focused = None;
+    }
+
+    pub fn focused(&self) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.focused.as_ref().and_then(|w| w.upgrade())
+    }
+
+    /// Focus frame is shown after a navigation input until the mouse is used
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    /// Moves the focus to the nearest widget in the direction among the
+    /// `reachable` ones. Without focus the first reachable focusable widget
+    /// is focused. Returns false if the focus didn't change.
+    pub fn navigate(
+        &mut self,
+        direction: Direction,
+        reachable: impl Fn(&Rc<RefCell<dyn Widget<'a> + 'a>>) -> bool,
+    ) -> bool {
+        self.active = true;
+        self.entries.retain(|e| e.strong_count() > 0);
+        let candidates: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|e| e.upgrade())
+            .filter(|w| !self.is_focused(w))
+            .filter(|w| reachable(w))
+            .collect();
+        let rects: Vec<_> = candidates.iter().map(|w| w.borrow().get_rect()).collect();
+        let next = match self.focused() {
+            Some(focused) => {
+                let from = focused.borrow().get_rect();
+                nearest(from, direction, &rects)
+            }
+            None if candidates.is_empty() => None,
+            None => Some(0),
+        };
+        let next = next.map(|index| candidates[index].clone());
+        match next {
+            Some(widget) => {
+                self.focused = Some(Rc::downgrade(&widget));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turns stick motion into directions. A direction is returned once per
+    /// push of the stick, holding it doesn't repeat.
+    pub fn axis_moved(&mut self, axis: Axis, value: f32) -> Option<Direction> {
+        let (slot, negative, positive) = match axis {
+            Axis::LeftStickX => (0, Direction::Left, Direction::Right),
+            // Stick Y axis points up
+            Axis::LeftStickY => (1, Direction::Down, Direction::Up),
+            _ => return None,
+        };
+        let direction = if value >= STICK_PRESS {
+            Some(positive)
+        } else if value <= -STICK_PRESS {
+            Some(negative)
+        } else {
+            None
+        };
+        match direction {
+            Some(d) if self.stick[slot] != Some(d) => {
+                self.stick[slot] = Some(d);
+                Some(d)
+            }
+            Some(_) => None,
+            None => {
+                if value.abs() < STICK_RELEASE {
+                    self.stick[slot] = None;
+                }
+                None
+            }
+        }
+    }
+
+    /// Called when back is pressed and nothing else took it
+    pub fn on_back(&mut self, handler: impl Fn() + 'a) -> Rc<dyn Fn() + 'a> {
+        let rc: Rc<dyn Fn() + 'a> = Rc::new(handler);
+        self.on_back_handlers.push(rc.clone());
+        rc
+    }
+
+    pub fn remove_handler(&mut self, handler: &Rc<dyn Fn() + 'a>) {
+        self.on_back_handlers.retain(|h| !Rc::ptr_eq(h, handler));
+    }
+
+    /// Queues the `on_back` handlers. Returns false if there are none.
+    pub fn back(&mut self) -> bool {
+        self.active = true;
+        self.pending_handlers
+            .extend(self.on_back_handlers.iter().cloned());
+        !self.on_back_handlers.is_empty()
+    }
+
+    /// Draws the frame around the focused widget
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let widget = match self.focused() {
+            Some(ref widget) if self.active => widget.clone(),
+            _ => return Ok(()),
+        };
+        let rect = widget.borrow().get_rect();
+        if rect.w <= 0. || rect.h <= 0. {
+            return Ok(());
+        }
+        let mut mb = MeshBuilder::new();
+        mb.rectangle(DrawMode::stroke(2.), rect, FOCUS_COLOR);
+        let mesh = mb.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
+
+impl<'a> IActions<'a> for Navigation<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
@@ -0,0 +1,330 @@
+use crate::gui::draw_text;
+use crate::gui::image::{Fit, ImageData};
+use ggez::graphics::{self, Align, Color, DrawMode, DrawParam, MeshBuilder, Rect, Vertex};
+use ggez::nalgebra::Point2;
+use ggez::{Context, GameResult};
+use std::rc::Rc;
+
+/// Single drawing operation in screen coordinates
+#[derive(Clone)]
+pub enum DrawCommand {
+    FillRect {
+        rect: Rect,
+        color: Color,
+    },
+    StrokeRect {
+        rect: Rect,
+        width: f32,
+        color: Color,
+    },
+    Line {
+        points: Vec<[f32; 2]>,
+        width: f32,
+        color: Color,
+    },
+    FillCircle {
+        center: [f32; 2],
+        radius: f32,
+        color: Color,
+    },
+    StrokeCircle {
+        center: [f32; 2],
+        radius: f32,
+        width: f32,
+        color: Color,
+    },
+    FillPolygon {
+        points: Vec<[f32; 2]>,
+        color: Color,
+    },
+    /// Rect with colors interpolated left to right or top to bottom
+    Gradient {
+        rect: Rect,
+        from: Color,
+        to: Color,
+        horizontal: bool,
+    },
+    Text {
+        rect: Rect,
+        text: String,
+        align: Align,
+        color: Color,
+    },
+    Image {
+        rect: Rect,
+        data: Rc<ImageData>,
+        fit: Fit,
+    },
+}
+
+impl DrawCommand {
+    /// Shape which covers nothing, e.g. a zero length line or an empty
+    /// rect. ggez fails to build meshes of these, so `render` skips them.
+    pub fn is_degenerate(&self) -> bool {
+        let empty = |rect: &Rect| rect.w <= 0. || rect.h <= 0.;
+        match self {
+            DrawCommand::FillRect { rect, .. } | DrawCommand::Gradient { rect, .. } => empty(rect),
+            DrawCommand::StrokeRect { rect, width, .. } => empty(rect) || *width <= 0.,
+            DrawCommand::Line { points, width, .. } => {
+                distinct_points(points).len() < 2 || *width <= 0.
+            }
+            DrawCommand::FillCircle { radius, .. } => *radius <= 0.,
+            DrawCommand::StrokeCircle { radius, width, .. } => *radius <= 0. || *width <= 0.,
+            DrawCommand::FillPolygon { points, .. } => polygon_area(points) == 0.,
+            DrawCommand::Text { .. } | DrawCommand::Image { .. } => false,
+        }
+    }
+}
+
+// Points without consecutive repeats, which have no direction between them
+fn distinct_points(points: &[[f32; 2]]) -> Vec<[f32; 2]> {
+    let mut points = points.to_vec();
+    points.dedup();
+    points
+}
+
+fn polygon_area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f32>()
+        / 2.
+}
+
+/// Records drawing commands given in coordinates relative to the origin.
+/// Recording doesn't need graphics context; `render` replays commands
+/// with ggez in the order they were given.
+pub struct Painter {
+    origin: [f32; 2],
+    commands: Vec<DrawCommand>,
+}
+
+impl Painter {
+    pub fn new(origin: [f32; 2]) -> Self {
+        Self {
+            origin,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn take_commands(&mut self) -> Vec<DrawCommand> {
+        self.commands.drain(..).collect()
+    }
+
+    /// Appends commands which are already in screen coordinates, e.g.
+    /// taken from another painter
+    pub fn extend<I: IntoIterator<Item = DrawCommand>>(&mut self, commands: I) -> &mut Self {
+        self.commands.extend(commands);
+        self
+    }
+
+    fn point(&self, p: [f32; 2]) -> [f32; 2] {
+        [p[0] + self.origin[0], p[1] + self.origin[1]]
+    }
+
+    fn rect(&self, r: Rect) -> Rect {
+        Rect::new(r.x + self.origin[0], r.y + self.origin[1], r.w, r.h)
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) -> &mut Self {
+        let rect = self.rect(rect);
+        self.commands.push(DrawCommand::FillRect { rect, color });
+        self
+    }
+
+    pub fn stroke_rect(&mut self, rect: Rect, width: f32, color: Color) -> &mut Self {
+        let rect = self.rect(rect);
+        self.commands
+            .push(DrawCommand::StrokeRect { rect, width, color });
+        self
+    }
+
+    pub fn line(&mut self, from: [f32; 2], to: [f32; 2], width: f32, color: Color) -> &mut Self {
+        self.polyline(&[from, to], width, color)
+    }
+
+    pub fn polyline(&mut self, points: &[[f32; 2]], width: f32, color: Color) -> &mut Self {
+        let points = points.iter().map(|p| self.point(*p)).collect();
+        self.commands.push(DrawCommand::Line {
+            points,
+            width,
+            color,
+        });
+        self
+    }
+
+    pub fn fill_circle(&mut self, center: [f32; 2], radius: f32, color: Color) -> &mut Self {
+        let center = self.point(center);
+        self.commands.push(DrawCommand::FillCircle {
+            center,
+            radius,
+            color,
+        });
+        self
+    }
+
+    pub fn stroke_circle(
+        &mut self,
+        center: [f32; 2],
+        radius: f32,
+        width: f32,
+        color: Color,
+    ) -> &mut Self {
+        let center = self.point(center);
+        self.commands.push(DrawCommand::StrokeCircle {
+            center,
+            radius,
+            width,
+            color,
+        });
+        self
+    }
+
+    pub fn fill_polygon(&mut self, points: &[[f32; 2]], color: Color) -> &mut Self {
+        let points = points.iter().map(|p| self.point(*p)).collect();
+        self.commands
+            .push(DrawCommand::FillPolygon { points, color });
+        self
+    }
+
+    pub fn gradient(&mut self, rect: Rect, from: Color, to: Color, horizontal: bool) -> &mut Self {
+        let rect = self.rect(rect);
+        self.commands.push(DrawCommand::Gradient {
+            rect,
+            from,
+            to,
+            horizontal,
+        });
+        self
+    }
+
+    /// Single line of text vertically centered in the rect
+    pub fn text<S: Into<String>>(
+        &mut self,
+        rect: Rect,
+        text: S,
+        align: Align,
+        color: Color,
+    ) -> &mut Self {
+        let rect = self.rect(rect);
+        self.commands.push(DrawCommand::Text {
+            rect,
+            text: text.into(),
+            align,
+            color,
+        });
+        self
+    }
+
+    pub fn image(&mut self, rect: Rect, data: Rc<ImageData>, fit: Fit) -> &mut Self {
+        let rect = self.rect(rect);
+        self.commands.push(DrawCommand::Image { rect, data, fit });
+        self
+    }
+
+    /// Draws recorded commands. Consecutive shapes are batched into one mesh.
+    pub fn render(&self, ctx: &mut Context) -> GameResult {
+        let mut mb = MeshBuilder::new();
+        let mut shapes = false;
+        for command in &self.commands {
+            if command.is_degenerate() {
+                continue;
+            }
+            match command {
+                DrawCommand::FillRect { rect, color } => {
+                    mb.rectangle(DrawMode::fill(), *rect, *color);
+                }
+                DrawCommand::StrokeRect { rect, width, color } => {
+                    mb.rectangle(DrawMode::stroke(*width), *rect, *color);
+                }
+                DrawCommand::Line {
+                    points,
+                    width,
+                    color,
+                } => {
+                    let points: Vec<_> = distinct_points(points)
+                        .iter()
+                        .map(|p| Point2::new(p[0], p[1]))
+                        .collect();
+                    mb.line(&points, *width, *color)?;
+                }
+                DrawCommand::FillCircle {
+                    center,
+                    radius,
+                    color,
+                } => {
+                    let center = Point2::new(center[0], center[1]);
+                    mb.circle(DrawMode::fill(), center, *radius, 0.1, *color);
+                }
+                DrawCommand::StrokeCircle {
+                    center,
+                    radius,
+                    width,
+                    color,
+                } => {
+                    let center = Point2::new(center[0], center[1]);
+                    mb.circle(DrawMode::stroke(*width), center, *radius, 0.1, *color);
+                }
+                DrawCommand::FillPolygon { points, color } => {
+                    let points: Vec<_> = points.iter().map(|p| Point2::new(p[0], p[1])).collect();
+                    mb.polygon(DrawMode::fill(), &points, *color)?;
+                }
+                DrawCommand::Gradient {
+                    rect,
+                    from,
+                    to,
+                    horizontal,
+                } => {
+                    let vertex = |x: f32, y: f32, color: Color| Vertex {
+                        pos: [x, y],
+                        uv: [0., 0.],
+                        color: color.into(),
+                    };
+                    let (tr, bl) = if *horizontal { (to, from) } else { (from, to) };
+                    let verts = [
+                        vertex(rect.x, rect.y, *from),
+                        vertex(rect.x + rect.w, rect.y, *tr),
+                        vertex(rect.x + rect.w, rect.y + rect.h, *to),
+                        vertex(rect.x, rect.y + rect.h, *bl),
+                    ];
+                    mb.raw(&verts, &[0, 1, 2, 0, 2, 3], None);
+                }
+                DrawCommand::Text {
+                    rect,
+                    text,
+                    align,
+                    color,
+                } => {
+                    flush(ctx, &mut mb, &mut shapes)?;
+                    draw_text(ctx, *rect, text, *align, *color)?;
+                    continue;
+                }
+                DrawCommand::Image { rect, data, fit } => {
+                    flush(ctx, &mut mb, &mut shapes)?;
+                    data.draw(ctx, *rect, *fit)?;
+                    continue;
+                }
+            }
+            shapes = true;
+        }
+        flush(ctx, &mut mb, &mut shapes)
+    }
+}
+
+// Draws shapes collected so far, so that text and images go above them
+fn flush(ctx: &mut Context, mb: &mut MeshBuilder, shapes: &mut bool) -> GameResult {
+    if *shapes {
+        let mesh = mb.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+        *mb = MeshBuilder::new();
+        *shapes = false;
+    }
+    Ok(())
+}
@@ -1,5 +1,7 @@
-use crate::gui::{IActions, ILayout, Widget};
-use ggez::event::{EventHandler, MouseButton};
+use crate::gui::capture::PointerCapture;
+use crate::gui::painter::Painter;
+use crate::gui::{collect_hovered_child, IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
 use ggez::graphics::Rect;
 use ggez::{Context, GameResult};
 use std::cell::RefCell;
@@ -7,12 +9,14 @@ use std::rc::Rc;
 
 pub struct Panel<'a> {
     widgets: Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    capture: PointerCapture,
 }
 
 impl<'a> Panel<'a> {
     pub fn new() -> Self {
         Self {
             widgets: Vec::new(),
+            capture: PointerCapture::new(),
         }
     }
 
@@ -22,8 +26,8 @@ impl<'a> Panel<'a> {
     }
 }
 
-impl EventHandler for Panel<'_> {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
+impl IEvents for Panel<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
         for w in &self.widgets {
             w.borrow_mut().update(ctx)?
         }
@@ -37,15 +41,78 @@ impl EventHandler for Panel<'_> {
         Ok(())
     }
 
-    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        for w in &self.widgets {
+            w.borrow_mut().paint_into(ctx, painter)
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        let rects: Vec<_> = self.widgets.iter().map(|w| w.borrow().get_rect()).collect();
+        self.capture.press(button, x, y, rects);
         for w in &self.widgets {
             w.borrow_mut().mouse_button_down_event(ctx, button, x, y)
         }
     }
 
-    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+    // Layers under the press get the release and motion until it, see
+    // `PointerCapture`
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        for (index, w) in self.widgets.iter().enumerate() {
+            if self.capture.routes_to(index) {
+                w.borrow_mut().mouse_button_up_event(ctx, button, x, y)
+            }
+        }
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        for (index, w) in self.widgets.iter().enumerate() {
+            if self.capture.routes_to(index) {
+                w.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy)
+            }
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        for w in &self.widgets {
+            w.borrow_mut().mouse_wheel_event(ctx, x, y)
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
         for w in &self.widgets {
-            w.borrow_mut().mouse_button_up_event(ctx, button, x, y)
+            w.borrow_mut().key_down_event(ctx, keycode, keymods, repeat)
+        }
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        for w in &self.widgets {
+            w.borrow_mut().key_up_event(ctx, keycode, keymods)
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        for w in &self.widgets {
+            w.borrow_mut().text_input_event(ctx, character)
         }
     }
 }
@@ -75,6 +142,20 @@ impl<'a> IActions<'a> for Panel<'a> {
     }
 }
 
+impl<'a> IOverlays<'a> for Panel<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        let mut v = Vec::new();
+        for w in &mut self.widgets {
+            v.append(&mut w.borrow_mut().collect_overlays());
+        }
+        v
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(self.widgets.iter(), x, y, hovered)
+    }
+}
+
 pub struct Builder<'a> {
     panel: Panel<'a>,
 }
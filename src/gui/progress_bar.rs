@@ -0,0 +1,229 @@
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{IActions, IContext, IEvents, ILabel, ILayout, IOverlays, TRcSelf, Widget};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+
+/// State of the progress bar, usable without graphics context. Label
+/// replaces the default percentage text.
+pub trait TBackend<'a>: TRcSelf + ILabel<'a> {
+    fn get_value(&self) -> f32;
+    /// Value is clamped to 0..1
+    fn set_value(&mut self, value: f32);
+    fn is_text_visible(&self) -> bool;
+    fn set_text_visible(&mut self, visible: bool);
+    /// Text drawn over the bar, if any
+    fn text(&self) -> Option<String>;
+}
+
+pub trait TFrontend<'a, BE: TBackend<'a>>: TRcSelf + ILayout + IActions<'a> {
+    fn backend(&self) -> Rc<RefCell<BE>>;
+}
+
+pub struct Backend {
+    value: f32,
+    label: String,
+    text_visible: bool,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl TRcSelf for Backend {
+    fn create() -> Rc<RefCell<Self>> {
+        let v = Rc::new(RefCell::new(Self {
+            value: 0.,
+            label: String::new(),
+            text_visible: true,
+            rcself: None,
+        }));
+        v.borrow_mut().rcself = Some(Rc::downgrade(&v));
+        v
+    }
+    fn wrcself(&self) -> Weak<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().clone()
+    }
+}
+
+impl<'a> TBackend<'a> for Backend {
+    fn get_value(&self) -> f32 {
+        self.value
+    }
+    fn set_value(&mut self, value: f32) {
+        self.value = if value.is_nan() {
+            0.
+        } else {
+            value.clamp(0., 1.)
+        };
+    }
+    fn is_text_visible(&self) -> bool {
+        self.text_visible
+    }
+    fn set_text_visible(&mut self, visible: bool) {
+        self.text_visible = visible;
+    }
+    fn text(&self) -> Option<String> {
+        if !self.text_visible {
+            None
+        } else if self.label.is_empty() {
+            Some(format!("{}%", (self.value * 100.).round()))
+        } else {
+            Some(self.label.clone())
+        }
+    }
+}
+
+impl<'a> ILabel<'a> for Backend {
+    fn get_label(&self) -> String {
+        self.label.clone()
+    }
+    fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+}
+
+pub struct Builder<'a, BE: TBackend<'a>, FE: TFrontend<'a, BE>> {
+    rcfront: Rc<RefCell<FE>>,
+    phantom: PhantomData<&'a BE>,
+}
+
+impl<'a, BE, FE> Builder<'a, BE, FE>
+where
+    BE: TBackend<'a>,
+    FE: TFrontend<'a, BE>,
+{
+    pub fn new() -> Self {
+        Self {
+            rcfront: FE::create(),
+            phantom: PhantomData,
+        }
+    }
+    pub fn set_value(self, value: f32) -> Self {
+        self.rcfront
+            .borrow()
+            .backend()
+            .borrow_mut()
+            .set_value(value);
+        self
+    }
+    pub fn set_label<S: Into<String>>(self, label: S) -> Self {
+        self.rcfront
+            .borrow()
+            .backend()
+            .borrow_mut()
+            .set_label(label.into());
+        self
+    }
+    pub fn set_text_visible(self, visible: bool) -> Self {
+        self.rcfront
+            .borrow()
+            .backend()
+            .borrow_mut()
+            .set_text_visible(visible);
+        self
+    }
+    pub fn build(self) -> Rc<RefCell<FE>> {
+        self.rcfront
+    }
+}
+
+pub struct Frontend<'a, BE: TBackend<'a>> {
+    rcback: Rc<RefCell<BE>>,
+    rect: Rect,
+    rcself: Option<Weak<RefCell<Self>>>,
+    phantom: PhantomData<&'a BE>,
+}
+
+impl<'a, BE> TRcSelf for Frontend<'a, BE>
+where
+    BE: TBackend<'a>,
+{
+    fn create() -> Rc<RefCell<Self>> {
+        let v = Rc::new(RefCell::new(Self {
+            rcback: BE::create(),
+            rect: Rect::zero(),
+            rcself: None,
+            phantom: PhantomData,
+        }));
+        v.borrow_mut().rcself = Some(Rc::downgrade(&v));
+        v
+    }
+    fn wrcself(&self) -> Weak<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().clone()
+    }
+}
+
+impl<'a, BE> TFrontend<'a, BE> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn backend(&self) -> Rc<RefCell<BE>> {
+        self.rcback.clone()
+    }
+}
+
+impl<'a, BE> IEvents for Frontend<'a, BE>
+where
+    BE: TBackend<'a>,
+{
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        let margin = 5.;
+        let mut rect = self.rect;
+        rect.x += margin;
+        rect.y += margin;
+        rect.w -= margin * 2.;
+        rect.h -= margin * 2.;
+        let back = self.rcback.borrow();
+        let mut done = rect;
+        done.w *= back.get_value();
+        painter.fill_rect(rect, graphics::WHITE);
+        if done.w > 0. {
+            painter.fill_rect(done, HIGHLIGHT_COLOR);
+        }
+        painter.stroke_rect(rect, 1., graphics::BLACK);
+        if let Some(text) = back.text() {
+            painter.text(rect, text, Align::Center, graphics::BLACK);
+        }
+    }
+}
+
+impl<'a, BE> ILayout for Frontend<'a, BE>
+where
+    BE: TBackend<'a>,
+{
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a, BE> IActions<'a> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a, BE> IOverlays<'a> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
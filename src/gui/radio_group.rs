@@ -1,14 +1,17 @@
-use crate::gui::{is_same, IActions, ICheckbox, ILayout};
-use ggez::event::EventHandler;
+use crate::gui::{is_same, IActions, ICheckbox, IContext, IEvents, ILayout, IOverlays, Widget};
 use ggez::graphics::Rect;
-use ggez::input::mouse::MouseButton;
 use ggez::{Context, GameResult};
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 
+/// Keeps at most one of the radios checked. Unchecking the checked radio
+/// is undone unless `set_allow_none` is used. The group draws nothing,
+/// neither with `draw` nor with `paint_into`: the radios are drawn by the
+/// container they are placed in.
 pub struct RadioGroup<'a> {
     radios: Vec<Rc<RefCell<dyn ICheckbox<'a> + 'a>>>,
     owned_handler: Option<Rc<dyn Fn(Rc<RefCell<dyn ICheckbox<'a> + 'a>>) + 'a>>,
+    allow_none: bool,
     rcself: Option<Weak<RefCell<Self>>>,
 }
 
@@ -17,6 +20,7 @@ impl<'a> RadioGroup<'a> {
         Self {
             radios: Vec::new(),
             owned_handler: None,
+            allow_none: false,
             rcself: None,
         }
     }
@@ -35,7 +39,7 @@ impl<'a> RadioGroup<'a> {
                             r.borrow_mut().set_state(false);
                         }
                     }
-                } else {
+                } else if !rcself.borrow().allow_none {
                     c.borrow_mut().set_state(true);
                 }
             }));
@@ -43,6 +47,16 @@ impl<'a> RadioGroup<'a> {
         self.owned_handler.as_ref().unwrap().clone()
     }
 
+    /// Lets the checked item be unchecked, leaving none checked. This is
+    /// how accordions of `GroupBox`es allow all sections to be collapsed.
+    pub fn set_allow_none(&mut self, allow_none: bool) {
+        self.allow_none = allow_none;
+    }
+
+    pub fn is_allow_none(&self) -> bool {
+        self.allow_none
+    }
+
     pub fn add_widget(&mut self, w: Rc<RefCell<dyn ICheckbox<'a> + 'a>>) {
         w.borrow_mut().on_changed_rc(self.owned_handler());
         self.radios.push(w);
@@ -58,32 +72,14 @@ impl<'a> RadioGroup<'a> {
     }
 }
 
-impl EventHandler for RadioGroup<'_> {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
+impl IEvents for RadioGroup<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
         Ok(())
     }
 
     fn draw(&mut self, _ctx: &mut Context) -> GameResult {
         Ok(())
     }
-
-    fn mouse_button_down_event(
-        &mut self,
-        _ctx: &mut Context,
-        _button: MouseButton,
-        _x: f32,
-        _y: f32,
-    ) {
-    }
-
-    fn mouse_button_up_event(
-        &mut self,
-        _ctx: &mut Context,
-        _button: MouseButton,
-        _x: f32,
-        _y: f32,
-    ) {
-    }
 }
 
 impl ILayout for RadioGroup<'_> {
@@ -99,6 +95,12 @@ impl<'a> IActions<'a> for RadioGroup<'a> {
     }
 }
 
+impl<'a> IOverlays<'a> for RadioGroup<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
 pub struct RadioGroupBuilder<'a> {
     radio_group: Rc<RefCell<RadioGroup<'a>>>,
 }
@@ -118,4 +120,9 @@ impl<'a> RadioGroupBuilder<'a> {
         self.radio_group.borrow_mut().add_widget(w);
         self
     }
+
+    pub fn set_allow_none(self, allow_none: bool) -> Self {
+        self.radio_group.borrow_mut().set_allow_none(allow_none);
+        self
+    }
 }
@@ -0,0 +1,468 @@
+use crate::gui::harness::{CHAR_WIDTH, LINE_HEIGHT};
+use crate::gui::image::{Fit, ImageData};
+use crate::gui::painter::{DrawCommand, Painter};
+use crate::gui::{IContext, IEvents, TEXT_MARGIN};
+use ggez::graphics::{Align, Color, Rect};
+use ggez::{GameError, GameResult};
+use image::png::PNGEncoder;
+use image::ColorType;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DIFF_COLOR: [u8; 4] = [255, 0, 0, 255];
+
+/// RGBA picture drawn on the CPU from `DrawCommand`s, so that widgets can
+/// be drawn and compared without GPU. Shapes cover the pixels whose
+/// centers are inside them, without antialiasing. There are no fonts: each
+/// char of text is a block in a cell of the `FakeContext` text metrics, so
+/// snapshots show where the text is, not what it says.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+fn to_rgba8(color: Color) -> [u8; 4] {
+    let c = |v: f32| (v.clamp(0., 1.) * 255.).round() as u8;
+    [c(color.r), c(color.g), c(color.b), c(color.a)]
+}
+
+// Source-over blending of non-premultiplied colors
+fn blend(dst: &mut [u8], src: [u8; 4]) {
+    let sa = src[3] as f32 / 255.;
+    if sa <= 0. {
+        return;
+    }
+    let da = dst[3] as f32 / 255.;
+    let a = sa + da * (1. - sa);
+    for i in 0..3 {
+        let c = (src[i] as f32 * sa + dst[i] as f32 * da * (1. - sa)) / a;
+        dst[i] = c.round() as u8;
+    }
+    dst[3] = (a * 255.).round() as u8;
+}
+
+fn distance_to_segment(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> Option<f32> {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let len2 = dx * dx + dy * dy;
+    if len2 <= 0. {
+        return None;
+    }
+    // Butt ends: points beyond the segment ends aren't covered
+    let t = ((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / len2;
+    if !(0. ..=1.).contains(&t) {
+        return None;
+    }
+    Some((p[0] - a[0] - t * dx).hypot(p[1] - a[1] - t * dy))
+}
+
+// Even-odd rule
+fn polygon_contains(points: &[[f32; 2]], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a[1] > y) != (b[1] > y) && x < (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]) + a[0] {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn bounds(points: &[[f32; 2]], grow: f32) -> Rect {
+    let (mut x0, mut y0) = (f32::MAX, f32::MAX);
+    let (mut x1, mut y1) = (f32::MIN, f32::MIN);
+    for p in points {
+        x0 = x0.min(p[0]);
+        y0 = y0.min(p[1]);
+        x1 = x1.max(p[0]);
+        y1 = y1.max(p[1]);
+    }
+    Rect::new(
+        x0 - grow,
+        y0 - grow,
+        x1 - x0 + grow * 2.,
+        y1 - y0 + grow * 2.,
+    )
+}
+
+impl Raster {
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let pixel = to_rgba8(background);
+        Self {
+            width,
+            height,
+            rgba: pixel
+                .iter()
+                .cloned()
+                .cycle()
+                .take(width as usize * height as usize * 4)
+                .collect(),
+        }
+    }
+
+    pub fn from_rgba8(width: u32, height: u32, rgba: Vec<u8>) -> GameResult<Self> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(GameError::ResourceLoadError(format!(
+                "expected {} bytes of RGBA data for {}x{} raster, got {}",
+                width as usize * height as usize * 4,
+                width,
+                height,
+                rgba.len()
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    pub fn from_png_bytes(bytes: &[u8]) -> GameResult<Self> {
+        let decoded = ::image::load_from_memory_with_format(bytes, ::image::ImageFormat::PNG)
+            .map_err(|e| GameError::ResourceLoadError(e.to_string()))?
+            .to_rgba();
+        let (width, height) = decoded.dimensions();
+        Self::from_rgba8(width, height, decoded.into_raw())
+    }
+
+    pub fn load_png<P: AsRef<Path>>(path: P) -> GameResult<Self> {
+        Self::from_png_bytes(&fs::read(path)?)
+    }
+
+    pub fn to_png_bytes(&self) -> GameResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        PNGEncoder::new(&mut bytes).encode(
+            &self.rgba,
+            self.width,
+            self.height,
+            ColorType::RGBA(8),
+        )?;
+        Ok(bytes)
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> GameResult {
+        fs::write(path, self.to_png_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Transparent black outside of the raster
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        if x >= self.width || y >= self.height {
+            return [0; 4];
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.rgba[i],
+            self.rgba[i + 1],
+            self.rgba[i + 2],
+            self.rgba[i + 3],
+        ]
+    }
+
+    /// Draws the commands in order
+    pub fn draw(&mut self, commands: &[DrawCommand]) {
+        for command in commands {
+            self.draw_command(command)
+        }
+    }
+
+    fn draw_command(&mut self, command: &DrawCommand) {
+        match command {
+            DrawCommand::FillRect { rect, color } => {
+                let r = *rect;
+                self.fill(r, *color, |x, y| r.contains([x, y]))
+            }
+            DrawCommand::StrokeRect { rect, width, color } => {
+                // Stroke is centered on the edges
+                let h = width / 2.;
+                let outer = Rect::new(rect.x - h, rect.y - h, rect.w + width, rect.h + width);
+                let inner = Rect::new(rect.x + h, rect.y + h, rect.w - width, rect.h - width);
+                self.fill(outer, *color, |x, y| {
+                    outer.contains([x, y]) && !inner.contains([x, y])
+                })
+            }
+            DrawCommand::Line {
+                points,
+                width,
+                color,
+            } => {
+                if points.len() < 2 {
+                    return;
+                }
+                let h = width / 2.;
+                self.fill(bounds(points, h), *color, |x, y| {
+                    points.windows(2).any(|s| {
+                        let d = distance_to_segment([x, y], s[0], s[1]);
+                        d.filter(|d| *d <= h).is_some()
+                    })
+                })
+            }
+            DrawCommand::FillCircle {
+                center,
+                radius,
+                color,
+            } => {
+                let (c, r) = (*center, *radius);
+                self.fill(bounds(&[c], r), *color, |x, y| {
+                    (x - c[0]).hypot(y - c[1]) <= r
+                })
+            }
+            DrawCommand::StrokeCircle {
+                center,
+                radius,
+                width,
+                color,
+            } => {
+                let (c, r, h) = (*center, *radius, width / 2.);
+                self.fill(bounds(&[c], r + h), *color, |x, y| {
+                    ((x - c[0]).hypot(y - c[1]) - r).abs() <= h
+                })
+            }
+            DrawCommand::FillPolygon { points, color } => {
+                if points.len() < 3 {
+                    return;
+                }
+                self.fill(bounds(points, 0.), *color, |x, y| {
+                    polygon_contains(points, x, y)
+                })
+            }
+            DrawCommand::Gradient {
+                rect,
+                from,
+                to,
+                horizontal,
+            } => self.draw_gradient(*rect, *from, *to, *horizontal),
+            DrawCommand::Text {
+                rect,
+                text,
+                align,
+                color,
+            } => self.draw_text(*rect, text, *align, *color),
+            DrawCommand::Image { rect, data, fit } => self.draw_image(*rect, data, *fit),
+        }
+    }
+
+    // Blends the color into the pixels in the bounds whose centers are inside
+    fn fill<F: Fn(f32, f32) -> bool>(&mut self, bounds: Rect, color: Color, inside: F) {
+        let src = to_rgba8(color);
+        let x0 = bounds.x.floor().max(0.) as u32;
+        let y0 = bounds.y.floor().max(0.) as u32;
+        let x1 = (bounds.right().ceil().max(0.) as u32).min(self.width);
+        let y1 = (bounds.bottom().ceil().max(0.) as u32).min(self.height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if inside(x as f32 + 0.5, y as f32 + 0.5) {
+                    let i = (y as usize * self.width as usize + x as usize) * 4;
+                    blend(&mut self.rgba[i..i + 4], src);
+                }
+            }
+        }
+    }
+
+    // Color of each covered pixel is interpolated at its center
+    fn draw_gradient(&mut self, rect: Rect, from: Color, to: Color, horizontal: bool) {
+        if rect.w <= 0. || rect.h <= 0. {
+            return;
+        }
+        let x0 = rect.x.floor().max(0.) as u32;
+        let y0 = rect.y.floor().max(0.) as u32;
+        let x1 = (rect.right().ceil().max(0.) as u32).min(self.width);
+        let y1 = (rect.bottom().ceil().max(0.) as u32).min(self.height);
+        let mix = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                if !rect.contains([cx, cy]) {
+                    continue;
+                }
+                let t = if horizontal {
+                    (cx - rect.x) / rect.w
+                } else {
+                    (cy - rect.y) / rect.h
+                };
+                let color = Color::new(
+                    mix(from.r, to.r, t),
+                    mix(from.g, to.g, t),
+                    mix(from.b, to.b, t),
+                    mix(from.a, to.a, t),
+                );
+                let i = (y as usize * self.width as usize + x as usize) * 4;
+                blend(&mut self.rgba[i..i + 4], to_rgba8(color));
+            }
+        }
+    }
+
+    // Lays the text out as `draw_text` does, with the `FakeContext` metrics
+    fn draw_text(&mut self, rect: Rect, text: &str, align: Align, color: Color) {
+        let bounds_w = rect.w - TEXT_MARGIN * 2.;
+        let lines: Vec<_> = text.lines().collect();
+        let height = lines.len().max(1) as f32 * LINE_HEIGHT;
+        let top = rect.y + (rect.h - height) / 2.;
+        for (row, line) in lines.iter().enumerate() {
+            let width = line.chars().count() as f32 * CHAR_WIDTH;
+            let left = rect.x
+                + TEXT_MARGIN
+                + match align {
+                    Align::Left => 0.,
+                    Align::Center => (bounds_w - width) / 2.,
+                    Align::Right => bounds_w - width,
+                };
+            let y = top + row as f32 * LINE_HEIGHT;
+            for (col, c) in line.chars().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                let x = left + col as f32 * CHAR_WIDTH;
+                let glyph = Rect::new(x + 1., y + 3., CHAR_WIDTH - 2., LINE_HEIGHT - 6.);
+                self.fill(glyph, color, |x, y| glyph.contains([x, y]));
+            }
+        }
+    }
+
+    // Nearest texel for each covered pixel
+    fn draw_image(&mut self, rect: Rect, data: &ImageData, fit: Fit) {
+        let (w, h) = (data.width() as u32, data.height() as u32);
+        let (src, dest) = fit.place(w as f32, h as f32, rect);
+        if dest.w <= 0. || dest.h <= 0. {
+            return;
+        }
+        let x0 = dest.x.floor().max(0.) as u32;
+        let y0 = dest.y.floor().max(0.) as u32;
+        let x1 = (dest.right().ceil().max(0.) as u32).min(self.width);
+        let y1 = (dest.bottom().ceil().max(0.) as u32).min(self.height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                if !dest.contains([cx, cy]) {
+                    continue;
+                }
+                let u = src.x + (cx - dest.x) / dest.w * src.w;
+                let v = src.y + (cy - dest.y) / dest.h * src.h;
+                let tx = ((u * w as f32) as u32).min(w - 1);
+                let ty = ((v * h as f32) as u32).min(h - 1);
+                let t = (ty as usize * w as usize + tx as usize) * 4;
+                let texel = &data.rgba()[t..t + 4];
+                let i = (y as usize * self.width as usize + x as usize) * 4;
+                blend(
+                    &mut self.rgba[i..i + 4],
+                    [texel[0], texel[1], texel[2], texel[3]],
+                );
+            }
+        }
+    }
+
+    /// Number of pixels which differ by more than the tolerance in any
+    /// channel, and the picture of them: differing pixels are red, the
+    /// others are the pixels of `self` darkened. Rasters of different sizes
+    /// differ in all pixels of the larger size.
+    pub fn diff(&self, other: &Raster, tolerance: u8) -> (usize, Raster) {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        let mut picture = Raster::new(width, height, Color::new(0., 0., 0., 1.));
+        let same_size = self.width == other.width && self.height == other.height;
+        let mut count = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let (a, b) = (self.pixel(x, y), other.pixel(x, y));
+                let differs = !same_size
+                    || a.iter()
+                        .zip(&b)
+                        .any(|(a, b)| (*a as i16 - *b as i16).abs() > tolerance as i16);
+                let i = (y as usize * width as usize + x as usize) * 4;
+                let out = if differs {
+                    count += 1;
+                    DIFF_COLOR
+                } else {
+                    [a[0] / 4, a[1] / 4, a[2] / 4, 255]
+                };
+                picture.rgba[i..i + 4].copy_from_slice(&out);
+            }
+        }
+        (count, picture)
+    }
+}
+
+/// Records the widget with `paint_into` and draws it on a raster of the
+/// size
+pub fn render(
+    widget: &mut dyn IEvents,
+    ctx: &mut dyn IContext,
+    width: u32,
+    height: u32,
+    background: Color,
+) -> Raster {
+    let mut painter = Painter::new([0., 0.]);
+    widget.paint_into(ctx, &mut painter);
+    let mut raster = Raster::new(width, height, background);
+    raster.draw(painter.commands());
+    raster
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+/// Compares the raster with the golden PNG, allowing each channel to
+/// differ by the tolerance. On mismatch writes `<name>.actual.png` and
+/// `<name>.diff.png` next to the golden and panics. A missing golden is a
+/// mismatch too. With `UPDATE_GOLDEN` set in the environment the golden is
+/// written from the raster instead.
+pub fn assert_snapshot<P: AsRef<Path>>(raster: &Raster, golden: P, tolerance: u8) {
+    let golden = golden.as_ref();
+    let actual_path = sibling(golden, "actual");
+    let diff_path = sibling(golden, "diff");
+    if let Some(dir) = golden.parent() {
+        fs::create_dir_all(dir).expect("can't create the golden directory");
+    }
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        raster.save_png(golden).expect("can't write the golden");
+        return;
+    }
+    let expected = match Raster::load_png(golden) {
+        Ok(expected) => expected,
+        Err(e) => {
+            let _ = raster.save_png(&actual_path);
+            panic!(
+                "can't load golden {}: {}, the result is in {}; run with UPDATE_GOLDEN=1 to accept it",
+                golden.display(),
+                e,
+                actual_path.display()
+            );
+        }
+    };
+    let (count, diff) = expected.diff(raster, tolerance);
+    if count == 0 {
+        let _ = fs::remove_file(&actual_path);
+        let _ = fs::remove_file(&diff_path);
+        return;
+    }
+    let _ = raster.save_png(&actual_path);
+    let _ = diff.save_png(&diff_path);
+    panic!(
+        "{} pixels differ from golden {} ({}x{} vs {}x{}), see {} and {}",
+        count,
+        golden.display(),
+        raster.width(),
+        raster.height(),
+        expected.width(),
+        expected.height(),
+        actual_path.display(),
+        diff_path.display()
+    );
+}
@@ -0,0 +1,767 @@
+use crate::gui::harness::FakeContext;
+use crate::gui::painter::Painter;
+use crate::gui::shortcuts::{self, KeyChord};
+use crate::gui::touch::TouchPhase;
+use crate::gui::window_manager::WindowManager;
+use crate::gui::{IContext, IEvents};
+use ggez::event::{Axis, Button, EventHandler, GamepadId, KeyCode, KeyMods, MouseButton};
+use ggez::{Context, GameError, GameResult};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const HEADER: &str = "# yorool_gui recording 1";
+
+/// Input event as it came to the window manager. Modifiers are recorded as
+/// a separate event when they change, since widgets read them from the
+/// context on mouse events too.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordedEvent {
+    /// `update` with the frame delta
+    Frame(Duration),
+    Resize(f32, f32),
+    Mods(KeyMods),
+    MouseDown(MouseButton, f32, f32),
+    MouseUp(MouseButton, f32, f32),
+    MouseMotion(f32, f32, f32, f32),
+    MouseWheel(f32, f32),
+    KeyDown(KeyChord, bool),
+    KeyUp(KeyChord),
+    TextInput(char),
+    /// Finger id, phase and position
+    Touch(u64, TouchPhase, f32, f32),
+    GamepadButton(Button),
+    GamepadAxis(Axis, f32),
+}
+
+/// Event with the time since start it came at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Record {
+    pub time: Duration,
+    pub event: RecordedEvent,
+}
+
+// Every key code, keys are written by their variant names
+const KEY_CODES: &[KeyCode] = &[
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Key0,
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Escape,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+    KeyCode::Snapshot,
+    KeyCode::Scroll,
+    KeyCode::Pause,
+    KeyCode::Insert,
+    KeyCode::Home,
+    KeyCode::Delete,
+    KeyCode::End,
+    KeyCode::PageDown,
+    KeyCode::PageUp,
+    KeyCode::Left,
+    KeyCode::Up,
+    KeyCode::Right,
+    KeyCode::Down,
+    KeyCode::Back,
+    KeyCode::Return,
+    KeyCode::Space,
+    KeyCode::Compose,
+    KeyCode::Caret,
+    KeyCode::Numlock,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+    KeyCode::AbntC1,
+    KeyCode::AbntC2,
+    KeyCode::Add,
+    KeyCode::Apostrophe,
+    KeyCode::Apps,
+    KeyCode::At,
+    KeyCode::Ax,
+    KeyCode::Backslash,
+    KeyCode::Calculator,
+    KeyCode::Capital,
+    KeyCode::Colon,
+    KeyCode::Comma,
+    KeyCode::Convert,
+    KeyCode::Decimal,
+    KeyCode::Divide,
+    KeyCode::Equals,
+    KeyCode::Grave,
+    KeyCode::Kana,
+    KeyCode::Kanji,
+    KeyCode::LAlt,
+    KeyCode::LBracket,
+    KeyCode::LControl,
+    KeyCode::LShift,
+    KeyCode::LWin,
+    KeyCode::Mail,
+    KeyCode::MediaSelect,
+    KeyCode::MediaStop,
+    KeyCode::Minus,
+    KeyCode::Multiply,
+    KeyCode::Mute,
+    KeyCode::MyComputer,
+    KeyCode::NavigateForward,
+    KeyCode::NavigateBackward,
+    KeyCode::NextTrack,
+    KeyCode::NoConvert,
+    KeyCode::NumpadComma,
+    KeyCode::NumpadEnter,
+    KeyCode::NumpadEquals,
+    KeyCode::OEM102,
+    KeyCode::Period,
+    KeyCode::PlayPause,
+    KeyCode::Power,
+    KeyCode::PrevTrack,
+    KeyCode::RAlt,
+    KeyCode::RBracket,
+    KeyCode::RControl,
+    KeyCode::RShift,
+    KeyCode::RWin,
+    KeyCode::Semicolon,
+    KeyCode::Slash,
+    KeyCode::Sleep,
+    KeyCode::Stop,
+    KeyCode::Subtract,
+    KeyCode::Sysrq,
+    KeyCode::Tab,
+    KeyCode::Underline,
+    KeyCode::Unlabeled,
+    KeyCode::VolumeDown,
+    KeyCode::VolumeUp,
+    KeyCode::Wake,
+    KeyCode::WebBack,
+    KeyCode::WebFavorites,
+    KeyCode::WebForward,
+    KeyCode::WebHome,
+    KeyCode::WebRefresh,
+    KeyCode::WebSearch,
+    KeyCode::WebStop,
+    KeyCode::Yen,
+    KeyCode::Copy,
+    KeyCode::Paste,
+    KeyCode::Cut,
+];
+
+const TOUCH_PHASES: &[(TouchPhase, &str)] = &[
+    (TouchPhase::Started, "started"),
+    (TouchPhase::Moved, "moved"),
+    (TouchPhase::Ended, "ended"),
+    (TouchPhase::Cancelled, "cancelled"),
+];
+
+// Gamepad buttons and axes are written by their variant names too
+const GAMEPAD_BUTTONS: &[Button] = &[
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::C,
+    Button::Z,
+    Button::LeftTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+    Button::Unknown,
+];
+
+const GAMEPAD_AXES: &[Axis] = &[
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::LeftZ,
+    Axis::RightStickX,
+    Axis::RightStickY,
+    Axis::RightZ,
+    Axis::DPadX,
+    Axis::DPadY,
+    Axis::Unknown,
+];
+
+fn format_phase(phase: TouchPhase) -> &'static str {
+    TOUCH_PHASES.iter().find(|(p, _)| *p == phase).unwrap().1
+}
+
+fn parse_phase(s: &str) -> Option<TouchPhase> {
+    TOUCH_PHASES
+        .iter()
+        .find(|(_, name)| *name == s)
+        .map(|(p, _)| *p)
+}
+
+fn parse_name<T: fmt::Debug + Copy>(values: &[T], s: &str) -> Option<T> {
+    values.iter().find(|v| format!("{:?}", v) == s).copied()
+}
+
+fn format_button(button: MouseButton) -> String {
+    match button {
+        MouseButton::Left => "left".to_string(),
+        MouseButton::Right => "right".to_string(),
+        MouseButton::Middle => "middle".to_string(),
+        MouseButton::Other(n) => n.to_string(),
+    }
+}
+
+fn parse_button(s: &str) -> Option<MouseButton> {
+    match s {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        _ => s.parse().ok().map(MouseButton::Other),
+    }
+}
+
+// Whitespace and control chars are written as code points, so that each
+// record stays on one line and splits on spaces
+fn format_char(c: char) -> String {
+    if c.is_whitespace() || c.is_control() {
+        format!("U+{:04X}", c as u32)
+    } else {
+        c.to_string()
+    }
+}
+
+fn parse_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ if s.starts_with("U+") => std::char::from_u32(u32::from_str_radix(&s[2..], 16).ok()?),
+        _ => None,
+    }
+}
+
+fn format_mods(keymods: KeyMods) -> String {
+    if keymods.is_empty() {
+        "none".to_string()
+    } else {
+        shortcuts::format_keymods(keymods)
+    }
+}
+
+fn parse_mods(s: &str) -> Option<KeyMods> {
+    if s == "none" {
+        Some(KeyMods::NONE)
+    } else {
+        shortcuts::parse_keymods(s)
+    }
+}
+
+// Modifiers and the key code as separate words, e.g. "none LShift"
+fn format_key(chord: KeyChord) -> String {
+    format!("{} {:?}", format_mods(chord.keymods), chord.keycode)
+}
+
+fn parse_key(keymods: &str, keycode: &str) -> Option<KeyChord> {
+    let keycode = KEY_CODES.iter().find(|k| format!("{:?}", k) == keycode)?;
+    Some(KeyChord::new(*keycode, parse_mods(keymods)?))
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ", self.time.as_micros())?;
+        match self.event {
+            RecordedEvent::Frame(delta) => write!(f, "frame {}", delta.as_micros()),
+            RecordedEvent::Resize(w, h) => write!(f, "resize {} {}", w, h),
+            RecordedEvent::Mods(keymods) => write!(f, "mods {}", format_mods(keymods)),
+            RecordedEvent::MouseDown(button, x, y) => {
+                write!(f, "down {} {} {}", format_button(button), x, y)
+            }
+            RecordedEvent::MouseUp(button, x, y) => {
+                write!(f, "up {} {} {}", format_button(button), x, y)
+            }
+            RecordedEvent::MouseMotion(x, y, dx, dy) => {
+                write!(f, "move {} {} {} {}", x, y, dx, dy)
+            }
+            RecordedEvent::MouseWheel(x, y) => write!(f, "wheel {} {}", x, y),
+            RecordedEvent::KeyDown(chord, repeat) => {
+                write!(f, "key_down {}", format_key(chord))?;
+                if repeat {
+                    write!(f, " repeat")?;
+                }
+                Ok(())
+            }
+            RecordedEvent::KeyUp(chord) => write!(f, "key_up {}", format_key(chord)),
+            RecordedEvent::TextInput(c) => write!(f, "text {}", format_char(c)),
+            RecordedEvent::Touch(id, phase, x, y) => {
+                write!(f, "touch {} {} {} {}", id, format_phase(phase), x, y)
+            }
+            RecordedEvent::GamepadButton(btn) => write!(f, "pad_button {:?}", btn),
+            RecordedEvent::GamepadAxis(axis, value) => write!(f, "pad_axis {:?} {}", axis, value),
+        }
+    }
+}
+
+impl Record {
+    /// Parses a line written by `Display`
+    pub fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<_> = line.split_whitespace().collect();
+        let (time, name, args) = match parts.as_slice() {
+            [time, name, args @ ..] => (time, *name, args),
+            _ => return None,
+        };
+        let time = Duration::from_micros(time.parse().ok()?);
+        let float = |index: usize| -> Option<f32> { args.get(index)?.parse().ok() };
+        let event = match (name, args.len()) {
+            ("frame", 1) => RecordedEvent::Frame(Duration::from_micros(args[0].parse().ok()?)),
+            ("resize", 2) => RecordedEvent::Resize(float(0)?, float(1)?),
+            ("mods", 1) => RecordedEvent::Mods(parse_mods(args[0])?),
+            ("down", 3) => RecordedEvent::MouseDown(parse_button(args[0])?, float(1)?, float(2)?),
+            ("up", 3) => RecordedEvent::MouseUp(parse_button(args[0])?, float(1)?, float(2)?),
+            ("move", 4) => RecordedEvent::MouseMotion(float(0)?, float(1)?, float(2)?, float(3)?),
+            ("wheel", 2) => RecordedEvent::MouseWheel(float(0)?, float(1)?),
+            ("key_down", 2) => RecordedEvent::KeyDown(parse_key(args[0], args[1])?, false),
+            ("key_down", 3) if args[2] == "repeat" => {
+                RecordedEvent::KeyDown(parse_key(args[0], args[1])?, true)
+            }
+            ("key_up", 2) => RecordedEvent::KeyUp(parse_key(args[0], args[1])?),
+            ("text", 1) => RecordedEvent::TextInput(parse_char(args[0])?),
+            ("touch", 4) => RecordedEvent::Touch(
+                args[0].parse().ok()?,
+                parse_phase(args[1])?,
+                float(2)?,
+                float(3)?,
+            ),
+            ("pad_button", 1) => {
+                RecordedEvent::GamepadButton(parse_name(GAMEPAD_BUTTONS, args[0])?)
+            }
+            ("pad_axis", 2) => {
+                RecordedEvent::GamepadAxis(parse_name(GAMEPAD_AXES, args[0])?, float(1)?)
+            }
+            _ => return None,
+        };
+        Some(Self { time, event })
+    }
+}
+
+/// Writes the records one per line after a header line
+pub fn format_records(records: &[Record]) -> String {
+    let mut text = String::from(HEADER);
+    text.push('\n');
+    for record in records {
+        text.push_str(&record.to_string());
+        text.push('\n');
+    }
+    text
+}
+
+/// Parses records written by `format_records`. Empty lines and lines
+/// starting with '#' are skipped, so notes can be added to a recording.
+pub fn parse_records(text: &str) -> GameResult<Vec<Record>> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            Record::parse(line).ok_or_else(|| {
+                GameError::ResourceLoadError(format!("Bad recording line {}: {}", index + 1, line))
+            })
+        })
+        .collect()
+}
+
+/// Wraps `WindowManager` and records every event passed to it before
+/// forwarding it. Run it with `event::run` in place of the window manager,
+/// then `save` the recording.
+pub struct Recorder<'a> {
+    window_manager: WindowManager<'a>,
+    records: Vec<Record>,
+    keymods: KeyMods,
+    size: Option<(f32, f32)>,
+}
+
+impl<'a> Recorder<'a> {
+    pub fn new(window_manager: WindowManager<'a>) -> Self {
+        Self {
+            window_manager,
+            records: Vec::new(),
+            keymods: KeyMods::NONE,
+            size: None,
+        }
+    }
+
+    pub fn window_manager(&mut self) -> &mut WindowManager<'a> {
+        &mut self.window_manager
+    }
+
+    pub fn into_inner(self) -> WindowManager<'a> {
+        self.window_manager
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.keymods = KeyMods::NONE;
+        self.size = None;
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> GameResult {
+        fs::write(path, format_records(&self.records))?;
+        Ok(())
+    }
+
+    // Records the window size before the first event and the modifiers
+    // whenever they change, then the event itself
+    fn record(&mut self, ctx: &mut dyn IContext, event: RecordedEvent) {
+        let time = ctx.time_since_start();
+        if self.size.is_none() {
+            let (width, height) = ctx.drawable_size();
+            self.size = Some((width, height));
+            self.records.push(Record {
+                time,
+                event: RecordedEvent::Resize(width, height),
+            });
+        }
+        let keymods = ctx.keymods();
+        if keymods != self.keymods {
+            self.keymods = keymods;
+            self.records.push(Record {
+                time,
+                event: RecordedEvent::Mods(keymods),
+            });
+        }
+        self.records.push(Record { time, event });
+    }
+}
+
+impl IEvents for Recorder<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        let delta = ctx.delta();
+        self.record(ctx, RecordedEvent::Frame(delta));
+        IEvents::update(&mut self.window_manager, ctx)
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        IEvents::draw(&mut self.window_manager, ctx)
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        IEvents::paint_into(&mut self.window_manager, ctx, painter)
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.record(ctx, RecordedEvent::MouseDown(button, x, y));
+        IEvents::mouse_button_down_event(&mut self.window_manager, ctx, button, x, y)
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.record(ctx, RecordedEvent::MouseUp(button, x, y));
+        IEvents::mouse_button_up_event(&mut self.window_manager, ctx, button, x, y)
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        self.record(ctx, RecordedEvent::MouseMotion(x, y, dx, dy));
+        IEvents::mouse_motion_event(&mut self.window_manager, ctx, x, y, dx, dy)
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        self.record(ctx, RecordedEvent::MouseWheel(x, y));
+        IEvents::mouse_wheel_event(&mut self.window_manager, ctx, x, y)
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        let chord = KeyChord::new(keycode, keymods);
+        self.record(ctx, RecordedEvent::KeyDown(chord, repeat));
+        IEvents::key_down_event(&mut self.window_manager, ctx, keycode, keymods, repeat)
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        let chord = KeyChord::new(keycode, keymods);
+        self.record(ctx, RecordedEvent::KeyUp(chord));
+        IEvents::key_up_event(&mut self.window_manager, ctx, keycode, keymods)
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        self.record(ctx, RecordedEvent::TextInput(character));
+        IEvents::text_input_event(&mut self.window_manager, ctx, character)
+    }
+
+    fn resize_event(&mut self, ctx: &mut dyn IContext, width: f32, height: f32) {
+        self.size = Some((width, height));
+        self.record(ctx, RecordedEvent::Resize(width, height));
+        IEvents::resize_event(&mut self.window_manager, ctx, width, height)
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut dyn IContext, btn: Button) {
+        self.record(ctx, RecordedEvent::GamepadButton(btn));
+        IEvents::gamepad_button_down_event(&mut self.window_manager, ctx, btn)
+    }
+
+    fn gamepad_axis_event(&mut self, ctx: &mut dyn IContext, axis: Axis, value: f32) {
+        self.record(ctx, RecordedEvent::GamepadAxis(axis, value));
+        IEvents::gamepad_axis_event(&mut self.window_manager, ctx, axis, value)
+    }
+
+    fn touch_event(&mut self, ctx: &mut dyn IContext, id: u64, phase: TouchPhase, x: f32, y: f32) {
+        self.record(ctx, RecordedEvent::Touch(id, phase, x, y));
+        IEvents::touch_event(&mut self.window_manager, ctx, id, phase, x, y)
+    }
+}
+
+impl EventHandler for Recorder<'_> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        IEvents::update(self, ctx)
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        IEvents::draw(self, ctx)
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        IEvents::mouse_button_down_event(self, ctx, button, x, y)
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        IEvents::mouse_button_up_event(self, ctx, button, x, y)
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        IEvents::mouse_motion_event(self, ctx, x, y, dx, dy)
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        IEvents::mouse_wheel_event(self, ctx, x, y)
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        IEvents::key_down_event(self, ctx, keycode, keymods, repeat)
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods) {
+        IEvents::key_up_event(self, ctx, keycode, keymods)
+    }
+
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) {
+        IEvents::text_input_event(self, ctx, character)
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        IEvents::resize_event(self, ctx, width, height)
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
+        IEvents::gamepad_button_down_event(self, ctx, btn)
+    }
+
+    fn gamepad_axis_event(&mut self, ctx: &mut Context, axis: Axis, value: f32, _id: GamepadId) {
+        IEvents::gamepad_axis_event(self, ctx, axis, value)
+    }
+}
+
+/// Feeds a recording to a window manager running on `FakeContext`. The
+/// context clock, frame deltas, window size and modifiers are all taken
+/// from the recording, so a replay gives the same result every time.
+pub struct Player {
+    records: Vec<Record>,
+    position: usize,
+    pointer: [f32; 2],
+}
+
+impl Player {
+    pub fn new(records: Vec<Record>) -> Self {
+        Self {
+            records,
+            position: 0,
+            pointer: [0., 0.],
+        }
+    }
+
+    pub fn parse(text: &str) -> GameResult<Self> {
+        Ok(Self::new(parse_records(text)?))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> GameResult<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.records.len()
+    }
+
+    /// Time of the last record
+    pub fn duration(&self) -> Duration {
+        self.records.last().map(|r| r.time).unwrap_or_default()
+    }
+
+    /// Last pointer position sent by the replay
+    pub fn pointer(&self) -> [f32; 2] {
+        self.pointer
+    }
+
+    /// Starts the replay over. The target isn't reset.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+        self.pointer = [0., 0.];
+    }
+
+    /// Sends the next record. Returns false when the recording is over.
+    pub fn step(&mut self, target: &mut dyn IEvents, ctx: &mut FakeContext) -> GameResult<bool> {
+        let record = match self.records.get(self.position) {
+            Some(record) => *record,
+            None => return Ok(false),
+        };
+        self.position += 1;
+        let delta = match record.event {
+            RecordedEvent::Frame(delta) => delta,
+            _ => ctx.delta(),
+        };
+        ctx.set_time(record.time, delta);
+        match record.event {
+            RecordedEvent::Frame(_) => target.update(ctx)?,
+            RecordedEvent::Resize(w, h) => {
+                ctx.set_drawable_size(w, h);
+                target.resize_event(ctx, w, h);
+            }
+            RecordedEvent::Mods(keymods) => ctx.set_keymods(keymods),
+            RecordedEvent::MouseDown(button, x, y) => {
+                self.pointer = [x, y];
+                target.mouse_button_down_event(ctx, button, x, y);
+            }
+            RecordedEvent::MouseUp(button, x, y) => {
+                self.pointer = [x, y];
+                target.mouse_button_up_event(ctx, button, x, y);
+            }
+            RecordedEvent::MouseMotion(x, y, dx, dy) => {
+                self.pointer = [x, y];
+                target.mouse_motion_event(ctx, x, y, dx, dy);
+            }
+            RecordedEvent::MouseWheel(x, y) => target.mouse_wheel_event(ctx, x, y),
+            RecordedEvent::KeyDown(chord, repeat) => {
+                target.key_down_event(ctx, chord.keycode, chord.keymods, repeat)
+            }
+            RecordedEvent::KeyUp(chord) => target.key_up_event(ctx, chord.keycode, chord.keymods),
+            RecordedEvent::TextInput(c) => target.text_input_event(ctx, c),
+            RecordedEvent::Touch(id, phase, x, y) => target.touch_event(ctx, id, phase, x, y),
+            RecordedEvent::GamepadButton(btn) => target.gamepad_button_down_event(ctx, btn),
+            RecordedEvent::GamepadAxis(axis, value) => target.gamepad_axis_event(ctx, axis, value),
+        }
+        Ok(true)
+    }
+
+    /// Sends the records up to and including the time, e.g. to check the
+    /// state in the middle of a recording
+    pub fn play_until(
+        &mut self,
+        time: Duration,
+        target: &mut dyn IEvents,
+        ctx: &mut FakeContext,
+    ) -> GameResult {
+        while self
+            .records
+            .get(self.position)
+            .filter(|r| r.time <= time)
+            .is_some()
+        {
+            self.step(target, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Sends the rest of the recording
+    pub fn play(&mut self, target: &mut dyn IEvents, ctx: &mut FakeContext) -> GameResult {
+        while self.step(target, ctx)? {}
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
+use crate::gui::capture::PointerCapture;
+use crate::gui::painter::Painter;
+use crate::gui::{collect_hovered_child, IActions, IContext, IEvents, ILayout, IOverlays};
 use crate::gui::{is_same, Widget};
-use crate::gui::{IActions, ILayout};
-use ggez::event::{EventHandler, MouseButton};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
 use ggez::graphics::Rect;
 use ggez::{Context, GameResult};
 use std::cell::RefCell;
@@ -10,6 +12,8 @@ pub struct Ribbon<'a> {
     widgets: Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>,
     rect: Rect,
     horizontal: bool,
+    layout_changed: bool,
+    capture: PointerCapture,
     rcself: Option<Weak<RefCell<Self>>>,
 }
 
@@ -19,6 +23,8 @@ impl<'a> Ribbon<'a> {
             widgets: Vec::new(),
             rect: Rect::zero(),
             horizontal: true,
+            layout_changed: false,
+            capture: PointerCapture::new(),
             rcself: None,
         }
     }
@@ -39,9 +45,72 @@ impl<'a> Ribbon<'a> {
 
     pub fn remove_widget<T: ?Sized>(&mut self, w: Rc<RefCell<T>>) -> &mut Self {
         self.widgets.drain_filter(|pw| is_same(pw, &w)).count();
+        self.capture.cancel();
         self
     }
 
+    pub fn insert_widget(
+        &mut self,
+        index: usize,
+        widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+    ) -> &mut Self {
+        let index = index.min(self.widgets.len());
+        self.widgets.insert(index, widget);
+        self.capture.cancel();
+        self.set_rect(self.rect);
+        self.layout_changed = true;
+        self
+    }
+
+    pub fn widget_count(&self) -> usize {
+        self.widgets.len()
+    }
+
+    pub fn index_of<T: ?Sized>(&self, w: Rc<RefCell<T>>) -> Option<usize> {
+        self.widgets.iter().position(|pw| is_same(pw, &w))
+    }
+
+    /// Moves the widget to the `to` position, counted before the removal.
+    /// Arguments are the same as the ones of `insert_index_at`, so a
+    /// dropped child is moved with `move_widget(from, insert_index_at(x, y))`.
+    pub fn move_widget(&mut self, from: usize, to: usize) -> &mut Self {
+        if from < self.widgets.len() {
+            let widget = self.widgets.remove(from);
+            let to = if to > from { to - 1 } else { to };
+            self.widgets.insert(to.min(self.widgets.len()), widget);
+            self.capture.cancel();
+            self.set_rect(self.rect);
+            self.layout_changed = true;
+        }
+        self
+    }
+
+    /// Position a widget dropped at the point would be inserted at: before
+    /// the first child whose middle is after the point
+    pub fn insert_index_at(&self, x: f32, y: f32) -> usize {
+        self.widgets
+            .iter()
+            .position(|w| {
+                let r = w.borrow().get_rect();
+                if self.horizontal {
+                    x < r.x + r.w / 2.
+                } else {
+                    y < r.y + r.h / 2.
+                }
+            })
+            .unwrap_or(self.widgets.len())
+    }
+
+    // Size of the widget along the ribbon direction, `None` if flexible
+    fn preferred_length(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<f32> {
+        let (w, h) = widget.borrow().get_preferred_size();
+        if self.horizontal {
+            w
+        } else {
+            h
+        }
+    }
+
     fn for_all_res<F: FnMut(Rc<RefCell<dyn Widget<'a> + 'a>>) -> GameResult>(
         &self,
         mut f: F,
@@ -57,50 +126,139 @@ impl<'a> Ribbon<'a> {
             f(w.clone())
         }
     }
+
+    // Children which get pointer motion and releases, see `PointerCapture`
+    fn for_captured<F: FnMut(Rc<RefCell<dyn Widget + 'a>>)>(&self, mut f: F) {
+        for (index, w) in self.widgets.iter().enumerate() {
+            if self.capture.routes_to(index) {
+                f(w.clone())
+            }
+        }
+    }
 }
 
-impl EventHandler for Ribbon<'_> {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        self.for_all_res(|w| w.borrow_mut().update(ctx))
+impl IEvents for Ribbon<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.for_all_res(|w| w.borrow_mut().update(ctx))?;
+        let mut changed = false;
+        self.for_all(|w| changed |= w.borrow_mut().take_layout_changed());
+        if changed {
+            self.set_rect(self.rect);
+            self.layout_changed = true;
+        }
+        Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         self.for_all_res(|w| w.borrow_mut().draw(ctx))
     }
 
-    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.for_all(|w| w.borrow_mut().paint_into(ctx, painter))
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        let rects: Vec<_> = self.widgets.iter().map(|w| w.borrow().get_rect()).collect();
+        self.capture.press(button, x, y, rects);
         self.for_all(|w| w.borrow_mut().mouse_button_down_event(ctx, button, x, y))
     }
 
-    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
-        self.for_all(|w| w.borrow_mut().mouse_button_up_event(ctx, button, x, y))
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.for_captured(|w| w.borrow_mut().mouse_button_up_event(ctx, button, x, y));
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        self.for_captured(|w| w.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy))
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        self.for_all(|w| w.borrow_mut().mouse_wheel_event(ctx, x, y))
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        self.for_all(|w| w.borrow_mut().key_down_event(ctx, keycode, keymods, repeat))
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        self.for_all(|w| w.borrow_mut().key_up_event(ctx, keycode, keymods))
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        self.for_all(|w| w.borrow_mut().text_input_event(ctx, character))
     }
 }
 
 impl ILayout for Ribbon<'_> {
     fn set_rect(&mut self, rect: Rect) {
         self.rect = rect;
-        if self.horizontal {
-            let dw = self.rect.w / self.widgets.len() as f32;
-            let mut x = self.rect.x;
-            self.for_all(|wgt| {
-                wgt.borrow_mut()
-                    .set_rect(Rect::new(x, self.rect.y, dw, self.rect.h));
-                x += dw;
-            });
+        let total = if self.horizontal { rect.w } else { rect.h };
+        let lengths: Vec<_> = self
+            .widgets
+            .iter()
+            .map(|w| self.preferred_length(w))
+            .collect();
+        let fixed: f32 = lengths.iter().flatten().sum();
+        let flexible = lengths.iter().filter(|l| l.is_none()).count();
+        // Fixed widgets are shrunk proportionally when they don't fit
+        let scale = if fixed > total { total / fixed } else { 1. };
+        let share = if flexible > 0 {
+            (total - fixed * scale) / flexible as f32
         } else {
-            let dh = self.rect.h / self.widgets.len() as f32;
-            let mut y = self.rect.y;
-            self.for_all(|wgt| {
-                wgt.borrow_mut()
-                    .set_rect(Rect::new(self.rect.x, y, self.rect.w, dh));
-                y += dh;
-            });
+            0.
+        };
+        let mut pos = if self.horizontal { rect.x } else { rect.y };
+        for (wgt, length) in self.widgets.iter().zip(lengths) {
+            let length = length.map_or(share, |l| l * scale);
+            let wrect = if self.horizontal {
+                Rect::new(pos, rect.y, length, rect.h)
+            } else {
+                Rect::new(rect.x, pos, rect.w, length)
+            };
+            wgt.borrow_mut().set_rect(wrect);
+            pos += length;
         }
     }
     fn get_rect(&self) -> Rect {
         self.rect.clone()
     }
+    fn get_preferred_size(&self) -> (Option<f32>, Option<f32>) {
+        // Ribbon has fixed length only if all its widgets have it
+        let length = if self.widgets.is_empty() {
+            None
+        } else {
+            self.widgets
+                .iter()
+                .map(|w| self.preferred_length(w))
+                .sum::<Option<f32>>()
+        };
+        if self.horizontal {
+            (length, None)
+        } else {
+            (None, length)
+        }
+    }
+    fn take_layout_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.layout_changed, false)
+    }
 }
 
 impl<'a> IActions<'a> for Ribbon<'a> {
@@ -113,6 +271,20 @@ impl<'a> IActions<'a> for Ribbon<'a> {
     }
 }
 
+impl<'a> IOverlays<'a> for Ribbon<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        let mut v = Vec::new();
+        for w in &mut self.widgets {
+            v.append(&mut w.borrow_mut().collect_overlays());
+        }
+        v
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(self.widgets.iter(), x, y, hovered)
+    }
+}
+
 pub struct Builder<'a> {
     ribbon: Ribbon<'a>,
 }
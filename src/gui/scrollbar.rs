@@ -0,0 +1,146 @@
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use ggez::graphics::{self, Rect};
+
+/// Vertical scrollbar for widgets which show a window of rows. It doesn't
+/// handle events itself: the owning widget passes pointer positions to it
+/// and paints it with its own shapes.
+pub struct ScrollBar {
+    rect: Rect,
+    total: usize,
+    visible: usize,
+    first: usize,
+    drag: Option<f32>,
+}
+
+impl ScrollBar {
+    pub const WIDTH: f32 = 10.;
+
+    pub fn new() -> Self {
+        Self {
+            rect: Rect::zero(),
+            total: 0,
+            visible: 0,
+            first: 0,
+            drag: None,
+        }
+    }
+
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    pub fn get_rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn set_range(&mut self, total: usize, visible: usize) {
+        self.total = total;
+        self.visible = visible;
+        self.set_first(self.first);
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn visible(&self) -> usize {
+        self.visible
+    }
+
+    /// Range of rows currently shown
+    pub fn rows(&self) -> std::ops::Range<usize> {
+        self.first..(self.first + self.visible).min(self.total)
+    }
+
+    pub fn is_needed(&self) -> bool {
+        self.total > self.visible
+    }
+
+    pub fn first(&self) -> usize {
+        self.first
+    }
+
+    pub fn set_first(&mut self, first: usize) {
+        self.first = first.min(self.max_first());
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let first = (self.first as isize + delta).max(0) as usize;
+        self.set_first(first);
+    }
+
+    /// Scrolls minimally so that the row becomes visible
+    pub fn scroll_to(&mut self, row: usize) {
+        if row < self.first {
+            self.set_first(row);
+        } else if self.visible > 0 && row >= self.first + self.visible {
+            self.set_first(row + 1 - self.visible);
+        }
+    }
+
+    fn max_first(&self) -> usize {
+        self.total.saturating_sub(self.visible)
+    }
+
+    fn thumb(&self) -> Rect {
+        let h = if self.total > 0 {
+            (self.rect.h * self.visible as f32 / self.total as f32).max(Self::WIDTH)
+        } else {
+            self.rect.h
+        };
+        let h = h.min(self.rect.h);
+        let y = match self.max_first() {
+            0 => 0.,
+            max_first => (self.rect.h - h) * self.first as f32 / max_first as f32,
+        };
+        Rect::new(self.rect.x, self.rect.y + y, self.rect.w, h)
+    }
+
+    fn drag_to(&mut self, y: f32) {
+        if let Some(offset) = self.drag {
+            let thumb = self.thumb();
+            let free = self.rect.h - thumb.h;
+            if free > 0. {
+                let ratio = ((y - offset - self.rect.y) / free).clamp(0., 1.);
+                self.set_first((ratio * self.max_first() as f32).round() as usize);
+            }
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Starts dragging the thumb. Click outside of the thumb moves it
+    /// under the pointer first. Returns false if the point is not on
+    /// the scrollbar.
+    pub fn mouse_down(&mut self, x: f32, y: f32) -> bool {
+        if !self.is_needed() || !self.rect.contains([x, y]) {
+            return false;
+        }
+        let thumb = self.thumb();
+        if thumb.contains([x, y]) {
+            self.drag = Some(y - thumb.y);
+        } else {
+            self.drag = Some(thumb.h / 2.);
+            self.drag_to(y);
+        }
+        true
+    }
+
+    pub fn mouse_motion(&mut self, y: f32) {
+        self.drag_to(y)
+    }
+
+    pub fn mouse_up(&mut self) {
+        self.drag = None;
+    }
+
+    pub fn paint(&self, painter: &mut Painter) {
+        if self.is_needed() {
+            painter.stroke_rect(self.rect, 1., graphics::WHITE);
+            painter.fill_rect(self.thumb(), HIGHLIGHT_COLOR);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use ggez::event::{KeyCode, KeyMods};
+use std::collections::BTreeSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelectionMode {
+    Single,
+    Multi,
+}
+
+/// Selected rows of list-like widgets. Besides the set of selected indices
+/// keeps the current row (the one keyboard navigation moves from) and the
+/// anchor row from which Shift extends ranges.
+/// Methods which react on user input return true if set of selected rows
+/// was changed.
+pub struct Selection {
+    mode: SelectionMode,
+    selected: BTreeSet<usize>,
+    anchor: Option<usize>,
+    current: Option<usize>,
+}
+
+impl Selection {
+    pub fn new(mode: SelectionMode) -> Self {
+        Self {
+            mode,
+            selected: BTreeSet::new(),
+            anchor: None,
+            current: None,
+        }
+    }
+
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: SelectionMode) {
+        self.mode = mode;
+        if mode == SelectionMode::Single && self.selected.len() > 1 {
+            let keep = self
+                .current
+                .filter(|c| self.selected.contains(c))
+                .or_else(|| self.selected.iter().next().cloned());
+            self.selected.retain(|i| Some(*i) == keep);
+        }
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn set_current(&mut self, current: Option<usize>) {
+        self.current = current;
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    pub fn selected(&self) -> Vec<usize> {
+        self.selected.iter().cloned().collect()
+    }
+
+    pub fn first_selected(&self) -> Option<usize> {
+        self.selected.iter().next().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn clear(&mut self) -> bool {
+        let changed = !self.selected.is_empty();
+        self.selected.clear();
+        changed
+    }
+
+    pub fn select_only(&mut self, index: usize) -> bool {
+        let changed = self.selected.len() != 1 || !self.selected.contains(&index);
+        self.selected.clear();
+        self.selected.insert(index);
+        self.anchor = Some(index);
+        self.current = Some(index);
+        changed
+    }
+
+    pub fn toggle(&mut self, index: usize) -> bool {
+        if self.mode == SelectionMode::Single {
+            return self.select_only(index);
+        }
+        if !self.selected.remove(&index) {
+            self.selected.insert(index);
+        }
+        self.anchor = Some(index);
+        self.current = Some(index);
+        true
+    }
+
+    /// Selects rows from the anchor to the index. If `extend` is set the
+    /// range is added to already selected rows instead of replacing them.
+    pub fn select_range(&mut self, index: usize, extend: bool) -> bool {
+        if self.mode == SelectionMode::Single {
+            return self.select_only(index);
+        }
+        let anchor = *self.anchor.get_or_insert(index);
+        let range = anchor.min(index)..=anchor.max(index);
+        self.current = Some(index);
+        if extend {
+            let before = self.selected.len();
+            self.selected.extend(range);
+            before != self.selected.len()
+        } else {
+            let selected: BTreeSet<usize> = range.collect();
+            let changed = selected != self.selected;
+            self.selected = selected;
+            changed
+        }
+    }
+
+    pub fn select_all(&mut self, count: usize) -> bool {
+        if self.mode == SelectionMode::Single || count == 0 {
+            return false;
+        }
+        let changed = self.selected.len() != count;
+        self.selected = (0..count).collect();
+        changed
+    }
+
+    /// Drops rows which are out of range after the model was shrunk
+    pub fn truncate(&mut self, count: usize) -> bool {
+        self.anchor = self.anchor.filter(|a| *a < count);
+        self.current = self.current.filter(|c| *c < count);
+        !self.selected.split_off(&count).is_empty()
+    }
+
+    /// Mouse click on the row: Ctrl toggles the row, Shift selects range
+    pub fn click(&mut self, index: usize, keymods: KeyMods) -> bool {
+        let ctrl = keymods.contains(KeyMods::CTRL);
+        if keymods.contains(KeyMods::SHIFT) {
+            self.select_range(index, ctrl)
+        } else if ctrl {
+            self.toggle(index)
+        } else {
+            self.select_only(index)
+        }
+    }
+
+    /// Keyboard move to the row: Shift extends selection from the anchor,
+    /// Ctrl moves the current row only
+    pub fn navigate(&mut self, index: usize, keymods: KeyMods) -> bool {
+        if self.mode == SelectionMode::Multi && keymods.contains(KeyMods::SHIFT) {
+            self.select_range(index, keymods.contains(KeyMods::CTRL))
+        } else if self.mode == SelectionMode::Multi && keymods.contains(KeyMods::CTRL) {
+            self.current = Some(index);
+            false
+        } else {
+            self.select_only(index)
+        }
+    }
+
+    /// Navigation keys for the list of `count` rows with `page` rows
+    /// visible at once: arrows, PageUp/PageDown, Home/End, Space to select
+    /// the current row (Ctrl+Space toggles it) and Ctrl+A to select all
+    pub fn key_down(
+        &mut self,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        count: usize,
+        page: usize,
+    ) -> bool {
+        if count == 0 {
+            return false;
+        }
+        let page = page.max(1) as isize;
+        let delta = match keycode {
+            KeyCode::Up => -1,
+            KeyCode::Down => 1,
+            KeyCode::PageUp => -page,
+            KeyCode::PageDown => page,
+            KeyCode::Home => -(count as isize),
+            KeyCode::End => count as isize,
+            KeyCode::Space => {
+                return match self.current {
+                    Some(current) if keymods.contains(KeyMods::CTRL) => self.toggle(current),
+                    Some(current) => self.select_only(current),
+                    None => false,
+                }
+            }
+            KeyCode::A if keymods.contains(KeyMods::CTRL) => return self.select_all(count),
+            _ => return false,
+        };
+        // Without a current row moves start just before the first one, so
+        // Down selects the first row and End the last
+        let from = match self.current {
+            Some(current) => current as isize,
+            None if delta > 0 => -1,
+            None => 0,
+        };
+        let index = (from + delta).clamp(0, count as isize - 1) as usize;
+        self.navigate(index, keymods)
+    }
+
+    /// Replaces selected rows, e.g. after rows were reordered
+    pub fn set_selected<I: IntoIterator<Item = usize>>(&mut self, rows: I) {
+        self.selected = rows.into_iter().collect();
+        if self.mode == SelectionMode::Single {
+            let first = self.first_selected();
+            self.selected.retain(|i| Some(*i) == first);
+        }
+    }
+
+    pub fn set_anchor(&mut self, anchor: Option<usize>) {
+        self.anchor = anchor;
+    }
+}
@@ -0,0 +1,385 @@
+use crate::gui::{IActions, Widget};
+use ggez::event::{KeyCode, KeyMods};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+/// Key with modifiers, e.g. `Ctrl+Shift+S`. Shown next to menu items and
+/// matched against key presses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub keycode: KeyCode,
+    pub keymods: KeyMods,
+}
+
+const MOD_NAMES: &[(KeyMods, &str)] = &[
+    (KeyMods::CTRL, "Ctrl"),
+    (KeyMods::ALT, "Alt"),
+    (KeyMods::SHIFT, "Shift"),
+    (KeyMods::LOGO, "Logo"),
+];
+
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::Escape, "Esc"),
+    (KeyCode::Return, "Enter"),
+    (KeyCode::NumpadEnter, "NumEnter"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Back, "Backspace"),
+    (KeyCode::Insert, "Ins"),
+    (KeyCode::Delete, "Del"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PgUp"),
+    (KeyCode::PageDown, "PgDn"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equals, "="),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Slash, "/"),
+];
+
+impl KeyChord {
+    pub fn new(keycode: KeyCode, keymods: KeyMods) -> Self {
+        Self { keycode, keymods }
+    }
+
+    /// Parses strings like "Ctrl+O", "Shift+F5" or "Del". Names are
+    /// case-insensitive; returns `None` for unknown keys.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut keymods = KeyMods::NONE;
+        let mut keycode = None;
+        for part in s.split('+').map(str::trim) {
+            if keycode.is_some() {
+                return None;
+            }
+            if let Some((m, _)) = MOD_NAMES
+                .iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(part))
+            {
+                keymods |= *m;
+            } else {
+                keycode = KEY_NAMES
+                    .iter()
+                    .find(|(_, name)| name.eq_ignore_ascii_case(part))
+                    .map(|(k, _)| *k);
+                keycode?;
+            }
+        }
+        keycode.map(|keycode| Self::new(keycode, keymods))
+    }
+
+    pub fn matches(&self, keycode: KeyCode, keymods: KeyMods) -> bool {
+        self.keycode == keycode && self.keymods == keymods
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (m, name) in MOD_NAMES {
+            if self.keymods.contains(*m) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match KEY_NAMES.iter().find(|(k, _)| *k == self.keycode) {
+            Some((_, name)) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self.keycode),
+        }
+    }
+}
+
+/// Modifiers as in chords, e.g. "Ctrl+Shift". Empty if there are none.
+pub fn format_keymods(keymods: KeyMods) -> String {
+    MOD_NAMES
+        .iter()
+        .filter(|(m, _)| keymods.contains(*m))
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Parses modifiers written by `format_keymods`
+pub fn parse_keymods(s: &str) -> Option<KeyMods> {
+    let mut keymods = KeyMods::NONE;
+    for part in s.split('+').map(str::trim).filter(|p| !p.is_empty()) {
+        let (m, _) = MOD_NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(part))?;
+        keymods |= *m;
+    }
+    Some(keymods)
+}
+
+/// Splits label like "&Save" into the shown text and the char index of
+/// the mnemonic. "&&" stands for a literal ampersand.
+pub fn parse_mnemonic(label: &str) -> (String, Option<usize>) {
+    let mut text = String::new();
+    let mut mnemonic = None;
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('&') => text.push('&'),
+            Some(c) => {
+                if mnemonic.is_none() {
+                    mnemonic = Some(text.chars().count());
+                }
+                text.push(c);
+            }
+            None => {}
+        }
+    }
+    (text, mnemonic)
+}
+
+/// Alt+key chord of the mnemonic letter or digit
+pub fn mnemonic_chord(c: char) -> Option<KeyChord> {
+    let name = c.to_ascii_uppercase().to_string();
+    KEY_NAMES
+        .iter()
+        .take_while(|(k, _)| *k != KeyCode::F1)
+        .find(|(_, n)| *n == name)
+        .map(|(k, _)| KeyChord::new(*k, KeyMods::ALT))
+}
+
+/// Alt+key chord of the letter marked by '&' in the label, see
+/// `parse_mnemonic`
+pub fn label_mnemonic(label: &str) -> Option<KeyChord> {
+    let (text, mnemonic) = parse_mnemonic(label);
+    mnemonic
+        .and_then(|index| text.chars().nth(index))
+        .and_then(mnemonic_chord)
+}
+
+pub type ShortcutId = u64;
+
+/// Where a shortcut is active. Widgets are held by weak references, the
+/// shortcut is dropped together with its widget.
+#[derive(Clone)]
+pub enum Scope<'a> {
+    Global,
+    /// Active while the focus is in the window
+    Window(Weak<RefCell<dyn Widget<'a> + 'a>>),
+    /// Active while the focus is in the widget or its children
+    Subtree(Weak<RefCell<dyn Widget<'a> + 'a>>),
+}
+
+impl<'a> Scope<'a> {
+    pub fn window(widget: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Self {
+        Scope::Window(Rc::downgrade(&widget))
+    }
+
+    pub fn subtree(widget: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Self {
+        Scope::Subtree(Rc::downgrade(&widget))
+    }
+
+    fn widget(&self) -> Option<&Weak<RefCell<dyn Widget<'a> + 'a>>> {
+        match self {
+            Scope::Global => None,
+            Scope::Window(w) | Scope::Subtree(w) => Some(w),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        match self.widget() {
+            Some(w) => w.strong_count() > 0,
+            None => true,
+        }
+    }
+
+    fn same_as(&self, other: &Scope<'a>) -> bool {
+        match (self, other) {
+            (Scope::Global, Scope::Global) => true,
+            (Scope::Window(a), Scope::Window(b)) | (Scope::Subtree(a), Scope::Subtree(b)) => {
+                a.as_ptr() as *const () == b.as_ptr() as *const ()
+            }
+            _ => false,
+        }
+    }
+
+    // Sort key of the active scope, lower wins: the innermost subtree,
+    // then the window, then global. `None` if the scope isn't active.
+    fn priority(&self, focus: Option<[f32; 2]>) -> Option<(u8, f32)> {
+        let rect = |w: &Weak<RefCell<dyn Widget<'a> + 'a>>| {
+            let rect = w.upgrade()?.borrow().get_rect();
+            if rect.contains(focus?) {
+                Some(rect.w * rect.h)
+            } else {
+                None
+            }
+        };
+        match self {
+            Scope::Subtree(w) => rect(w).map(|area| (0, area)),
+            Scope::Window(w) => rect(w).map(|area| (1, area)),
+            Scope::Global => Some((2, 0.)),
+        }
+    }
+}
+
+/// Shortcut with the same chord already registered in the same scope
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub chord: KeyChord,
+    pub existing: ShortcutId,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "shortcut {} is already registered in this scope",
+            self.chord
+        )
+    }
+}
+
+struct Entry<'a> {
+    id: ShortcutId,
+    chord: KeyChord,
+    scope: Scope<'a>,
+    action: Rc<dyn Fn() + 'a>,
+}
+
+/// Key chords mapped to actions. `WindowManager` owns it and passes key
+/// presses to it before the widgets. Focus is where the last mouse press
+/// was, the same way widgets take focus on click. Matched actions are
+/// queued and run through `IActions`.
+pub struct Shortcuts<'a> {
+    entries: Vec<Entry<'a>>,
+    next_id: ShortcutId,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+}
+
+impl<'a> Shortcuts<'a> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+            pending_handlers: Vec::new(),
+        }
+    }
+
+    /// Fails if the chord is taken in the same scope. The same chord in
+    /// different scopes is allowed, the innermost active scope wins.
+    pub fn add(
+        &mut self,
+        chord: KeyChord,
+        scope: Scope<'a>,
+        action: impl Fn() + 'a,
+    ) -> Result<ShortcutId, Conflict> {
+        self.entries.retain(|e| e.scope.is_alive());
+        if let Some(existing) = self.find(chord, &scope) {
+            return Err(Conflict { chord, existing });
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(Entry {
+            id,
+            chord,
+            scope,
+            action: Rc::new(action),
+        });
+        Ok(id)
+    }
+
+    pub fn remove(&mut self, id: ShortcutId) {
+        self.entries.retain(|e| e.id != id);
+    }
+
+    pub fn find(&self, chord: KeyChord, scope: &Scope<'a>) -> Option<ShortcutId> {
+        self.entries
+            .iter()
+            .find(|e| e.chord == chord && e.scope.same_as(scope) && e.scope.is_alive())
+            .map(|e| e.id)
+    }
+
+    pub fn get_chord(&self, id: ShortcutId) -> Option<KeyChord> {
+        self.entries.iter().find(|e| e.id == id).map(|e| e.chord)
+    }
+
+    /// Queues the action of the matching shortcut in the innermost active
+    /// scope. Returns false if there is none.
+    pub fn key_pressed(
+        &mut self,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        focus: Option<[f32; 2]>,
+    ) -> bool {
+        self.entries.retain(|e| e.scope.is_alive());
+        let found = self
+            .entries
+            .iter()
+            .filter(|e| e.chord.matches(keycode, keymods))
+            .filter_map(|e| e.scope.priority(focus).map(|p| (p, e)))
+            .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+            .map(|(_, e)| e.action.clone());
+        match found {
+            Some(action) => {
+                self.pending_handlers.push(action);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a> IActions<'a> for Shortcuts<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
@@ -0,0 +1,388 @@
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{is_same, Handler, IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+const BUTTON_WIDTH: f32 = 20.;
+// PageUp and PageDown step by this many steps
+const PAGE_STEPS: f64 = 10.;
+// f64 keeps no more digits than this after the decimal point
+const MAX_PRECISION: usize = 15;
+
+/// Numeric input with increment and decrement buttons. Value is stepped
+/// by buttons, wheel and arrow keys, or typed and checked when Enter is
+/// pressed or the box loses focus. Precision 0 makes it an integer input.
+pub struct SpinBox<'a> {
+    value: f64,
+    min: f64,
+    max: f64,
+    step: f64,
+    precision: usize,
+    suffix: String,
+    editing: Option<String>,
+    rect: Rect,
+    focused: bool,
+    hovered: bool,
+    on_value_changed_handlers: Vec<Handler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> SpinBox<'a> {
+    fn new() -> Self {
+        Self {
+            value: 0.,
+            min: f64::MIN,
+            max: f64::MAX,
+            step: 1.,
+            precision: 0,
+            suffix: String::new(),
+            editing: None,
+            rect: Rect::zero(),
+            focused: false,
+            hovered: false,
+            on_value_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_value_changed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>) + 'a,
+    ) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_value_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_value_changed_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_value_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_value_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_value_changed(&mut self) {
+        for h in &self.on_value_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    pub fn get_value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn get_int(&self) -> i64 {
+        self.value.round() as i64
+    }
+
+    /// Sets clamped and rounded value without firing handlers
+    pub fn set_value(&mut self, value: f64) -> &mut Self {
+        self.value = self.normalize(value);
+        self
+    }
+
+    pub fn set_range(&mut self, min: f64, max: f64) -> &mut Self {
+        self.min = min.min(max);
+        self.max = max.max(min);
+        self.value = self.normalize(self.value);
+        self
+    }
+
+    pub fn get_range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    pub fn set_step(&mut self, step: f64) -> &mut Self {
+        self.step = step.abs();
+        self
+    }
+
+    /// Number of digits after the decimal point, at most 15
+    pub fn set_precision(&mut self, precision: usize) -> &mut Self {
+        self.precision = precision.min(MAX_PRECISION);
+        self.value = self.normalize(self.value);
+        self
+    }
+
+    /// Unit shown after the value, e.g. " px" or "%"
+    pub fn set_suffix<S: Into<String>>(&mut self, suffix: S) -> &mut Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Value formatted with precision and unit suffix
+    pub fn format(&self) -> String {
+        format!("{:.*}{}", self.precision, self.value, self.suffix)
+    }
+
+    /// Parses typed text, the unit suffix may be omitted. The result is
+    /// clamped and rounded.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let text = text.trim();
+        let suffix = self.suffix.trim();
+        let text = if suffix.is_empty() {
+            text
+        } else {
+            text.trim_end_matches(suffix).trim_end()
+        };
+        text.replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map(|v| self.normalize(v))
+    }
+
+    fn normalize(&self, value: f64) -> f64 {
+        let scale = 10f64.powi(self.precision as i32);
+        let scaled = value * scale;
+        // Values too large to scale have no digits after the point anyway
+        let value = if scaled.is_finite() {
+            scaled.round() / scale
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max)
+    }
+
+    fn change(&mut self, value: f64) {
+        let value = self.normalize(value);
+        if value != self.value {
+            self.value = value;
+            self.fire_on_value_changed();
+        }
+    }
+
+    pub fn step_by(&mut self, steps: f64) {
+        self.commit();
+        self.change(self.value + steps * self.step);
+    }
+
+    /// Applies the typed text; invalid text is dropped
+    pub fn commit(&mut self) {
+        if let Some(text) = self.editing.take() {
+            if let Some(value) = self.parse(&text) {
+                self.change(value);
+            }
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.editing = None;
+    }
+
+    fn up_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x + self.rect.w - BUTTON_WIDTH,
+            self.rect.y,
+            BUTTON_WIDTH,
+            self.rect.h / 2.,
+        )
+    }
+
+    fn down_rect(&self) -> Rect {
+        let mut rect = self.up_rect();
+        rect.y += rect.h;
+        rect
+    }
+
+    fn text_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x,
+            self.rect.y,
+            (self.rect.w - BUTTON_WIDTH).max(0.),
+            self.rect.h,
+        )
+    }
+
+    fn key_down(&mut self, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Up => self.step_by(1.),
+            KeyCode::Down => self.step_by(-1.),
+            KeyCode::PageUp => self.step_by(PAGE_STEPS),
+            KeyCode::PageDown => self.step_by(-PAGE_STEPS),
+            KeyCode::Return => self.commit(),
+            KeyCode::Escape => self.cancel(),
+            KeyCode::Back => {
+                let text = format!("{:.*}", self.precision, self.value);
+                let edit = self.editing.get_or_insert(text);
+                edit.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl IEvents for SpinBox<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        painter.fill_rect(self.text_rect(), graphics::WHITE);
+        for (rect, up) in [(self.up_rect(), true), (self.down_rect(), false)].iter() {
+            painter.fill_rect(*rect, HIGHLIGHT_COLOR);
+            painter.stroke_rect(*rect, 1., graphics::WHITE);
+            let cx = rect.x + rect.w / 2.;
+            let cy = rect.y + rect.h / 2.;
+            let dy = if *up { -3. } else { 3. };
+            painter.fill_polygon(
+                &[[cx - 5., cy - dy], [cx + 5., cy - dy], [cx, cy + dy]],
+                graphics::BLACK,
+            );
+        }
+        if self.focused {
+            painter.stroke_rect(self.text_rect(), 2., graphics::BLACK);
+        }
+        let text = match self.editing {
+            Some(ref edit) => format!("{}|", edit),
+            None => self.format(),
+        };
+        painter.text(self.text_rect(), text, Align::Left, graphics::BLACK);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if !self.rect.contains([x, y]) {
+            if self.focused {
+                self.commit();
+                self.focused = false;
+            }
+            return;
+        }
+        self.focused = true;
+        if button != MouseButton::Left {
+            return;
+        }
+        if self.up_rect().contains([x, y]) {
+            self.step_by(1.);
+        } else if self.down_rect().contains([x, y]) {
+            self.step_by(-1.);
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, y: f32) {
+        if self.hovered && y != 0. {
+            self.step_by(y.signum() as f64);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(keycode)
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut dyn IContext, character: char) {
+        if self.focused && !character.is_control() {
+            self.editing.get_or_insert_with(String::new).push(character);
+        }
+    }
+}
+
+impl ILayout for SpinBox<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for SpinBox<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for SpinBox<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    spin_box: SpinBox<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            spin_box: SpinBox::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<SpinBox<'a>>> {
+        let rc = Rc::new(RefCell::new(self.spin_box));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_range(mut self, min: f64, max: f64) -> Self {
+        self.spin_box.set_range(min, max);
+        self
+    }
+
+    pub fn set_step(mut self, step: f64) -> Self {
+        self.spin_box.set_step(step);
+        self
+    }
+
+    pub fn set_precision(mut self, precision: usize) -> Self {
+        self.spin_box.set_precision(precision);
+        self
+    }
+
+    pub fn set_suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+        self.spin_box.set_suffix(suffix);
+        self
+    }
+
+    pub fn set_value(mut self, value: f64) -> Self {
+        self.spin_box.set_value(value);
+        self
+    }
+
+    pub fn on_value_changed(mut self, handler: impl Fn(Rc<RefCell<SpinBox<'a>>>) + 'a) -> Self {
+        self.spin_box.on_value_changed(handler);
+        self
+    }
+}
@@ -0,0 +1,219 @@
+use crate::gui::painter::Painter;
+use crate::gui::{IActions, IContext, IEvents, ILayout, IOverlays, TRcSelf, Widget};
+use ggez::graphics::{Color, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+const DOTS: usize = 8;
+
+/// Animation state of the busy indicator. Time is passed explicitly,
+/// so it can be driven without graphics context.
+pub trait TBackend<'a>: TRcSelf {
+    fn is_running(&self) -> bool;
+    fn set_running(&mut self, running: bool);
+    fn get_period(&self) -> Duration;
+    fn set_period(&mut self, period: Duration);
+    /// Moves the animation forward, does nothing when stopped
+    fn advance(&mut self, dt: Duration);
+    /// Position of the animation inside the period, 0..1
+    fn phase(&self) -> f32;
+}
+
+pub trait TFrontend<'a, BE: TBackend<'a>>: TRcSelf + ILayout + IActions<'a> {
+    fn backend(&self) -> Rc<RefCell<BE>>;
+}
+
+pub struct Backend {
+    running: bool,
+    period: Duration,
+    phase: f32,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl TRcSelf for Backend {
+    fn create() -> Rc<RefCell<Self>> {
+        let v = Rc::new(RefCell::new(Self {
+            running: true,
+            period: Duration::from_millis(1000),
+            phase: 0.,
+            rcself: None,
+        }));
+        v.borrow_mut().rcself = Some(Rc::downgrade(&v));
+        v
+    }
+    fn wrcself(&self) -> Weak<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().clone()
+    }
+}
+
+impl<'a> TBackend<'a> for Backend {
+    fn is_running(&self) -> bool {
+        self.running
+    }
+    fn set_running(&mut self, running: bool) {
+        self.running = running;
+    }
+    fn get_period(&self) -> Duration {
+        self.period
+    }
+    fn set_period(&mut self, period: Duration) {
+        self.period = period;
+    }
+    fn advance(&mut self, dt: Duration) {
+        if self.running && self.period > Duration::from_secs(0) {
+            self.phase = (self.phase + dt.as_secs_f32() / self.period.as_secs_f32()).fract();
+        }
+    }
+    fn phase(&self) -> f32 {
+        self.phase
+    }
+}
+
+pub struct Builder<'a, BE: TBackend<'a>, FE: TFrontend<'a, BE>> {
+    rcfront: Rc<RefCell<FE>>,
+    phantom: PhantomData<&'a BE>,
+}
+
+impl<'a, BE, FE> Builder<'a, BE, FE>
+where
+    BE: TBackend<'a>,
+    FE: TFrontend<'a, BE>,
+{
+    pub fn new() -> Self {
+        Self {
+            rcfront: FE::create(),
+            phantom: PhantomData,
+        }
+    }
+    pub fn set_running(self, running: bool) -> Self {
+        self.rcfront
+            .borrow()
+            .backend()
+            .borrow_mut()
+            .set_running(running);
+        self
+    }
+    pub fn set_period(self, period: Duration) -> Self {
+        self.rcfront
+            .borrow()
+            .backend()
+            .borrow_mut()
+            .set_period(period);
+        self
+    }
+    pub fn build(self) -> Rc<RefCell<FE>> {
+        self.rcfront
+    }
+}
+
+/// Circle of dots with a darker one running around it
+pub struct Frontend<'a, BE: TBackend<'a>> {
+    rcback: Rc<RefCell<BE>>,
+    rect: Rect,
+    rcself: Option<Weak<RefCell<Self>>>,
+    phantom: PhantomData<&'a BE>,
+}
+
+impl<'a, BE> TRcSelf for Frontend<'a, BE>
+where
+    BE: TBackend<'a>,
+{
+    fn create() -> Rc<RefCell<Self>> {
+        let v = Rc::new(RefCell::new(Self {
+            rcback: BE::create(),
+            rect: Rect::zero(),
+            rcself: None,
+            phantom: PhantomData,
+        }));
+        v.borrow_mut().rcself = Some(Rc::downgrade(&v));
+        v
+    }
+    fn wrcself(&self) -> Weak<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().clone()
+    }
+}
+
+impl<'a, BE> TFrontend<'a, BE> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn backend(&self) -> Rc<RefCell<BE>> {
+        self.rcback.clone()
+    }
+}
+
+impl<'a, BE> IEvents for Frontend<'a, BE>
+where
+    BE: TBackend<'a>,
+{
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.rcback.borrow_mut().advance(ctx.delta());
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        let back = self.rcback.borrow();
+        let radius = self.rect.w.min(self.rect.h) / 2. - 5.;
+        if radius <= 0. {
+            return;
+        }
+        let dot = radius / 5.;
+        let cx = self.rect.x + self.rect.w / 2.;
+        let cy = self.rect.y + self.rect.h / 2.;
+        let head = (back.phase() * DOTS as f32) as usize % DOTS;
+        for n in 0..DOTS {
+            // Dots fade out behind the head
+            let shade = if back.is_running() {
+                ((head + DOTS - n) % DOTS) as f32 / DOTS as f32
+            } else {
+                0.7
+            };
+            let angle = 2. * PI * n as f32 / DOTS as f32;
+            let center = [
+                cx + (radius - dot) * angle.sin(),
+                cy - (radius - dot) * angle.cos(),
+            ];
+            painter.fill_circle(center, dot, Color::new(shade, shade, shade, 1.));
+        }
+    }
+}
+
+impl<'a, BE> ILayout for Frontend<'a, BE>
+where
+    BE: TBackend<'a>,
+{
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a, BE> IActions<'a> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a, BE> IOverlays<'a> for Frontend<'a, BE>
+where
+    BE: TBackend<'a> + 'a,
+{
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
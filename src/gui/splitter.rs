@@ -0,0 +1,480 @@
+use crate::gui::capture::PointerCapture;
+use crate::gui::gestures::GestureSettings;
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    collect_hovered_child, is_same, Handler, IActions, IContext, IEvents, ILayout, IOverlays,
+    Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    First,
+    Second,
+}
+
+/// Two widgets separated by a divider which can be dragged. The position
+/// of the divider is kept as a ratio, so it survives resizing; minimal
+/// sizes of the sides are applied when laying out.
+pub struct Splitter<'a> {
+    first: Option<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    second: Option<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    horizontal: bool,
+    ratio: f32,
+    min_first: f32,
+    min_second: f32,
+    divider_width: f32,
+    collapsed: Option<Side>,
+    drag: Option<f32>,
+    // Time and position of the press on the divider until the pointer moves
+    press: Option<(Duration, f32)>,
+    last_click: Option<Duration>,
+    double_click_time: Duration,
+    capture: PointerCapture,
+    rect: Rect,
+    on_ratio_changed_handlers: Vec<Handler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> Splitter<'a> {
+    fn new() -> Self {
+        Self {
+            first: None,
+            second: None,
+            horizontal: true,
+            ratio: 0.5,
+            min_first: 0.,
+            min_second: 0.,
+            divider_width: 6.,
+            collapsed: None,
+            drag: None,
+            press: None,
+            last_click: None,
+            double_click_time: GestureSettings::default().double_click_time,
+            capture: PointerCapture::new(),
+            rect: Rect::zero(),
+            on_ratio_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    /// Fired when the user drags the divider or collapses a side
+    pub fn on_ratio_changed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>) + 'a,
+    ) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_ratio_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_ratio_changed_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_ratio_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_ratio_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_ratio_changed(&mut self) {
+        for h in &self.on_ratio_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    pub fn set_first(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) -> &mut Self {
+        self.first = Some(widget);
+        self.layout();
+        self
+    }
+
+    pub fn set_second(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) -> &mut Self {
+        self.second = Some(widget);
+        self.layout();
+        self
+    }
+
+    /// Horizontal splitter places the sides left and right of the divider
+    pub fn set_horizontal(&mut self, horizontal: bool) -> &mut Self {
+        self.horizontal = horizontal;
+        self.layout();
+        self
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.horizontal
+    }
+
+    pub fn get_ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Share of the first side, 0..1. Doesn't fire handlers.
+    pub fn set_ratio(&mut self, ratio: f32) -> &mut Self {
+        self.ratio = ratio.clamp(0., 1.);
+        self.layout();
+        self
+    }
+
+    pub fn set_min_sizes(&mut self, min_first: f32, min_second: f32) -> &mut Self {
+        self.min_first = min_first.max(0.);
+        self.min_second = min_second.max(0.);
+        self.layout();
+        self
+    }
+
+    pub fn set_divider_width(&mut self, divider_width: f32) -> &mut Self {
+        self.divider_width = divider_width.max(0.);
+        self.layout();
+        self
+    }
+
+    pub fn get_double_click_time(&self) -> Duration {
+        self.double_click_time
+    }
+
+    /// Second click on the divider faster than this collapses the smaller
+    /// side. The default is the one of `GestureSettings`.
+    pub fn set_double_click_time(&mut self, double_click_time: Duration) -> &mut Self {
+        self.double_click_time = double_click_time;
+        self
+    }
+
+    pub fn get_collapsed(&self) -> Option<Side> {
+        self.collapsed
+    }
+
+    /// Collapsed side gets no space; the ratio is kept for restoring it
+    pub fn set_collapsed(&mut self, collapsed: Option<Side>) {
+        self.collapsed = collapsed;
+        self.layout();
+    }
+
+    // Space shared by the sides along the splitter direction
+    fn extent(&self) -> f32 {
+        let length = if self.horizontal {
+            self.rect.w
+        } else {
+            self.rect.h
+        };
+        (length - self.divider_width).max(0.)
+    }
+
+    fn origin(&self) -> f32 {
+        if self.horizontal {
+            self.rect.x
+        } else {
+            self.rect.y
+        }
+    }
+
+    /// Size of the first side with collapsing and min sizes applied
+    pub fn first_size(&self) -> f32 {
+        let extent = self.extent();
+        match self.collapsed {
+            Some(Side::First) => 0.,
+            Some(Side::Second) => extent,
+            None => (self.ratio * extent)
+                .min(extent - self.min_second)
+                .max(self.min_first)
+                .min(extent),
+        }
+    }
+
+    fn paint_divider(&self, painter: &mut Painter) {
+        let rect = self.divider_rect();
+        if rect.w > 0. && rect.h > 0. {
+            let color = if self.drag.is_some() {
+                graphics::WHITE
+            } else {
+                HIGHLIGHT_COLOR
+            };
+            painter.fill_rect(rect, color);
+        }
+    }
+
+    fn divider_rect(&self) -> Rect {
+        let pos = self.origin() + self.first_size();
+        if self.horizontal {
+            Rect::new(pos, self.rect.y, self.divider_width, self.rect.h)
+        } else {
+            Rect::new(self.rect.x, pos, self.rect.w, self.divider_width)
+        }
+    }
+
+    fn layout(&mut self) {
+        let size = self.first_size();
+        let rest = self.extent() - size;
+        let r = self.rect;
+        let (first, second) = if self.horizontal {
+            (
+                Rect::new(r.x, r.y, size, r.h),
+                Rect::new(r.x + size + self.divider_width, r.y, rest, r.h),
+            )
+        } else {
+            (
+                Rect::new(r.x, r.y, r.w, size),
+                Rect::new(r.x, r.y + size + self.divider_width, r.w, rest),
+            )
+        };
+        if let Some(w) = &self.first {
+            w.borrow_mut().set_rect(first);
+        }
+        if let Some(w) = &self.second {
+            w.borrow_mut().set_rect(second);
+        }
+    }
+
+    fn children(&self) -> impl Iterator<Item = &Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.first.iter().chain(self.second.iter())
+    }
+
+    fn for_all<F: FnMut(Rc<RefCell<dyn Widget<'a> + 'a>>)>(&self, mut f: F) {
+        for w in self.children() {
+            f(w.clone())
+        }
+    }
+
+    // Children which get pointer motion and releases, see `PointerCapture`
+    fn for_captured<F: FnMut(Rc<RefCell<dyn Widget<'a> + 'a>>)>(&self, mut f: F) {
+        for (index, w) in self.children().enumerate() {
+            if self.capture.routes_to(index) {
+                f(w.clone())
+            }
+        }
+    }
+
+    fn for_all_res<F: FnMut(Rc<RefCell<dyn Widget<'a> + 'a>>) -> GameResult>(
+        &self,
+        mut f: F,
+    ) -> GameResult {
+        for w in self.children() {
+            f(w.clone())?
+        }
+        Ok(())
+    }
+
+    fn toggle_collapsed(&mut self) {
+        self.collapsed = match self.collapsed {
+            Some(_) => None,
+            None if self.ratio <= 0.5 => Some(Side::First),
+            None => Some(Side::Second),
+        };
+        self.layout();
+        self.fire_on_ratio_changed();
+    }
+
+    fn press(&mut self, x: f32, y: f32, now: Duration) {
+        if !self.divider_rect().contains([x, y]) {
+            return;
+        }
+        match self.last_click {
+            Some(time) if now.checked_sub(time).unwrap_or_default() < self.double_click_time => {
+                self.last_click = None;
+                self.toggle_collapsed();
+            }
+            _ => {
+                let pos = if self.horizontal { x } else { y };
+                self.press = Some((now, pos));
+                self.drag = Some(pos - self.origin() - self.first_size());
+            }
+        }
+    }
+
+    fn drag_to(&mut self, x: f32, y: f32) {
+        let offset = match self.drag {
+            Some(offset) => offset,
+            None => return,
+        };
+        let extent = self.extent();
+        if extent <= 0. {
+            return;
+        }
+        let pos = if self.horizontal { x } else { y };
+        if let Some((_, start)) = self.press {
+            if start != pos {
+                self.press = None;
+            }
+        }
+        let size = (pos - offset - self.origin())
+            .min(extent - self.min_second)
+            .max(self.min_first)
+            .clamp(0., extent);
+        let ratio = size / extent;
+        if ratio != self.ratio || self.collapsed.is_some() {
+            self.ratio = ratio;
+            self.collapsed = None;
+            self.layout();
+            self.fire_on_ratio_changed();
+        }
+    }
+}
+
+impl IEvents for Splitter<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.for_all_res(|w| w.borrow_mut().update(ctx))
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.for_all_res(|w| w.borrow_mut().draw(ctx))?;
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_divider(&mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.for_all(|w| w.borrow_mut().paint_into(ctx, painter));
+        self.paint_divider(painter);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button == MouseButton::Left {
+            self.press(x, y, ctx.time_since_start());
+        }
+        let rects: Vec<_> = self.children().map(|w| w.borrow().get_rect()).collect();
+        self.capture.press(button, x, y, rects);
+        self.for_all(|w| w.borrow_mut().mouse_button_down_event(ctx, button, x, y))
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if button == MouseButton::Left {
+            self.drag = None;
+            // Only a press released without moving counts as a click
+            self.last_click = self.press.take().map(|(time, _)| time);
+        }
+        self.for_captured(|w| w.borrow_mut().mouse_button_up_event(ctx, button, x, y));
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        self.drag_to(x, y);
+        self.for_captured(|w| w.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy))
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        self.for_all(|w| w.borrow_mut().mouse_wheel_event(ctx, x, y))
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        self.for_all(|w| w.borrow_mut().key_down_event(ctx, keycode, keymods, repeat))
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        self.for_all(|w| w.borrow_mut().key_up_event(ctx, keycode, keymods))
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        self.for_all(|w| w.borrow_mut().text_input_event(ctx, character))
+    }
+}
+
+impl ILayout for Splitter<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.layout();
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Splitter<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        let mut v: Vec<_> = self.pending_handlers.drain(..).collect();
+        self.for_all(|w| v.append(&mut w.borrow_mut().collect_fired()));
+        v
+    }
+}
+
+impl<'a> IOverlays<'a> for Splitter<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        let mut v = Vec::new();
+        self.for_all(|w| v.append(&mut w.borrow_mut().collect_overlays()));
+        v
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(self.children(), x, y, hovered)
+    }
+}
+
+pub struct Builder<'a> {
+    splitter: Splitter<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            splitter: Splitter::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<Splitter<'a>>> {
+        let rc = Rc::new(RefCell::new(self.splitter));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_first(mut self, w: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Self {
+        self.splitter.set_first(w);
+        self
+    }
+
+    pub fn set_second(mut self, w: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Self {
+        self.splitter.set_second(w);
+        self
+    }
+
+    pub fn set_horizontal(mut self, horizontal: bool) -> Self {
+        self.splitter.set_horizontal(horizontal);
+        self
+    }
+
+    pub fn set_ratio(mut self, ratio: f32) -> Self {
+        self.splitter.set_ratio(ratio);
+        self
+    }
+
+    pub fn set_min_sizes(mut self, min_first: f32, min_second: f32) -> Self {
+        self.splitter.set_min_sizes(min_first, min_second);
+        self
+    }
+
+    pub fn on_ratio_changed(mut self, handler: impl Fn(Rc<RefCell<Splitter<'a>>>) + 'a) -> Self {
+        self.splitter.on_ratio_changed(handler);
+        self
+    }
+}
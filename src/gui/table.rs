@@ -0,0 +1,589 @@
+use crate::gui::painter::Painter;
+use crate::gui::scrollbar::ScrollBar;
+use crate::gui::selection::{Selection, SelectionMode};
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    draw_label, is_same, Handler, IActions, IContext, IEvents, ILayout, IOverlays, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::{Rc, Weak};
+
+// Distance from the column border where dragging resizes the column
+const RESIZE_GRIP: f32 = 4.;
+const MIN_COLUMN_WIDTH: f32 = 20.;
+const DEFAULT_COLUMN_WIDTH: f32 = 100.;
+
+/// Data shown by `Table`. Rows are identified by their index in the model;
+/// the table keeps its own sorted order of them. Only visible cells are
+/// requested from the model.
+pub trait ITableModel {
+    fn row_count(&self) -> usize;
+    fn column_count(&self) -> usize;
+    fn column_title(&self, column: usize) -> String;
+    fn cell_text(&self, row: usize, column: usize) -> String;
+    /// Draws the cell content. Selection background is already drawn.
+    fn draw_cell(&self, ctx: &mut Context, row: usize, column: usize, rect: Rect) -> GameResult {
+        draw_label(ctx, rect, &self.cell_text(row, column), Align::Left)
+    }
+    /// Records the cell for `paint_into`. Models which override `draw_cell`
+    /// should record the same drawing here.
+    fn paint_cell(&self, painter: &mut Painter, row: usize, column: usize, rect: Rect) {
+        let text = self.cell_text(row, column);
+        painter.text(rect, text, Align::Left, graphics::BLACK);
+    }
+    /// Order of rows when table is sorted by the column
+    fn compare(&self, column: usize, a: usize, b: usize) -> Ordering {
+        self.cell_text(a, column).cmp(&self.cell_text(b, column))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+enum Drag {
+    Resize { column: usize, left: f32 },
+    Header(usize),
+}
+
+struct EmptyModel;
+
+impl ITableModel for EmptyModel {
+    fn row_count(&self) -> usize {
+        0
+    }
+    fn column_count(&self) -> usize {
+        0
+    }
+    fn column_title(&self, _column: usize) -> String {
+        String::new()
+    }
+    fn cell_text(&self, _row: usize, _column: usize) -> String {
+        String::new()
+    }
+}
+
+pub struct Table<'a> {
+    model: Rc<RefCell<dyn ITableModel + 'a>>,
+    // Model rows in the order they are shown
+    order: Vec<usize>,
+    sort: Option<(usize, SortOrder)>,
+    widths: Vec<f32>,
+    selection: Selection,
+    scrollbar: ScrollBar,
+    row_height: f32,
+    rect: Rect,
+    focused: bool,
+    hovered: bool,
+    drag: Option<Drag>,
+    on_selection_changed_handlers: Vec<Handler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> Table<'a> {
+    fn new() -> Self {
+        Self {
+            model: Rc::new(RefCell::new(EmptyModel)),
+            order: Vec::new(),
+            sort: None,
+            widths: Vec::new(),
+            selection: Selection::new(SelectionMode::Single),
+            scrollbar: ScrollBar::new(),
+            row_height: 30.,
+            rect: Rect::zero(),
+            focused: false,
+            hovered: false,
+            drag: None,
+            on_selection_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_selection_changed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>) + 'a,
+    ) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_selection_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_selection_changed_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_selection_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_selection_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_selection_changed(&mut self) {
+        for h in &self.on_selection_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    pub fn model(&self) -> Rc<RefCell<dyn ITableModel + 'a>> {
+        self.model.clone()
+    }
+
+    pub fn set_model(&mut self, model: Rc<RefCell<dyn ITableModel + 'a>>) {
+        self.model = model;
+        self.order.clear();
+        self.sort = None;
+        self.selection.clear();
+        self.selection.set_current(None);
+        self.scrollbar.set_first(0);
+        self.sync_model();
+    }
+
+    /// Selected rows as indices in the model
+    pub fn selected_rows(&self) -> Vec<usize> {
+        self.selection
+            .selected()
+            .into_iter()
+            .map(|r| self.order[r])
+            .collect()
+    }
+
+    /// Selection in terms of displayed (sorted) rows. Changed directly,
+    /// without firing handlers.
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    pub fn selection_mut(&mut self) -> &mut Selection {
+        &mut self.selection
+    }
+
+    pub fn set_selection_mode(&mut self, mode: SelectionMode) -> &mut Self {
+        self.selection.set_mode(mode);
+        self
+    }
+
+    pub fn set_row_height(&mut self, row_height: f32) -> &mut Self {
+        self.row_height = row_height;
+        self.layout();
+        self
+    }
+
+    /// Model row shown at the displayed position
+    pub fn model_row(&self, row: usize) -> usize {
+        self.order[row]
+    }
+
+    pub fn column_width(&self, column: usize) -> f32 {
+        self.widths
+            .get(column)
+            .cloned()
+            .unwrap_or(DEFAULT_COLUMN_WIDTH)
+    }
+
+    /// Widths are kept when the model changes, so they may be set before
+    /// the model or for columns which the model doesn't have yet
+    pub fn set_column_width(&mut self, column: usize, width: f32) {
+        if column >= self.widths.len() {
+            self.widths.resize(column + 1, DEFAULT_COLUMN_WIDTH);
+        }
+        self.widths[column] = width.max(MIN_COLUMN_WIDTH);
+    }
+
+    pub fn get_sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn set_sort(&mut self, sort: Option<(usize, SortOrder)>) {
+        self.sort = sort;
+        self.resort();
+    }
+
+    /// Sorts rows again, e.g. after model data was changed
+    pub fn resort(&mut self) {
+        let count = self.model.borrow().row_count();
+        let selected = self.selected_rows();
+        let current = self.selection.current().map(|c| self.order[c]);
+        let current = current.filter(|c| *c < count);
+        self.order = (0..count).collect();
+        if let Some((column, sort_order)) = self.sort {
+            let model = self.model.borrow();
+            self.order.sort_by(|a, b| {
+                let ord = model.compare(column, *a, *b);
+                match sort_order {
+                    SortOrder::Ascending => ord,
+                    SortOrder::Descending => ord.reverse(),
+                }
+            });
+        }
+        // Keep the same model rows selected
+        let mut position = vec![0; count];
+        for (row, model_row) in self.order.iter().enumerate() {
+            position[*model_row] = row;
+        }
+        let selected = selected.into_iter().filter(|r| *r < count);
+        self.selection.set_selected(selected.map(|r| position[r]));
+        let current = current.map(|c| position[c]);
+        self.selection.set_current(current);
+        self.selection.set_anchor(current);
+    }
+
+    fn toggle_sort(&mut self, column: usize) {
+        let sort_order = match self.sort {
+            Some((c, SortOrder::Ascending)) if c == column => SortOrder::Descending,
+            _ => SortOrder::Ascending,
+        };
+        self.set_sort(Some((column, sort_order)));
+    }
+
+    // Model may be changed outside, so row and column counts are checked on each update
+    fn sync_model(&mut self) {
+        let (rows, columns) = {
+            let model = self.model.borrow();
+            (model.row_count(), model.column_count())
+        };
+        if self.widths.len() < columns {
+            self.widths.resize(columns, DEFAULT_COLUMN_WIDTH);
+        }
+        if self.order.len() != rows {
+            // Selection is kept by model row, so the rows dropped are the
+            // removed model rows and not the last displayed ones
+            let selected = self.selected_rows().len();
+            self.resort();
+            if self.selected_rows().len() != selected {
+                self.fire_on_selection_changed();
+            }
+        }
+        self.layout();
+    }
+
+    fn header_rect(&self) -> Rect {
+        Rect::new(self.rect.x, self.rect.y, self.rect.w, self.row_height)
+    }
+
+    fn layout(&mut self) {
+        let rows_h = self.rect.h - self.row_height;
+        let visible = if self.row_height > 0. && rows_h > 0. {
+            (rows_h / self.row_height) as usize
+        } else {
+            0
+        };
+        self.scrollbar.set_range(self.order.len(), visible);
+        self.scrollbar.set_rect(Rect::new(
+            self.rect.x + self.rect.w - ScrollBar::WIDTH,
+            self.rect.y + self.row_height,
+            ScrollBar::WIDTH,
+            rows_h.max(0.),
+        ));
+    }
+
+    fn cells_right(&self) -> f32 {
+        let mut right = self.rect.x + self.rect.w;
+        if self.scrollbar.is_needed() {
+            right -= ScrollBar::WIDTH;
+        }
+        right
+    }
+
+    // Widths of the columns which the model has now
+    fn model_widths(&self) -> &[f32] {
+        let count = self.model.borrow().column_count();
+        &self.widths[..count.min(self.widths.len())]
+    }
+
+    // Horizontal extents of columns, cut by the right edge of the table
+    fn columns(&self) -> Vec<(f32, f32)> {
+        let right = self.cells_right();
+        let mut x = self.rect.x;
+        let mut columns = Vec::new();
+        for w in self.model_widths() {
+            if x >= right {
+                break;
+            }
+            columns.push((x, w.min(right - x)));
+            x += w;
+        }
+        columns
+    }
+
+    // Header with titles, row backgrounds, column borders and the
+    // scrollbar, the model draws the cells
+    fn paint_grid(&self, painter: &mut Painter, columns: &[(f32, f32)]) {
+        let header = self.header_rect();
+        painter.fill_rect(header, HIGHLIGHT_COLOR);
+        for row in self.scrollbar.rows() {
+            let rect = self.row_rect(row);
+            if self.selection.is_selected(row) {
+                painter.fill_rect(rect, HIGHLIGHT_COLOR);
+            } else {
+                painter.fill_rect(rect, graphics::WHITE);
+            }
+            if self.focused && self.selection.current() == Some(row) {
+                painter.stroke_rect(rect, 2., graphics::BLACK);
+            }
+        }
+        let bottom = self.row_rect(self.scrollbar.rows().end).y;
+        for (x, w) in columns {
+            let border = x + w;
+            painter.line([border, header.y], [border, bottom], 1., graphics::BLACK);
+        }
+        if let Some((column, sort_order)) = self.sort {
+            if let Some((x, w)) = columns.get(column) {
+                let s = (self.row_height / 6.).min(w / 4.);
+                let cx = x + w - s * 2.;
+                let cy = header.y + header.h / 2.;
+                let (tip, base) = match sort_order {
+                    SortOrder::Ascending => (cy - s / 2., cy + s / 2.),
+                    SortOrder::Descending => (cy + s / 2., cy - s / 2.),
+                };
+                painter.fill_polygon(
+                    &[[cx - s, base], [cx + s, base], [cx, tip]],
+                    graphics::BLACK,
+                );
+            }
+        }
+        painter.stroke_rect(self.rect, 1., graphics::WHITE);
+        self.scrollbar.paint(painter);
+        let model = self.model.borrow();
+        for (column, (x, w)) in columns.iter().enumerate() {
+            let rect = Rect::new(*x, header.y, *w, header.h);
+            painter.text(
+                rect,
+                model.column_title(column),
+                Align::Left,
+                graphics::BLACK,
+            );
+        }
+    }
+
+    fn row_rect(&self, row: usize) -> Rect {
+        let n = row - self.scrollbar.first() + 1;
+        Rect::new(
+            self.rect.x,
+            self.rect.y + n as f32 * self.row_height,
+            self.cells_right() - self.rect.x,
+            self.row_height,
+        )
+    }
+
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.scrollbar
+            .rows()
+            .find(|r| self.row_rect(*r).contains([x, y]))
+    }
+
+    fn header_press(&mut self, x: f32) {
+        let mut left = self.rect.x;
+        for (column, w) in self.model_widths().iter().enumerate() {
+            let border = left + w;
+            if (x - border).abs() <= RESIZE_GRIP {
+                self.drag = Some(Drag::Resize { column, left });
+                return;
+            }
+            if x < border {
+                self.drag = Some(Drag::Header(column));
+                return;
+            }
+            left = border;
+        }
+    }
+
+    fn key_down(&mut self, keycode: KeyCode, keymods: KeyMods) {
+        let count = self.order.len();
+        let page = self.scrollbar.visible();
+        if self.selection.key_down(keycode, keymods, count, page) {
+            self.fire_on_selection_changed();
+        }
+        if let Some(current) = self.selection.current() {
+            self.scrollbar.scroll_to(current);
+        }
+    }
+}
+
+impl IEvents for Table<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        self.sync_model();
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let columns = self.columns();
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_grid(&mut painter, &columns);
+        painter.render(ctx)?;
+        let model = self.model.borrow();
+        for row in self.scrollbar.rows() {
+            let rect = self.row_rect(row);
+            for (column, (x, w)) in columns.iter().enumerate() {
+                let cell = Rect::new(*x, rect.y, *w, rect.h);
+                model.draw_cell(ctx, self.order[row], column, cell)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        let columns = self.columns();
+        self.paint_grid(painter, &columns);
+        let model = self.model.borrow();
+        for row in self.scrollbar.rows() {
+            let rect = self.row_rect(row);
+            for (column, (x, w)) in columns.iter().enumerate() {
+                let cell = Rect::new(*x, rect.y, *w, rect.h);
+                model.paint_cell(painter, self.order[row], column, cell);
+            }
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if !self.rect.contains([x, y]) {
+            self.focused = false;
+            return;
+        }
+        self.focused = true;
+        if button != MouseButton::Left || self.scrollbar.mouse_down(x, y) {
+            return;
+        }
+        if self.header_rect().contains([x, y]) {
+            self.header_press(x);
+        } else if let Some(row) = self.row_at(x, y) {
+            if self.selection.click(row, ctx.keymods()) {
+                self.fire_on_selection_changed();
+            }
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.scrollbar.mouse_up();
+        if let Some(Drag::Header(column)) = self.drag.take() {
+            if self.header_rect().contains([x, y]) {
+                let widths = self.model_widths();
+                let left: f32 = widths[..column.min(widths.len())].iter().sum();
+                let left = self.rect.x + left;
+                if column < widths.len() && x >= left && x < left + widths[column] {
+                    self.toggle_sort(column);
+                }
+            }
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+        self.scrollbar.mouse_motion(y);
+        if let Some(Drag::Resize { column, left }) = self.drag {
+            self.set_column_width(column, x - left);
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, y: f32) {
+        if self.hovered {
+            self.scrollbar.scroll_by((-y * 3.) as isize);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(keycode, keymods)
+        }
+    }
+}
+
+impl ILayout for Table<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.layout();
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Table<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for Table<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    table: Table<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            table: Table::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<Table<'a>>> {
+        let rc = Rc::new(RefCell::new(self.table));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_model(mut self, model: Rc<RefCell<dyn ITableModel + 'a>>) -> Self {
+        self.table.set_model(model);
+        self
+    }
+
+    pub fn set_selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.table.set_selection_mode(mode);
+        self
+    }
+
+    pub fn set_row_height(mut self, row_height: f32) -> Self {
+        self.table.set_row_height(row_height);
+        self
+    }
+
+    pub fn set_column_width(mut self, column: usize, width: f32) -> Self {
+        self.table.set_column_width(column, width);
+        self
+    }
+
+    pub fn set_sort(mut self, sort: Option<(usize, SortOrder)>) -> Self {
+        self.table.set_sort(sort);
+        self
+    }
+
+    pub fn on_selection_changed(mut self, handler: impl Fn(Rc<RefCell<Table<'a>>>) + 'a) -> Self {
+        self.table.on_selection_changed(handler);
+        self
+    }
+}
@@ -0,0 +1,369 @@
+use crate::gui::capture::PointerCapture;
+use crate::gui::painter::Painter;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    collect_hovered_child, is_same, IActions, IContext, IEvents, ILayout, IOverlays, IndexHandler,
+    Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+struct Tab<'a> {
+    title: String,
+    widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+}
+
+/// Pages with titles. Only the active page is shown, laid out and gets events;
+/// the strip of titles above it switches pages.
+pub struct Tabs<'a> {
+    tabs: Vec<Tab<'a>>,
+    active: usize,
+    strip_height: f32,
+    rect: Rect,
+    focused: bool,
+    // Active page under the press, it is the only child
+    capture: PointerCapture,
+    on_tab_changed_handlers: Vec<IndexHandler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> Tabs<'a> {
+    fn new() -> Self {
+        Self {
+            tabs: Vec::new(),
+            active: 0,
+            strip_height: 30.,
+            rect: Rect::zero(),
+            focused: false,
+            capture: PointerCapture::new(),
+            on_tab_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_tab_changed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, usize) + 'a,
+    ) -> IndexHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_tab_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_tab_changed_rc(&mut self, handler: IndexHandler<'a, Self>) {
+        self.on_tab_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_tab_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_tab_changed(&mut self, index: usize) {
+        for h in &self.on_tab_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone(), index)));
+        }
+    }
+
+    pub fn add_tab<S: Into<String>>(
+        &mut self,
+        title: S,
+        widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+    ) -> &mut Self {
+        self.tabs.push(Tab {
+            title: title.into(),
+            widget,
+        });
+        self.layout();
+        self
+    }
+
+    pub fn remove_tab<T: ?Sized>(&mut self, w: Rc<RefCell<T>>) -> &mut Self {
+        self.tabs.drain_filter(|t| is_same(&t.widget, &w)).count();
+        if self.active >= self.tabs.len() {
+            self.active = self.tabs.len().saturating_sub(1);
+        }
+        self.layout();
+        self
+    }
+
+    pub fn tab_count(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn get_title(&self, index: usize) -> &str {
+        &self.tabs[index].title
+    }
+
+    pub fn set_title<S: Into<String>>(&mut self, index: usize, title: S) {
+        self.tabs[index].title = title.into();
+    }
+
+    pub fn get_active(&self) -> usize {
+        self.active
+    }
+
+    /// Switches the page without firing `on_tab_changed` handlers
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.active = index;
+            self.layout();
+        }
+    }
+
+    pub fn set_strip_height(&mut self, strip_height: f32) -> &mut Self {
+        self.strip_height = strip_height;
+        self.layout();
+        self
+    }
+
+    fn switch_to(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.active {
+            self.set_active(index);
+            self.fire_on_tab_changed(index);
+        }
+    }
+
+    fn active_widget(&self) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.tabs.get(self.active).map(|t| t.widget.clone())
+    }
+
+    // Titles of the pages, the active page draws itself
+    fn paint_strip(&self, painter: &mut Painter) {
+        for index in 0..self.tabs.len() {
+            let rect = self.tab_rect(index);
+            if index == self.active {
+                painter.fill_rect(rect, graphics::WHITE);
+            } else {
+                painter.fill_rect(rect, HIGHLIGHT_COLOR);
+                painter.stroke_rect(rect, 1., graphics::WHITE);
+            }
+        }
+        if self.focused && !self.tabs.is_empty() {
+            painter.stroke_rect(self.tab_rect(self.active), 2., graphics::BLACK);
+        }
+        for (index, tab) in self.tabs.iter().enumerate() {
+            let rect = self.tab_rect(index);
+            painter.text(rect, tab.title.as_str(), Align::Center, graphics::BLACK);
+        }
+    }
+
+    fn tab_rect(&self, index: usize) -> Rect {
+        let w = self.rect.w / self.tabs.len() as f32;
+        Rect::new(
+            self.rect.x + w * index as f32,
+            self.rect.y,
+            w,
+            self.strip_height,
+        )
+    }
+
+    fn content_rect(&self) -> Rect {
+        let strip = self.strip_height.min(self.rect.h);
+        Rect::new(
+            self.rect.x,
+            self.rect.y + strip,
+            self.rect.w,
+            self.rect.h - strip,
+        )
+    }
+
+    fn layout(&mut self) {
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().set_rect(self.content_rect());
+        }
+    }
+}
+
+impl IEvents for Tabs<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        match self.active_widget() {
+            Some(w) => w.borrow_mut().update(ctx),
+            None => Ok(()),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_strip(&mut painter);
+        painter.render(ctx)?;
+        match self.active_widget() {
+            Some(w) => w.borrow_mut().draw(ctx),
+            None => Ok(()),
+        }
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.paint_strip(painter);
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().paint_into(ctx, painter)
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.focused = false;
+        if button == MouseButton::Left {
+            if let Some(index) = (0..self.tabs.len()).find(|i| self.tab_rect(*i).contains([x, y])) {
+                self.focused = true;
+                self.switch_to(index);
+            }
+        }
+        let rects = self.active_widget().map(|w| w.borrow().get_rect());
+        self.capture.press(button, x, y, rects);
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().mouse_button_down_event(ctx, button, x, y)
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if let Some(w) = self.active_widget().filter(|_| self.capture.routes_to(0)) {
+            w.borrow_mut().mouse_button_up_event(ctx, button, x, y)
+        }
+        self.capture.release(button);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        if let Some(w) = self.active_widget().filter(|_| self.capture.routes_to(0)) {
+            w.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy)
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().mouse_wheel_event(ctx, x, y)
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        if self.focused {
+            let active = self.active;
+            match keycode {
+                KeyCode::Left if self.active > 0 => self.switch_to(self.active - 1),
+                KeyCode::Right => self.switch_to(self.active + 1),
+                _ => {}
+            }
+            // The key which switched the page isn't for the new page
+            if self.active != active {
+                return;
+            }
+        }
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().key_down_event(ctx, keycode, keymods, repeat)
+        }
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().key_up_event(ctx, keycode, keymods)
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        if let Some(w) = self.active_widget() {
+            w.borrow_mut().text_input_event(ctx, character)
+        }
+    }
+}
+
+impl ILayout for Tabs<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.layout();
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Tabs<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        // Hidden pages may have fired handlers before they were switched off
+        let mut v: Vec<_> = self.pending_handlers.drain(..).collect();
+        for t in &self.tabs {
+            v.append(&mut t.widget.borrow_mut().collect_fired());
+        }
+        v
+    }
+}
+
+impl<'a> IOverlays<'a> for Tabs<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        match self.active_widget() {
+            Some(w) => w.borrow_mut().collect_overlays(),
+            None => Vec::new(),
+        }
+    }
+
+    fn collect_hovered(&self, x: f32, y: f32, hovered: &mut Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>) {
+        collect_hovered_child(self.active_widget().iter(), x, y, hovered)
+    }
+}
+
+pub struct Builder<'a> {
+    tabs: Tabs<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self { tabs: Tabs::new() }
+    }
+
+    pub fn build(self) -> Rc<RefCell<Tabs<'a>>> {
+        let rc = Rc::new(RefCell::new(self.tabs));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn add_tab<S: Into<String>>(
+        mut self,
+        title: S,
+        w: Rc<RefCell<dyn Widget<'a> + 'a>>,
+    ) -> Self {
+        self.tabs.add_tab(title, w);
+        self
+    }
+
+    pub fn set_active(mut self, index: usize) -> Self {
+        self.tabs.set_active(index);
+        self
+    }
+
+    pub fn set_strip_height(mut self, strip_height: f32) -> Self {
+        self.tabs.set_strip_height(strip_height);
+        self
+    }
+
+    pub fn on_tab_changed(mut self, handler: impl Fn(Rc<RefCell<Tabs<'a>>>, usize) + 'a) -> Self {
+        self.tabs.on_tab_changed(handler);
+        self
+    }
+}
@@ -0,0 +1,627 @@
+use crate::gui::painter::Painter;
+use crate::gui::scrollbar::ScrollBar;
+use crate::gui::text_buffer::{Position, TextBuffer};
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{
+    is_same, without_text_margin, Handler, IActions, IContext, IEvents, ILayout, IOverlays, Widget,
+};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect, Text};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+const LINE_HEIGHT: f32 = 20.;
+const MARGIN: f32 = 5.;
+const CARET_WIDTH: f32 = 1.5;
+// Rows scrolled by one wheel step
+const WHEEL_ROWS: f32 = 3.;
+
+/// Part of a buffer line shown as one row of the text area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits the line into `(start, end)` char ranges not wider than `width`.
+/// Lines are broken after spaces where possible and inside too long words
+/// otherwise. Spaces at the end of a row may stick out of it.
+pub fn wrap_line(line: &str, width: f32, advance: impl Fn(char) -> f32) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut x = 0.;
+    let mut space_end = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let w = advance(chars[i]);
+        if x + w > width && i > start && !chars[i].is_whitespace() {
+            let end = match space_end {
+                Some(end) if end > start => end,
+                _ => i,
+            };
+            rows.push((start, end));
+            start = end;
+            x = chars[start..i].iter().map(|c| advance(*c)).sum();
+            space_end = None;
+            continue;
+        }
+        x += w;
+        if chars[i].is_whitespace() {
+            space_end = Some(i + 1);
+        }
+        i += 1;
+    }
+    rows.push((start, chars.len()));
+    rows
+}
+
+/// Multi-line text editor with wrapping, scrolling, mouse selection and
+/// undo. Editing is done by the `TextBuffer`; the widget lays the text
+/// out into rows and maps keys and pointer to buffer operations.
+pub struct TextArea<'a> {
+    buffer: TextBuffer,
+    rect: Rect,
+    focused: bool,
+    hovered: bool,
+    selecting: bool,
+    rows: Vec<Row>,
+    // Buffer version and width the rows were laid out for
+    layout_key: Option<(u64, f32)>,
+    advances: HashMap<char, f32>,
+    scrollbar: ScrollBar,
+    // Horizontal position kept while moving the caret up and down
+    goal_x: Option<f32>,
+    reported_version: u64,
+    on_changed_handlers: Vec<Handler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+impl<'a> TextArea<'a> {
+    fn new() -> Self {
+        Self {
+            buffer: TextBuffer::new(),
+            rect: Rect::zero(),
+            focused: false,
+            hovered: false,
+            selecting: false,
+            rows: Vec::new(),
+            layout_key: None,
+            advances: HashMap::new(),
+            scrollbar: ScrollBar::new(),
+            goal_x: None,
+            reported_version: 0,
+            on_changed_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    /// Handler is called when the text is changed by the user or through
+    /// `buffer_mut`, but not by `set_text`
+    pub fn on_changed(&mut self, handler: impl Fn(Rc<RefCell<Self>>) + 'a) -> Handler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_changed_rc(rc.clone());
+        rc
+    }
+    pub fn on_changed_rc(&mut self, handler: Handler<'a, Self>) {
+        self.on_changed_handlers.push(handler);
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        self.on_changed_handlers
+            .drain_filter(move |h| is_same(h, &handler))
+            .count();
+    }
+
+    fn fire_on_changed(&mut self) {
+        for h in &self.on_changed_handlers {
+            let rcself = self.rcself();
+            let hc = h.clone();
+            self.pending_handlers
+                .push(Rc::new(move || hc(rcself.clone())));
+        }
+    }
+
+    fn check_changed(&mut self) {
+        if self.buffer.version() != self.reported_version {
+            self.reported_version = self.buffer.version();
+            self.fire_on_changed();
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        self.buffer.text()
+    }
+
+    /// Replaces the text and clears undo history without firing handlers
+    pub fn set_text(&mut self, text: &str) -> &mut Self {
+        self.buffer.set_text(text);
+        self.reported_version = self.buffer.version();
+        self.scrollbar.set_first(0);
+        self
+    }
+
+    pub fn buffer(&self) -> &TextBuffer {
+        &self.buffer
+    }
+
+    pub fn buffer_mut(&mut self) -> &mut TextBuffer {
+        &mut self.buffer
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Rows of the text as laid out on the last update or draw
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn first_visible_row(&self) -> usize {
+        self.scrollbar.first()
+    }
+
+    fn text_rect(&self) -> Rect {
+        Rect::new(
+            self.rect.x + MARGIN,
+            self.rect.y + MARGIN,
+            (self.rect.w - ScrollBar::WIDTH - MARGIN * 2.).max(0.),
+            (self.rect.h - MARGIN * 2.).max(0.),
+        )
+    }
+
+    fn advance(&self, c: char) -> f32 {
+        self.advances.get(&c).cloned().unwrap_or(0.)
+    }
+
+    // Single glyphs are measured in runs, as widths are rounded to pixels
+    fn measure(&mut self, ctx: &mut dyn IContext) {
+        let missing: Vec<char> = (0..self.buffer.line_count())
+            .flat_map(|i| self.buffer.line(i).chars().collect::<Vec<_>>())
+            .filter(|c| !self.advances.contains_key(c))
+            .collect();
+        for c in missing {
+            if !self.advances.contains_key(&c) {
+                let glyph = if c == '\t' { ' ' } else { c };
+                let run = glyph.to_string().repeat(8);
+                let width = ctx.text_width(&Text::new(run)) / 8.;
+                let width = if c == '\t' {
+                    width * self.buffer.get_tab_width() as f32
+                } else {
+                    width
+                };
+                self.advances.insert(c, width);
+            }
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut dyn IContext) {
+        let text_rect = self.text_rect();
+        let key = (self.buffer.version(), text_rect.w);
+        if self.layout_key != Some(key) {
+            self.measure(ctx);
+            let mut rows = Vec::new();
+            for line in 0..self.buffer.line_count() {
+                let parts = wrap_line(self.buffer.line(line), text_rect.w, |c| self.advance(c));
+                rows.extend(
+                    parts
+                        .into_iter()
+                        .map(|(start, end)| Row { line, start, end }),
+                );
+            }
+            self.rows = rows;
+            self.layout_key = Some(key);
+        }
+        let visible = (text_rect.h / LINE_HEIGHT) as usize;
+        self.scrollbar.set_range(self.rows.len(), visible.max(1));
+        self.scrollbar.set_rect(Rect::new(
+            self.rect.x + self.rect.w - ScrollBar::WIDTH,
+            self.rect.y,
+            ScrollBar::WIDTH,
+            self.rect.h,
+        ));
+    }
+
+    // Row showing the position. Position at the wrap point belongs to
+    // the row starting there.
+    fn row_of(&self, position: Position) -> usize {
+        let first = self
+            .rows
+            .iter()
+            .position(|r| r.line == position.line)
+            .unwrap_or(0);
+        let mut index = first;
+        while index + 1 < self.rows.len()
+            && self.rows[index + 1].line == position.line
+            && self.rows[index + 1].start <= position.column
+        {
+            index += 1;
+        }
+        index
+    }
+
+    fn row_chars(&self, row: Row) -> impl Iterator<Item = char> + '_ {
+        self.buffer
+            .line(row.line)
+            .chars()
+            .skip(row.start)
+            .take(row.end - row.start)
+    }
+
+    fn x_of(&self, row: Row, column: usize) -> f32 {
+        self.row_chars(row)
+            .take(column.saturating_sub(row.start))
+            .map(|c| self.advance(c))
+            .sum()
+    }
+
+    // Column nearest to x in the row. Wrap point of the row belongs to
+    // the next row, so the caret stops before it.
+    fn column_at(&self, index: usize, x: f32) -> usize {
+        let row = self.rows[index];
+        let last_in_line = match self.rows.get(index + 1) {
+            Some(next) => next.line != row.line,
+            None => true,
+        };
+        let max = if last_in_line || row.end == row.start {
+            row.end
+        } else {
+            row.end - 1
+        };
+        let mut left = 0.;
+        let mut column = row.start;
+        for c in self.row_chars(row) {
+            let w = self.advance(c);
+            if column >= max || left + w / 2. > x {
+                break;
+            }
+            left += w;
+            column += 1;
+        }
+        column
+    }
+
+    fn position_at(&self, x: f32, y: f32) -> Position {
+        if self.rows.is_empty() {
+            return Position::default();
+        }
+        let text_rect = self.text_rect();
+        let offset = ((y - text_rect.y) / LINE_HEIGHT).floor();
+        let index = (self.scrollbar.first() as f32 + offset)
+            .max(0.)
+            .min((self.rows.len() - 1) as f32) as usize;
+        Position::new(
+            self.rows[index].line,
+            self.column_at(index, x - text_rect.x),
+        )
+    }
+
+    fn ensure_caret_visible(&mut self) {
+        let row = self.row_of(self.buffer.caret());
+        self.scrollbar.scroll_to(row);
+    }
+
+    fn move_rows(&mut self, delta: isize, extend: bool) {
+        let caret = self.buffer.caret();
+        let index = self.row_of(caret);
+        let goal_x = match self.goal_x {
+            Some(x) => x,
+            None => self.x_of(self.rows[index], caret.column),
+        };
+        let target = (index as isize + delta)
+            .max(0)
+            .min(self.rows.len() as isize - 1) as usize;
+        let position = if target == index && delta < 0 {
+            Position::new(caret.line, 0)
+        } else if target == index && delta > 0 {
+            self.buffer.end()
+        } else {
+            Position::new(self.rows[target].line, self.column_at(target, goal_x))
+        };
+        self.buffer.set_caret(position, extend);
+        self.goal_x = Some(goal_x);
+    }
+
+    fn key_down(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        self.layout(ctx);
+        let shift = keymods.contains(KeyMods::SHIFT);
+        let ctrl = keymods.contains(KeyMods::CTRL);
+        let caret = self.buffer.caret();
+        let selection = self.buffer.selection().filter(|_| !shift);
+        let mut keep_goal = false;
+        match keycode {
+            KeyCode::Left => {
+                let target = match selection {
+                    Some((start, _)) if !ctrl => start,
+                    _ if ctrl => self.buffer.word_left_of(caret),
+                    _ => self.buffer.left_of(caret),
+                };
+                self.buffer.set_caret(target, shift);
+            }
+            KeyCode::Right => {
+                let target = match selection {
+                    Some((_, end)) if !ctrl => end,
+                    _ if ctrl => self.buffer.word_right_of(caret),
+                    _ => self.buffer.right_of(caret),
+                };
+                self.buffer.set_caret(target, shift);
+            }
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => {
+                let page = self.scrollbar.visible().max(1) as isize;
+                let delta = match keycode {
+                    KeyCode::Up => -1,
+                    KeyCode::Down => 1,
+                    KeyCode::PageUp => -page,
+                    _ => page,
+                };
+                self.move_rows(delta, shift);
+                keep_goal = true;
+            }
+            KeyCode::Home => {
+                let target = if ctrl {
+                    Position::default()
+                } else {
+                    Position::new(caret.line, 0)
+                };
+                self.buffer.set_caret(target, shift);
+            }
+            KeyCode::End => {
+                let target = if ctrl {
+                    self.buffer.end()
+                } else {
+                    Position::new(caret.line, self.buffer.line_len(caret.line))
+                };
+                self.buffer.set_caret(target, shift);
+            }
+            KeyCode::Back => self.buffer.backspace(ctrl),
+            KeyCode::Delete => self.buffer.delete(ctrl),
+            KeyCode::Return | KeyCode::NumpadEnter => self.buffer.new_line(),
+            KeyCode::Tab if shift => self.buffer.unindent(),
+            KeyCode::Tab => self.buffer.tab(),
+            KeyCode::A if ctrl => self.buffer.select_all(),
+            KeyCode::Z if ctrl && shift => {
+                self.buffer.redo();
+            }
+            KeyCode::Z if ctrl => {
+                self.buffer.undo();
+            }
+            KeyCode::Y if ctrl => {
+                self.buffer.redo();
+            }
+            KeyCode::Escape => self.buffer.set_caret(caret, false),
+            _ => return,
+        }
+        if !keep_goal {
+            self.goal_x = None;
+        }
+        self.layout(ctx);
+        self.ensure_caret_visible();
+        self.check_changed();
+    }
+
+    fn paint(&self, painter: &mut Painter) {
+        let text_rect = self.text_rect();
+        painter.fill_rect(self.rect, graphics::WHITE);
+        let row_y =
+            |index: usize| text_rect.y + (index - self.scrollbar.first()) as f32 * LINE_HEIGHT;
+        if let Some((start, end)) = self.buffer.selection() {
+            for index in self.scrollbar.rows() {
+                let row = self.rows[index];
+                let from = if row.line == start.line {
+                    start.column.max(row.start)
+                } else {
+                    row.start
+                };
+                let to = if row.line == end.line {
+                    end.column.min(row.end)
+                } else {
+                    row.end
+                };
+                let inside = (row.line, row.end) >= (start.line, start.column)
+                    && (row.line, row.start) <= (end.line, end.column);
+                if !inside || from > to {
+                    continue;
+                }
+                let x0 = self.x_of(row, from);
+                let mut x1 = self.x_of(row, to);
+                // Selected line break is shown as a narrow box
+                if row.line < end.line && to == row.end {
+                    x1 += MARGIN;
+                }
+                if x1 > x0 {
+                    painter.fill_rect(
+                        Rect::new(text_rect.x + x0, row_y(index), x1 - x0, LINE_HEIGHT),
+                        HIGHLIGHT_COLOR,
+                    );
+                }
+            }
+        }
+        if self.focused {
+            let caret = self.buffer.caret();
+            let index = self.row_of(caret);
+            if self.scrollbar.rows().contains(&index) {
+                let x = text_rect.x + self.x_of(self.rows[index], caret.column);
+                painter.fill_rect(
+                    Rect::new(x, row_y(index), CARET_WIDTH, LINE_HEIGHT),
+                    graphics::BLACK,
+                );
+            }
+            painter.stroke_rect(self.rect, 2., graphics::BLACK);
+        }
+        self.scrollbar.paint(painter);
+        for index in self.scrollbar.rows() {
+            let row = self.rows[index];
+            let text: String = self
+                .row_chars(row)
+                .map(|c| if c == '\t' { ' ' } else { c })
+                .collect();
+            if !text.is_empty() {
+                let rect = Rect::new(text_rect.x, row_y(index), text_rect.w, LINE_HEIGHT);
+                painter.text(
+                    without_text_margin(rect),
+                    text,
+                    Align::Left,
+                    graphics::BLACK,
+                );
+            }
+        }
+    }
+}
+
+impl IEvents for TextArea<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        self.layout(ctx);
+        self.check_changed();
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(ctx, &mut painter);
+        painter.render(ctx)
+    }
+
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.layout(ctx);
+        self.paint(painter);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if !self.rect.contains([x, y]) {
+            self.focused = false;
+            return;
+        }
+        self.focused = true;
+        if button != MouseButton::Left || self.scrollbar.mouse_down(x, y) {
+            return;
+        }
+        self.layout(ctx);
+        let extend = ctx.keymods().contains(KeyMods::SHIFT);
+        let position = self.position_at(x, y);
+        self.buffer.set_caret(position, extend);
+        self.goal_x = None;
+        self.selecting = true;
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        if button == MouseButton::Left {
+            self.selecting = false;
+            self.scrollbar.mouse_up();
+        }
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+        if self.scrollbar.is_dragging() {
+            self.scrollbar.mouse_motion(y);
+        } else if self.selecting {
+            self.layout(ctx);
+            let position = self.position_at(x, y);
+            self.buffer.set_caret(position, true);
+            self.ensure_caret_visible();
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, y: f32) {
+        if self.hovered {
+            self.scrollbar.scroll_by((-y * WHEEL_ROWS) as isize);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(ctx, keycode, keymods)
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        if self.focused && !character.is_control() {
+            self.buffer.type_char(character);
+            self.goal_x = None;
+            self.layout(ctx);
+            self.ensure_caret_visible();
+            self.check_changed();
+        }
+    }
+}
+
+impl ILayout for TextArea<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for TextArea<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for TextArea<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    text_area: TextArea<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            text_area: TextArea::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<TextArea<'a>>> {
+        let rc = Rc::new(RefCell::new(self.text_area));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_text(mut self, text: &str) -> Self {
+        self.text_area.set_text(text);
+        self
+    }
+
+    pub fn set_tab_width(mut self, tab_width: usize) -> Self {
+        self.text_area.buffer_mut().set_tab_width(tab_width);
+        self
+    }
+
+    pub fn on_changed(mut self, handler: impl Fn(Rc<RefCell<TextArea<'a>>>) + 'a) -> Self {
+        self.text_area.on_changed(handler);
+        self
+    }
+}
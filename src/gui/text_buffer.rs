@@ -0,0 +1,514 @@
+/// Place in the text: line index and char index in the line
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditKind {
+    Typing,
+    Backspace,
+    Delete,
+    Other,
+}
+
+// Replacement of `removed` starting at `start` by `inserted`, with caret
+// and selection anchor before and after it
+#[derive(Clone, Debug)]
+struct Edit {
+    start: Position,
+    removed: String,
+    inserted: String,
+    before: (Position, Option<Position>),
+    after: (Position, Option<Position>),
+    kind: EditKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punct,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+fn byte_index(line: &str, column: usize) -> usize {
+    line.char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index)
+}
+
+// Position after `text` inserted at `start`
+fn end_of(start: Position, text: &str) -> Position {
+    let mut lines = text.split('\n');
+    let first = lines.next().unwrap_or_default().chars().count();
+    match lines.enumerate().last() {
+        Some((n, last)) => Position::new(start.line + n + 1, last.chars().count()),
+        None => Position::new(start.line, start.column + first),
+    }
+}
+
+/// Editable text kept as a list of lines, with caret, selection and
+/// undo history. Doesn't depend on ggez, so that editing logic can be
+/// used and tested without text layout. Columns count chars, not bytes.
+/// Consecutive typing or deleting is undone as one step; moving the caret
+/// or typing a space after a word starts a new step.
+pub struct TextBuffer {
+    lines: Vec<String>,
+    caret: Position,
+    anchor: Option<Position>,
+    tab_width: usize,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    coalesce: bool,
+    version: u64,
+}
+
+impl TextBuffer {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![String::new()],
+            caret: Position::default(),
+            anchor: None,
+            tab_width: 4,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            coalesce: false,
+            version: 0,
+        }
+    }
+
+    /// Replaces the whole text, clears selection and history
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.split('\n').map(|l| l.replace('\r', "")).collect();
+        self.caret = Position::default();
+        self.anchor = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.coalesce = false;
+        self.version += 1;
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Incremented on each change of the text
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&self, index: usize) -> &str {
+        &self.lines[index]
+    }
+
+    pub fn line_len(&self, index: usize) -> usize {
+        self.lines[index].chars().count()
+    }
+
+    pub fn get_tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width.max(1);
+    }
+
+    pub fn caret(&self) -> Position {
+        self.caret
+    }
+
+    pub fn anchor(&self) -> Option<Position> {
+        self.anchor
+    }
+
+    /// Ordered bounds of the selection, `None` if nothing is selected
+    pub fn selection(&self) -> Option<(Position, Position)> {
+        match self.anchor {
+            Some(anchor) if anchor != self.caret => {
+                Some((anchor.min(self.caret), anchor.max(self.caret)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> String {
+        match self.selection() {
+            Some((start, end)) => self.slice(start, end),
+            None => String::new(),
+        }
+    }
+
+    /// Moves the caret; with `extend` the selection is extended to it
+    pub fn set_caret(&mut self, position: Position, extend: bool) {
+        let position = self.clamp(position);
+        if extend {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = position;
+        self.coalesce = false;
+    }
+
+    pub fn select_all(&mut self) {
+        self.set_caret(Position::default(), false);
+        self.set_caret(self.end(), true);
+    }
+
+    pub fn clamp(&self, position: Position) -> Position {
+        let line = position.line.min(self.lines.len() - 1);
+        Position::new(line, position.column.min(self.line_len(line)))
+    }
+
+    /// Position after the last char
+    pub fn end(&self) -> Position {
+        let line = self.lines.len() - 1;
+        Position::new(line, self.line_len(line))
+    }
+
+    pub fn slice(&self, start: Position, end: Position) -> String {
+        let (start, end) = (self.clamp(start), self.clamp(end));
+        if start.line == end.line {
+            let line = &self.lines[start.line];
+            return line[byte_index(line, start.column)..byte_index(line, end.column)].to_string();
+        }
+        let first = &self.lines[start.line];
+        let last = &self.lines[end.line];
+        let mut text = first[byte_index(first, start.column)..].to_string();
+        for line in &self.lines[start.line + 1..end.line] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.push_str(&last[..byte_index(last, end.column)]);
+        text
+    }
+
+    fn char_before(&self, position: Position) -> Option<char> {
+        if position.column == 0 {
+            return None;
+        }
+        self.lines[position.line].chars().nth(position.column - 1)
+    }
+
+    fn char_at(&self, position: Position) -> Option<char> {
+        self.lines[position.line].chars().nth(position.column)
+    }
+
+    pub fn left_of(&self, position: Position) -> Position {
+        if position.column > 0 {
+            Position::new(position.line, position.column - 1)
+        } else if position.line > 0 {
+            Position::new(position.line - 1, self.line_len(position.line - 1))
+        } else {
+            position
+        }
+    }
+
+    pub fn right_of(&self, position: Position) -> Position {
+        if position.column < self.line_len(position.line) {
+            Position::new(position.line, position.column + 1)
+        } else if position.line + 1 < self.lines.len() {
+            Position::new(position.line + 1, 0)
+        } else {
+            position
+        }
+    }
+
+    /// Start of the word before the position, skipping spaces. At the
+    /// start of a line it is the end of the previous line.
+    pub fn word_left_of(&self, position: Position) -> Position {
+        let mut p = self.clamp(position);
+        if p.column == 0 {
+            return self.left_of(p);
+        }
+        while self.char_before(p).map(char_class) == Some(CharClass::Space) {
+            p.column -= 1;
+        }
+        if let Some(class) = self.char_before(p).map(char_class) {
+            while self.char_before(p).map(char_class) == Some(class) {
+                p.column -= 1;
+            }
+        }
+        p
+    }
+
+    /// End of the word after the position and the spaces following it.
+    /// At the end of a line it is the start of the next line.
+    pub fn word_right_of(&self, position: Position) -> Position {
+        let mut p = self.clamp(position);
+        if p.column == self.line_len(p.line) {
+            return self.right_of(p);
+        }
+        if let Some(class) = self.char_at(p).map(char_class) {
+            while self.char_at(p).map(char_class) == Some(class) {
+                p.column += 1;
+            }
+        }
+        while self.char_at(p).map(char_class) == Some(CharClass::Space) {
+            p.column += 1;
+        }
+        p
+    }
+
+    // Replaces text between positions without touching history
+    fn splice(&mut self, start: Position, end: Position, text: &str) -> String {
+        let removed = self.slice(start, end);
+        let first = &self.lines[start.line];
+        let last = &self.lines[end.line];
+        let head = first[..byte_index(first, start.column)].to_string();
+        let tail = last[byte_index(last, end.column)..].to_string();
+        let mut new_lines: Vec<String> = text.split('\n').map(String::from).collect();
+        new_lines[0].insert_str(0, &head);
+        new_lines.last_mut().unwrap().push_str(&tail);
+        self.lines.splice(start.line..=end.line, new_lines);
+        self.version += 1;
+        removed
+    }
+
+    fn replace(&mut self, start: Position, end: Position, text: &str, kind: EditKind) {
+        let (start, end) = (self.clamp(start.min(end)), self.clamp(start.max(end)));
+        let text = text.replace('\r', "");
+        if start == end && text.is_empty() {
+            return;
+        }
+        let before = (self.caret, self.anchor);
+        let removed = self.splice(start, end, &text);
+        self.caret = end_of(start, &text);
+        self.anchor = None;
+        let edit = Edit {
+            start,
+            removed,
+            inserted: text,
+            before,
+            after: (self.caret, None),
+            kind,
+        };
+        self.redo.clear();
+        if !(self.coalesce && self.merge(&edit)) {
+            self.undo.push(edit);
+        }
+        self.coalesce = kind != EditKind::Other;
+    }
+
+    // Appends the edit to the last one if they are parts of one run
+    fn merge(&mut self, edit: &Edit) -> bool {
+        let last = match self.undo.last_mut() {
+            Some(last) if last.kind == edit.kind => last,
+            _ => return false,
+        };
+        match edit.kind {
+            EditKind::Typing => {
+                let word_ended = edit.inserted.starts_with(char::is_whitespace)
+                    && !last.inserted.ends_with(char::is_whitespace);
+                if word_ended
+                    || edit.inserted.contains('\n')
+                    || edit.start != end_of(last.start, &last.inserted)
+                {
+                    return false;
+                }
+                last.inserted.push_str(&edit.inserted);
+            }
+            EditKind::Backspace => {
+                if end_of(edit.start, &edit.removed) != last.start {
+                    return false;
+                }
+                last.removed.insert_str(0, &edit.removed);
+                last.start = edit.start;
+            }
+            EditKind::Delete => {
+                if edit.start != last.start {
+                    return false;
+                }
+                last.removed.push_str(&edit.removed);
+            }
+            EditKind::Other => return false,
+        }
+        last.after = edit.after;
+        true
+    }
+
+    /// Replaces selection by the text as a separate undo step
+    pub fn insert(&mut self, text: &str) {
+        let (start, end) = self.selection().unwrap_or((self.caret, self.caret));
+        self.replace(start, end, text, EditKind::Other);
+    }
+
+    /// Replaces selection by the char, merging with typing before it
+    pub fn type_char(&mut self, c: char) {
+        match self.selection() {
+            Some((start, end)) => {
+                self.replace(start, end, &c.to_string(), EditKind::Other);
+            }
+            None => {
+                let caret = self.caret;
+                self.replace(caret, caret, &c.to_string(), EditKind::Typing);
+            }
+        }
+    }
+
+    pub fn new_line(&mut self) {
+        self.insert("\n");
+    }
+
+    /// Deletes selection, or the char (or word) before the caret
+    pub fn backspace(&mut self, word: bool) {
+        if let Some((start, end)) = self.selection() {
+            self.replace(start, end, "", EditKind::Other);
+            return;
+        }
+        let caret = self.caret;
+        let start = if word {
+            self.word_left_of(caret)
+        } else {
+            self.left_of(caret)
+        };
+        self.replace(start, caret, "", EditKind::Backspace);
+    }
+
+    /// Deletes selection, or the char (or word) after the caret
+    pub fn delete(&mut self, word: bool) {
+        if let Some((start, end)) = self.selection() {
+            self.replace(start, end, "", EditKind::Other);
+            return;
+        }
+        let caret = self.caret;
+        let end = if word {
+            self.word_right_of(caret)
+        } else {
+            self.right_of(caret)
+        };
+        self.replace(caret, end, "", EditKind::Delete);
+    }
+
+    /// Inserts spaces up to the next tab stop, or indents all selected
+    /// lines if the selection spans several lines
+    pub fn tab(&mut self) {
+        match self.selection() {
+            Some((start, end)) if start.line != end.line => self.indent_lines(start, end, true),
+            _ => {
+                let column = self.selection().map_or(self.caret, |s| s.0).column;
+                let spaces = self.tab_width - column % self.tab_width;
+                self.insert(&" ".repeat(spaces));
+            }
+        }
+    }
+
+    /// Removes up to tab width of leading spaces from the current or
+    /// selected lines
+    pub fn unindent(&mut self) {
+        let (start, end) = self.selection().unwrap_or((self.caret, self.caret));
+        self.indent_lines(start, end, false);
+    }
+
+    fn indent_lines(&mut self, start: Position, end: Position, indent: bool) {
+        // Line where selection ends at its start is not indented
+        let last = if end.line > start.line && end.column == 0 {
+            end.line - 1
+        } else {
+            end.line
+        };
+        let mut shifts = Vec::new();
+        let lines: Vec<String> = (start.line..=last)
+            .map(|index| {
+                let line = &self.lines[index];
+                if indent {
+                    shifts.push(self.tab_width as isize);
+                    format!("{}{}", " ".repeat(self.tab_width), line)
+                } else {
+                    let n = line
+                        .chars()
+                        .take(self.tab_width)
+                        .take_while(|c| *c == ' ')
+                        .count();
+                    shifts.push(-(n as isize));
+                    line[n..].to_string()
+                }
+            })
+            .collect();
+        if shifts.iter().all(|s| *s == 0) {
+            return;
+        }
+        let shift = |p: Position| {
+            if p.line < start.line || p.line > last {
+                return p;
+            }
+            let column = p.column as isize + shifts[p.line - start.line];
+            Position::new(p.line, column.max(0) as usize)
+        };
+        let (caret, anchor) = (shift(self.caret), self.anchor.map(shift));
+        let from = Position::new(start.line, 0);
+        let to = Position::new(last, self.line_len(last));
+        self.replace(from, to, &lines.join("\n"), EditKind::Other);
+        // Keep the selection over the shifted lines
+        self.caret = self.clamp(caret);
+        self.anchor = anchor.map(|a| self.clamp(a));
+        if let Some(edit) = self.undo.last_mut() {
+            edit.after = (self.caret, self.anchor);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let edit = match self.undo.pop() {
+            Some(edit) => edit,
+            None => return false,
+        };
+        let end = end_of(edit.start, &edit.inserted);
+        self.splice(edit.start, end, &edit.removed);
+        self.caret = edit.before.0;
+        self.anchor = edit.before.1;
+        self.redo.push(edit);
+        self.coalesce = false;
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let edit = match self.redo.pop() {
+            Some(edit) => edit,
+            None => return false,
+        };
+        let end = end_of(edit.start, &edit.removed);
+        self.splice(edit.start, end, &edit.inserted);
+        self.caret = edit.after.0;
+        self.anchor = edit.after.1;
+        self.undo.push(edit);
+        self.coalesce = false;
+        true
+    }
+
+    /// Forgets undo and redo history
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.coalesce = false;
+    }
+}
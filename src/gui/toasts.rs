@@ -0,0 +1,318 @@
+use crate::gui::painter::Painter;
+use crate::gui::{IActions, HIGHLIGHT_COLOR};
+use ggez::graphics::{self, Align, Color, Rect};
+use ggez::{Context, GameResult};
+use std::rc::Rc;
+use std::time::Duration;
+
+const WIDTH: f32 = 280.;
+// Distance from the screen edges and between toasts
+const MARGIN: f32 = 10.;
+const PADDING: f32 = 6.;
+const STRIPE_WIDTH: f32 = 5.;
+const LINE_HEIGHT: f32 = 20.;
+const ACTION_WIDTH: f32 = 80.;
+const ACTION_HEIGHT: f32 = 24.;
+
+pub type ToastId = u64;
+
+pub type ActionHandler<'a> = Rc<dyn Fn(ToastId) + 'a>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn color(self) -> Color {
+        match self {
+            Level::Info => Color::from_rgb(60, 120, 220),
+            Level::Success => Color::from_rgb(40, 170, 80),
+            Level::Warning => Color::from_rgb(230, 160, 20),
+            Level::Error => Color::from_rgb(210, 50, 50),
+        }
+    }
+}
+
+/// Screen corner the toasts are stacked from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+struct Action<'a> {
+    label: String,
+    handler: ActionHandler<'a>,
+}
+
+struct Toast<'a> {
+    id: ToastId,
+    text: String,
+    level: Level,
+    duration: Duration,
+    // Set on the first update after the toast is added
+    shown_at: Option<Duration>,
+    actions: Vec<Action<'a>>,
+    rect: Rect,
+}
+
+impl Toast<'_> {
+    fn height(&self) -> f32 {
+        let lines = self.text.lines().count().max(1) as f32;
+        let actions = if self.actions.is_empty() {
+            0.
+        } else {
+            ACTION_HEIGHT + PADDING
+        };
+        lines * LINE_HEIGHT + actions + PADDING * 2.
+    }
+
+    fn action_rect(&self, index: usize) -> Rect {
+        let from_right = (self.actions.len() - index) as f32;
+        Rect::new(
+            self.rect.x + self.rect.w - (ACTION_WIDTH + PADDING) * from_right,
+            self.rect.y + self.rect.h - PADDING - ACTION_HEIGHT,
+            ACTION_WIDTH,
+            ACTION_HEIGHT,
+        )
+    }
+}
+
+/// Transient notifications stacked in a screen corner. Each toast fades
+/// out at the end of its duration and is dismissed earlier by a click.
+/// Action buttons dismiss the toast and fire their handlers through
+/// `IActions`. `WindowManager` owns it and draws it above everything.
+pub struct Toasts<'a> {
+    toasts: Vec<Toast<'a>>,
+    next_id: ToastId,
+    corner: Corner,
+    fade: Duration,
+    now: Duration,
+    screen: Rect,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+}
+
+impl<'a> Toasts<'a> {
+    pub fn new() -> Self {
+        Self {
+            toasts: Vec::new(),
+            next_id: 0,
+            corner: Corner::BottomRight,
+            fade: Duration::from_millis(300),
+            now: Duration::from_secs(0),
+            screen: Rect::zero(),
+            pending_handlers: Vec::new(),
+        }
+    }
+
+    /// Shows the text for the duration. Text may have several lines.
+    pub fn notify<S: Into<String>>(
+        &mut self,
+        text: S,
+        level: Level,
+        duration: Duration,
+    ) -> ToastId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.toasts.push(Toast {
+            id,
+            text: text.into(),
+            level,
+            duration,
+            shown_at: None,
+            actions: Vec::new(),
+            rect: Rect::zero(),
+        });
+        self.layout();
+        id
+    }
+
+    /// Adds button to the toast. Does nothing if the toast is already gone.
+    pub fn add_action<S: Into<String>>(
+        &mut self,
+        id: ToastId,
+        label: S,
+        handler: impl Fn(ToastId) + 'a,
+    ) {
+        if let Some(toast) = self.toasts.iter_mut().find(|t| t.id == id) {
+            toast.actions.push(Action {
+                label: label.into(),
+                handler: Rc::new(handler),
+            });
+            self.layout();
+        }
+    }
+
+    pub fn dismiss(&mut self, id: ToastId) {
+        self.toasts.retain(|t| t.id != id);
+        self.layout();
+    }
+
+    pub fn clear(&mut self) {
+        self.toasts.clear();
+    }
+
+    pub fn is_shown(&self, id: ToastId) -> bool {
+        self.toasts.iter().any(|t| t.id == id)
+    }
+
+    /// Ids of the toasts, the oldest first
+    pub fn ids(&self) -> Vec<ToastId> {
+        self.toasts.iter().map(|t| t.id).collect()
+    }
+
+    pub fn get_rect(&self, id: ToastId) -> Option<Rect> {
+        self.toasts.iter().find(|t| t.id == id).map(|t| t.rect)
+    }
+
+    pub fn set_corner(&mut self, corner: Corner) {
+        self.corner = corner;
+        self.layout();
+    }
+
+    pub fn get_corner(&self) -> Corner {
+        self.corner
+    }
+
+    /// Time of fading out at the end of the toast duration
+    pub fn set_fade(&mut self, fade: Duration) {
+        self.fade = fade;
+    }
+
+    /// Opacity of the toast, 1 until it starts fading out
+    pub fn opacity(&self, id: ToastId) -> f32 {
+        match self.toasts.iter().find(|t| t.id == id) {
+            Some(toast) => self.toast_opacity(toast),
+            None => 0.,
+        }
+    }
+
+    fn toast_opacity(&self, toast: &Toast) -> f32 {
+        let elapsed = match toast.shown_at {
+            Some(at) => self.now.checked_sub(at).unwrap_or_default(),
+            None => return 1.,
+        };
+        let left = toast.duration.checked_sub(elapsed).unwrap_or_default();
+        if left >= self.fade || self.fade == Duration::from_secs(0) {
+            1.
+        } else {
+            left.as_secs_f32() / self.fade.as_secs_f32()
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.toasts.iter().any(|t| t.rect.contains([x, y]))
+    }
+
+    /// Click on the toast dismisses it, click on an action button also
+    /// fires the action. Returns false if no toast is under the point.
+    pub fn pointer_pressed(&mut self, x: f32, y: f32) -> bool {
+        let index = match self.toasts.iter().position(|t| t.rect.contains([x, y])) {
+            Some(index) => index,
+            None => return false,
+        };
+        let toast = self.toasts.remove(index);
+        if let Some(action) =
+            (0..toast.actions.len()).find(|i| toast.action_rect(*i).contains([x, y]))
+        {
+            let id = toast.id;
+            let handler = toast.actions[action].handler.clone();
+            self.pending_handlers.push(Rc::new(move || handler(id)));
+        }
+        self.layout();
+        true
+    }
+
+    /// Starts timers of new toasts and removes expired ones
+    pub fn update(&mut self, now: Duration, screen: Rect) {
+        self.now = now;
+        for toast in &mut self.toasts {
+            toast.shown_at.get_or_insert(now);
+        }
+        self.toasts.retain(|t| {
+            now.checked_sub(t.shown_at.unwrap_or(now))
+                .unwrap_or_default()
+                < t.duration
+        });
+        self.screen = screen;
+        self.layout();
+    }
+
+    fn layout(&mut self) {
+        let screen = self.screen;
+        let width = WIDTH.min(screen.w - MARGIN * 2.).max(0.);
+        let left = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => screen.x + MARGIN,
+            Corner::TopRight | Corner::BottomRight => screen.x + screen.w - MARGIN - width,
+        };
+        let from_top = match self.corner {
+            Corner::TopLeft | Corner::TopRight => true,
+            Corner::BottomLeft | Corner::BottomRight => false,
+        };
+        let mut offset = MARGIN;
+        for toast in &mut self.toasts {
+            let height = toast.height();
+            let top = if from_top {
+                screen.y + offset
+            } else {
+                screen.y + screen.h - offset - height
+            };
+            toast.rect = Rect::new(left, top, width, height);
+            offset += height + MARGIN;
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_into(&mut painter);
+        painter.render(ctx)
+    }
+
+    pub fn paint_into(&self, painter: &mut Painter) {
+        for toast in &self.toasts {
+            let opacity = self.toast_opacity(toast);
+            let faded = |c: Color| Color::new(c.r, c.g, c.b, c.a * opacity);
+            let rect = toast.rect;
+            painter.fill_rect(rect, faded(graphics::WHITE));
+            painter.fill_rect(
+                Rect::new(rect.x, rect.y, STRIPE_WIDTH, rect.h),
+                faded(toast.level.color()),
+            );
+            painter.stroke_rect(rect, 1., faded(graphics::BLACK));
+            for index in 0..toast.actions.len() {
+                let action = toast.action_rect(index);
+                painter.fill_rect(action, faded(HIGHLIGHT_COLOR));
+                painter.stroke_rect(action, 1., faded(graphics::BLACK));
+            }
+            for (index, line) in toast.text.lines().enumerate() {
+                let line_rect = Rect::new(
+                    rect.x + STRIPE_WIDTH + PADDING,
+                    rect.y + PADDING + index as f32 * LINE_HEIGHT,
+                    rect.w - STRIPE_WIDTH - PADDING * 2.,
+                    LINE_HEIGHT,
+                );
+                painter.text(line_rect, line, Align::Left, faded(graphics::BLACK));
+            }
+            for (index, action) in toast.actions.iter().enumerate() {
+                painter.text(
+                    toast.action_rect(index),
+                    action.label.as_str(),
+                    Align::Center,
+                    faded(graphics::BLACK),
+                );
+            }
+        }
+    }
+}
+
+impl<'a> IActions<'a> for Toasts<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
@@ -0,0 +1,180 @@
+use crate::gui::Widget;
+use ggez::graphics::{self, DrawMode, DrawParam, MeshBuilder, Rect, Text};
+use ggez::nalgebra::Point2;
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+const PADDING: f32 = 5.;
+// Tooltip is placed below the pointer so that the cursor doesn't cover it
+const POINTER_OFFSET: f32 = 20.;
+
+struct Entry<'a> {
+    widget: Weak<RefCell<dyn Widget<'a> + 'a>>,
+    text: String,
+}
+
+/// Tooltip texts of widgets and the timer which shows the tooltip when
+/// the pointer rests on a widget. `WindowManager` owns it, passes pointer
+/// events to it and draws it above all windows and overlays.
+pub struct Tooltips<'a> {
+    entries: Vec<Entry<'a>>,
+    delay: Duration,
+    pointer: Option<[f32; 2]>,
+    moved: bool,
+    hovered: Option<usize>,
+    since: Duration,
+    shown: Option<[f32; 2]>,
+    dismissed: bool,
+}
+
+impl<'a> Tooltips<'a> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            delay: Duration::from_millis(500),
+            pointer: None,
+            moved: false,
+            hovered: None,
+            since: Duration::from_secs(0),
+            shown: None,
+            dismissed: false,
+        }
+    }
+
+    /// Replaces the tooltip if the widget already has one. Only a weak
+    /// reference is kept, so tooltips of dropped widgets disappear.
+    pub fn set_tooltip<S: Into<String>>(
+        &mut self,
+        widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+        text: S,
+    ) {
+        let text = text.into();
+        match self.find(&widget) {
+            Some(index) => self.entries[index].text = text,
+            None => self.entries.push(Entry {
+                widget: Rc::downgrade(&widget),
+                text,
+            }),
+        }
+    }
+
+    pub fn remove_tooltip(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) {
+        if let Some(index) = self.find(&widget) {
+            self.entries.remove(index);
+            self.reset();
+        }
+    }
+
+    pub fn get_tooltip(&self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<&str> {
+        self.find(&widget)
+            .map(|index| self.entries[index].text.as_str())
+    }
+
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn pointer(&self) -> Option<[f32; 2]> {
+        self.pointer
+    }
+
+    /// Text and pointer position of the tooltip which is shown now
+    pub fn shown(&self) -> Option<(&str, [f32; 2])> {
+        match (self.hovered, self.shown) {
+            (Some(index), Some(at)) => Some((self.entries[index].text.as_str(), at)),
+            _ => None,
+        }
+    }
+
+    fn find(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.widget.as_ptr() as *const () == Rc::as_ptr(widget) as *const ())
+    }
+
+    fn reset(&mut self) {
+        self.hovered = None;
+        self.shown = None;
+    }
+
+    // The innermost of the hovered widgets which has a tooltip
+    fn entry_of(&self, hovered: &[Rc<RefCell<dyn Widget<'a> + 'a>>]) -> Option<usize> {
+        hovered.iter().rev().find_map(|w| self.find(w))
+    }
+
+    pub fn pointer_moved(&mut self, x: f32, y: f32) {
+        self.pointer = Some([x, y]);
+        self.moved = true;
+    }
+
+    /// Click hides the tooltip until the pointer leaves the widget
+    pub fn pointer_pressed(&mut self) {
+        self.shown = None;
+        self.dismissed = true;
+    }
+
+    /// Restarts the timer when the pointer moves to another widget or moves
+    /// before the tooltip is shown. `hovered` are the widgets which get the
+    /// pointer, outermost first, see `IOverlays::collect_hovered`.
+    pub fn update(&mut self, now: Duration, hovered: &[Rc<RefCell<dyn Widget<'a> + 'a>>]) {
+        let count = self.entries.len();
+        self.entries.retain(|e| e.widget.strong_count() > 0);
+        if self.entries.len() != count {
+            self.reset();
+        }
+        let target = match self.pointer {
+            Some(_) => self.entry_of(hovered),
+            None => None,
+        };
+        if target != self.hovered {
+            self.hovered = target;
+            self.shown = None;
+            self.dismissed = false;
+            self.since = now;
+        } else if self.moved && self.shown.is_none() {
+            self.since = now;
+        }
+        self.moved = false;
+        if self.hovered.is_some()
+            && self.shown.is_none()
+            && !self.dismissed
+            && now.checked_sub(self.since).unwrap_or_default() >= self.delay
+        {
+            self.shown = self.pointer;
+        }
+    }
+
+    /// Draws the tooltip near the pointer, moved inside the screen rect
+    pub fn draw(&self, ctx: &mut Context, screen: Rect) -> GameResult {
+        let (text, [px, py]) = match self.shown() {
+            Some(shown) => shown,
+            None => return Ok(()),
+        };
+        let text = Text::new(text);
+        let w = text.width(ctx) as f32 + PADDING * 2.;
+        let h = text.height(ctx) as f32 + PADDING * 2.;
+        let x = px.min(screen.x + screen.w - w).max(screen.x);
+        let mut y = py + POINTER_OFFSET;
+        if y + h > screen.y + screen.h {
+            y = py - h;
+        }
+        let y = y.max(screen.y);
+        let rect = Rect::new(x, y, w, h);
+        let mut mb = MeshBuilder::new();
+        mb.rectangle(DrawMode::fill(), rect, graphics::WHITE);
+        mb.rectangle(DrawMode::stroke(1.), rect, graphics::BLACK);
+        let mesh = mb.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+        graphics::draw(
+            ctx,
+            &text,
+            (Point2::new(x + PADDING, y + PADDING), graphics::BLACK),
+        )
+    }
+}
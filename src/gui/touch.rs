@@ -0,0 +1,321 @@
+use crate::gui::{IActions, Widget};
+pub use ggez::event::winit_event::TouchPhase;
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+/// Point outside of any widget. Pointer is moved there when the finger is
+/// lifted, since touch has no hover, and a press is released there to
+/// cancel it without a click.
+pub const OUTSIDE: [f32; 2] = [-1_000_000., -1_000_000.];
+// Pan distance of one wheel step
+const WHEEL_STEP: f32 = 60.;
+// Kinetic scroll velocity decays by e in this time and stops below
+// the minimal speed, both in pixels per second
+const KINETIC_DECAY: f32 = 0.325;
+const KINETIC_MIN_SPEED: f32 = 20.;
+// Weight of the latest motion in the pan velocity estimate
+const VELOCITY_SMOOTHING: f32 = 0.5;
+
+/// Pointer events the touches are mapped to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchEvent {
+    /// Pointer moved without button, widgets update their hover state
+    Hover(f32, f32),
+    /// Left button pressed at the point
+    Down(f32, f32),
+    /// Pointer moved with the button pressed
+    Move(f32, f32),
+    /// Left button released at the point
+    Up(f32, f32),
+    /// Wheel scrolled by whole steps over the point
+    Wheel { x: f32, y: f32, dx: f32, dy: f32 },
+}
+
+pub type PinchHandler<'a> = Rc<dyn Fn(f32, f32, f32) + 'a>;
+
+struct Finger {
+    id: u64,
+    pos: [f32; 2],
+}
+
+struct PinchEntry<'a> {
+    widget: Weak<RefCell<dyn Widget<'a> + 'a>>,
+    handler: PinchHandler<'a>,
+}
+
+/// Maps touches to pointer events. The first finger acts as the mouse with
+/// the left button pressed, so taps click buttons and checkboxes. A second
+/// finger cancels the press and starts a two-finger gesture: panning
+/// scrolls the widget under the fingers by wheel steps and keeps scrolling
+/// with decaying speed after the fingers are lifted, pinching calls the
+/// pinch handler of the innermost widget under the fingers.
+///
+/// `WindowManager` owns it and dispatches the returned events. ggez doesn't
+/// deliver touch events, they are passed to it by `IEvents::touch_event`
+/// from a custom event loop or a test. Time is passed explicitly.
+pub struct Touch<'a> {
+    fingers: Vec<Finger>,
+    // Set once the second finger lands, until all fingers are lifted
+    gesture: bool,
+    last_time: Duration,
+    velocity: [f32; 2],
+    // Set by a pan move and cleared by `update`
+    moved: bool,
+    kinetic: bool,
+    // Pan distance not yet sent as whole wheel steps
+    scroll: [f32; 2],
+    center: [f32; 2],
+    entries: Vec<PinchEntry<'a>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+}
+
+impl<'a> Touch<'a> {
+    pub fn new() -> Self {
+        Self {
+            fingers: Vec::new(),
+            gesture: false,
+            last_time: Duration::from_secs(0),
+            velocity: [0., 0.],
+            moved: false,
+            kinetic: false,
+            scroll: [0., 0.],
+            center: [0., 0.],
+            entries: Vec::new(),
+            pending_handlers: Vec::new(),
+        }
+    }
+
+    /// Handler gets the scale change since the previous call and the center
+    /// between the fingers. Replaces the handler if the widget already has one.
+    pub fn set_pinch_handler(
+        &mut self,
+        widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
+        handler: impl Fn(f32, f32, f32) + 'a,
+    ) {
+        let handler: PinchHandler<'a> = Rc::new(handler);
+        match self.find(&widget) {
+            Some(index) => self.entries[index].handler = handler,
+            None => self.entries.push(PinchEntry {
+                widget: Rc::downgrade(&widget),
+                handler,
+            }),
+        }
+    }
+
+    pub fn remove_pinch_handler(&mut self, widget: Rc<RefCell<dyn Widget<'a> + 'a>>) {
+        if let Some(index) = self.find(&widget) {
+            self.entries.remove(index);
+        }
+    }
+
+    fn find(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.widget.as_ptr() as *const () == Rc::as_ptr(widget) as *const ())
+    }
+
+    pub fn touch_count(&self) -> usize {
+        self.fingers.len()
+    }
+
+    pub fn is_scrolling(&self) -> bool {
+        self.kinetic
+    }
+
+    /// Stops kinetic scrolling
+    pub fn stop(&mut self) {
+        self.kinetic = false;
+        self.velocity = [0., 0.];
+        self.scroll = [0., 0.];
+    }
+
+    pub fn touch(
+        &mut self,
+        id: u64,
+        phase: TouchPhase,
+        x: f32,
+        y: f32,
+        now: Duration,
+    ) -> Vec<TouchEvent> {
+        let index = self.fingers.iter().position(|f| f.id == id);
+        match (phase, index) {
+            (TouchPhase::Started, None) => self.started(id, x, y, now),
+            (TouchPhase::Moved, Some(index)) => self.moved(index, x, y, now),
+            (TouchPhase::Ended, Some(index)) => self.ended(index, x, y, false),
+            (TouchPhase::Cancelled, Some(index)) => self.ended(index, x, y, true),
+            _ => Vec::new(),
+        }
+    }
+
+    fn centroid(&self) -> [f32; 2] {
+        let n = self.fingers.len().min(2) as f32;
+        let (sx, sy) = self
+            .fingers
+            .iter()
+            .take(2)
+            .fold((0., 0.), |(sx, sy), f| (sx + f.pos[0], sy + f.pos[1]));
+        [sx / n, sy / n]
+    }
+
+    fn spread(&self) -> f32 {
+        match self.fingers.as_slice() {
+            [a, b, ..] => ((a.pos[0] - b.pos[0]).powi(2) + (a.pos[1] - b.pos[1]).powi(2)).sqrt(),
+            _ => 0.,
+        }
+    }
+
+    fn started(&mut self, id: u64, x: f32, y: f32, now: Duration) -> Vec<TouchEvent> {
+        self.stop();
+        self.fingers.push(Finger { id, pos: [x, y] });
+        self.last_time = now;
+        match self.fingers.len() {
+            1 => vec![TouchEvent::Hover(x, y), TouchEvent::Down(x, y)],
+            2 if !self.gesture => {
+                self.gesture = true;
+                self.center = self.centroid();
+                let [ox, oy] = OUTSIDE;
+                let [cx, cy] = self.center;
+                vec![
+                    TouchEvent::Move(ox, oy),
+                    TouchEvent::Up(ox, oy),
+                    TouchEvent::Hover(cx, cy),
+                ]
+            }
+            _ => {
+                self.center = self.centroid();
+                Vec::new()
+            }
+        }
+    }
+
+    fn moved(&mut self, index: usize, x: f32, y: f32, now: Duration) -> Vec<TouchEvent> {
+        if !self.gesture {
+            self.fingers[index].pos = [x, y];
+            return vec![TouchEvent::Move(x, y)];
+        }
+        if index >= 2 {
+            self.fingers[index].pos = [x, y];
+            return Vec::new();
+        }
+        let spread = self.spread();
+        self.fingers[index].pos = [x, y];
+        let center = self.centroid();
+        let delta = [center[0] - self.center[0], center[1] - self.center[1]];
+        self.center = center;
+        self.moved = true;
+        let dt = now
+            .checked_sub(self.last_time)
+            .unwrap_or_default()
+            .as_secs_f32();
+        self.last_time = now;
+        if dt > 0. {
+            for (v, d) in self.velocity.iter_mut().zip(&delta) {
+                *v = *v * (1. - VELOCITY_SMOOTHING) + d / dt * VELOCITY_SMOOTHING;
+            }
+        }
+        let new_spread = self.spread();
+        if spread > 0. && new_spread > 0. && (new_spread - spread).abs() > f32::EPSILON {
+            self.pinch(new_spread / spread);
+        }
+        self.pan(delta)
+    }
+
+    fn ended(&mut self, index: usize, x: f32, y: f32, cancelled: bool) -> Vec<TouchEvent> {
+        self.fingers.remove(index);
+        if !self.gesture {
+            let [ox, oy] = OUTSIDE;
+            return if cancelled {
+                vec![TouchEvent::Move(ox, oy), TouchEvent::Up(ox, oy)]
+            } else {
+                vec![TouchEvent::Up(x, y), TouchEvent::Hover(ox, oy)]
+            };
+        }
+        if self.fingers.is_empty() {
+            self.gesture = false;
+            let speed = self.velocity[0].hypot(self.velocity[1]);
+            self.kinetic = !cancelled && speed >= KINETIC_MIN_SPEED;
+            if !self.kinetic {
+                self.stop();
+            }
+        } else {
+            // Remaining finger continues the gesture from its own position
+            self.center = self.centroid();
+        }
+        Vec::new()
+    }
+
+    fn pinch(&mut self, scale: f32) {
+        let [cx, cy] = self.center;
+        let area = |r: Rect| r.w * r.h;
+        self.entries.retain(|e| e.widget.strong_count() > 0);
+        let handler = self
+            .entries
+            .iter()
+            .filter_map(|e| e.widget.upgrade().map(|w| (w.borrow().get_rect(), e)))
+            .filter(|(rect, _)| rect.contains([cx, cy]))
+            .min_by(|(a, _), (b, _)| area(*a).total_cmp(&area(*b)))
+            .map(|(_, e)| e.handler.clone());
+        if let Some(handler) = handler {
+            self.pending_handlers
+                .push(Rc::new(move || handler(scale, cx, cy)));
+        }
+    }
+
+    // Accumulates the pan and returns it as whole wheel steps. Moving the
+    // fingers down scrolls the content up, the same as wheel up.
+    fn pan(&mut self, delta: [f32; 2]) -> Vec<TouchEvent> {
+        self.scroll[0] += delta[0];
+        self.scroll[1] += delta[1];
+        let steps = [
+            (self.scroll[0] / WHEEL_STEP).trunc(),
+            (self.scroll[1] / WHEEL_STEP).trunc(),
+        ];
+        if steps == [0., 0.] {
+            return Vec::new();
+        }
+        self.scroll[0] -= steps[0] * WHEEL_STEP;
+        self.scroll[1] -= steps[1] * WHEEL_STEP;
+        let [x, y] = self.center;
+        vec![TouchEvent::Wheel {
+            x,
+            y,
+            dx: steps[0],
+            dy: steps[1],
+        }]
+    }
+
+    /// Continues kinetic scrolling after a pan
+    pub fn update(&mut self, now: Duration) -> Vec<TouchEvent> {
+        // Fingers held still for a frame leave nothing to continue with
+        if self.gesture && !std::mem::replace(&mut self.moved, false) {
+            self.velocity = [0., 0.];
+        }
+        // Time of the last move is kept for the velocity, which is
+        // measured between moves and not between frames
+        if !self.kinetic {
+            return Vec::new();
+        }
+        let dt = now
+            .checked_sub(self.last_time)
+            .unwrap_or_default()
+            .as_secs_f32();
+        self.last_time = now;
+        let delta = [self.velocity[0] * dt, self.velocity[1] * dt];
+        let decay = (-dt / KINETIC_DECAY).exp();
+        self.velocity[0] *= decay;
+        self.velocity[1] *= decay;
+        let events = self.pan(delta);
+        if self.velocity[0].hypot(self.velocity[1]) < KINETIC_MIN_SPEED {
+            self.stop();
+        }
+        events
+    }
+}
+
+impl<'a> IActions<'a> for Touch<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
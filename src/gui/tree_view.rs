@@ -0,0 +1,737 @@
+use crate::gui::gestures::GestureSettings;
+use crate::gui::painter::Painter;
+use crate::gui::scrollbar::ScrollBar;
+use crate::gui::HIGHLIGHT_COLOR;
+use crate::gui::{draw_label, is_same, IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Align, Rect};
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+/// Identifier of the node in `ITreeModel`. Its meaning is up to the model.
+pub type NodeId = u64;
+
+pub type NodeHandler<'a, T> = Rc<dyn Fn(Rc<RefCell<T>>, NodeId) + 'a>;
+
+/// Hierarchical data shown by `TreeView`. Children of the node are
+/// requested only when the node is expanded first time, so the model
+/// may load them lazily.
+pub trait ITreeModel {
+    /// Children of the node, top level nodes for `None`
+    fn children(&mut self, parent: Option<NodeId>) -> Vec<NodeId>;
+    /// Should the node have an expand toggle. Called before children are loaded.
+    fn has_children(&self, node: NodeId) -> bool;
+    fn node_label(&self, node: NodeId) -> String;
+    /// Draws the node inside its row, to the right of the expand toggle
+    fn draw_node(&self, ctx: &mut Context, node: NodeId, rect: Rect) -> GameResult {
+        draw_label(ctx, rect, &self.node_label(node), Align::Left)
+    }
+    /// Records the node for `paint_into`. Models which override `draw_node`
+    /// should record the same drawing here.
+    fn paint_node(&self, painter: &mut Painter, node: NodeId, rect: Rect) {
+        painter.text(rect, self.node_label(node), Align::Left, graphics::BLACK);
+    }
+}
+
+struct Node {
+    id: NodeId,
+    has_children: bool,
+    expanded: bool,
+    children: Option<Vec<Node>>,
+}
+
+impl Node {
+    fn new(model: &dyn ITreeModel, id: NodeId) -> Self {
+        Self {
+            id,
+            has_children: model.has_children(id),
+            expanded: false,
+            children: None,
+        }
+    }
+
+    fn find_path(nodes: &[Node], id: NodeId, path: &mut Vec<usize>) -> bool {
+        for (n, node) in nodes.iter().enumerate() {
+            path.push(n);
+            if node.id == id {
+                return true;
+            }
+            if let Some(children) = &node.children {
+                if Self::find_path(children, id, path) {
+                    return true;
+                }
+            }
+            path.pop();
+        }
+        false
+    }
+}
+
+// Visible line of the tree
+struct Row {
+    path: Vec<usize>,
+    id: NodeId,
+    has_children: bool,
+    expanded: bool,
+}
+
+impl Row {
+    fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+pub struct TreeView<'a> {
+    model: Rc<RefCell<dyn ITreeModel + 'a>>,
+    nodes: Vec<Node>,
+    rows: Vec<Row>,
+    selected: Option<NodeId>,
+    scrollbar: ScrollBar,
+    row_height: f32,
+    indent: f32,
+    rect: Rect,
+    focused: bool,
+    hovered: bool,
+    last_click: Option<(NodeId, Duration)>,
+    double_click_time: Duration,
+    on_expanded_handlers: Vec<NodeHandler<'a, Self>>,
+    on_collapsed_handlers: Vec<NodeHandler<'a, Self>>,
+    on_selected_handlers: Vec<NodeHandler<'a, Self>>,
+    on_activated_handlers: Vec<NodeHandler<'a, Self>>,
+    pending_handlers: Vec<Rc<dyn Fn() + 'a>>,
+    rcself: Option<Weak<RefCell<Self>>>,
+}
+
+struct EmptyModel;
+
+impl ITreeModel for EmptyModel {
+    fn children(&mut self, _parent: Option<NodeId>) -> Vec<NodeId> {
+        Vec::new()
+    }
+    fn has_children(&self, _node: NodeId) -> bool {
+        false
+    }
+    fn node_label(&self, _node: NodeId) -> String {
+        String::new()
+    }
+}
+
+impl<'a> TreeView<'a> {
+    fn new() -> Self {
+        Self {
+            model: Rc::new(RefCell::new(EmptyModel)),
+            nodes: Vec::new(),
+            rows: Vec::new(),
+            selected: None,
+            scrollbar: ScrollBar::new(),
+            row_height: 30.,
+            indent: 20.,
+            rect: Rect::zero(),
+            focused: false,
+            hovered: false,
+            last_click: None,
+            double_click_time: GestureSettings::default().double_click_time,
+            on_expanded_handlers: Vec::new(),
+            on_collapsed_handlers: Vec::new(),
+            on_selected_handlers: Vec::new(),
+            on_activated_handlers: Vec::new(),
+            pending_handlers: Vec::new(),
+            rcself: None,
+        }
+    }
+
+    fn rcself(&self) -> Rc<RefCell<Self>> {
+        self.rcself.as_ref().unwrap().upgrade().unwrap()
+    }
+
+    pub fn on_expanded(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, NodeId) + 'a,
+    ) -> NodeHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_expanded_handlers.push(rc.clone());
+        rc
+    }
+    pub fn on_collapsed(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, NodeId) + 'a,
+    ) -> NodeHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_collapsed_handlers.push(rc.clone());
+        rc
+    }
+    pub fn on_selected(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, NodeId) + 'a,
+    ) -> NodeHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_selected_handlers.push(rc.clone());
+        rc
+    }
+    pub fn on_activated(
+        &mut self,
+        handler: impl Fn(Rc<RefCell<Self>>, NodeId) + 'a,
+    ) -> NodeHandler<'a, Self> {
+        let rc = Rc::new(handler);
+        self.on_activated_handlers.push(rc.clone());
+        rc
+    }
+    pub fn remove_handler<T: ?Sized>(&mut self, handler: Rc<T>) {
+        for handlers in [
+            &mut self.on_expanded_handlers,
+            &mut self.on_collapsed_handlers,
+            &mut self.on_selected_handlers,
+            &mut self.on_activated_handlers,
+        ]
+        .iter_mut()
+        {
+            handlers.drain_filter(|h| is_same(h, &handler)).count();
+        }
+    }
+
+    fn fire(&mut self, handlers: Vec<NodeHandler<'a, Self>>, node: NodeId) {
+        for h in handlers {
+            let rcself = self.rcself();
+            self.pending_handlers
+                .push(Rc::new(move || h(rcself.clone(), node)));
+        }
+    }
+
+    pub fn model(&self) -> Rc<RefCell<dyn ITreeModel + 'a>> {
+        self.model.clone()
+    }
+
+    pub fn set_model(&mut self, model: Rc<RefCell<dyn ITreeModel + 'a>>) {
+        self.model = model;
+        self.reload();
+    }
+
+    /// Rereads top level nodes from the model. All nodes become collapsed.
+    pub fn reload(&mut self) {
+        let mut model = self.model.borrow_mut();
+        self.nodes = model
+            .children(None)
+            .into_iter()
+            .map(|id| Node::new(&*model, id))
+            .collect();
+        drop(model);
+        self.selected = None;
+        self.scrollbar.set_first(0);
+        self.update_rows();
+    }
+
+    /// Forgets loaded children of the node. If the node is expanded they
+    /// are requested from the model again immediately.
+    pub fn refresh(&mut self, node: NodeId) {
+        if let Some(path) = self.find(node) {
+            let model = self.model.clone();
+            let n = self.node_mut(&path);
+            n.has_children = model.borrow().has_children(node);
+            n.children = None;
+            if n.expanded {
+                n.expanded = false;
+                self.expand_path(&path);
+            }
+            if let Some(selected) = self.selected {
+                if self.find(selected).is_none() {
+                    self.selected = Some(node);
+                }
+            }
+            self.update_rows();
+        }
+    }
+
+    pub fn get_selected(&self) -> Option<NodeId> {
+        self.selected
+    }
+
+    /// Selects the node if it's loaded, without firing handlers
+    pub fn set_selected(&mut self, node: Option<NodeId>) {
+        self.selected = node.filter(|n| self.find(*n).is_some());
+    }
+
+    pub fn is_expanded(&self, node: NodeId) -> bool {
+        match self.find(node) {
+            Some(path) => self.node(&path).expanded,
+            None => false,
+        }
+    }
+
+    /// Expands or collapses the loaded node without firing `on_expanded`
+    /// or `on_collapsed`. If the selected node gets hidden, the selection
+    /// moves to the collapsed node and `on_selected` fires, as it does on
+    /// collapse by the user.
+    pub fn set_expanded(&mut self, node: NodeId, expanded: bool) {
+        if let Some(path) = self.find(node) {
+            if expanded {
+                self.expand_path(&path);
+            } else {
+                self.node_mut(&path).expanded = false;
+            }
+            self.update_rows();
+        }
+    }
+
+    pub fn set_row_height(&mut self, row_height: f32) -> &mut Self {
+        self.row_height = row_height;
+        self.layout();
+        self
+    }
+
+    pub fn set_indent(&mut self, indent: f32) -> &mut Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn get_double_click_time(&self) -> Duration {
+        self.double_click_time
+    }
+
+    /// Second click on the same node faster than this activates it. The
+    /// default is the one of `GestureSettings`.
+    pub fn set_double_click_time(&mut self, double_click_time: Duration) -> &mut Self {
+        self.double_click_time = double_click_time;
+        self
+    }
+
+    fn find(&self, id: NodeId) -> Option<Vec<usize>> {
+        let mut path = Vec::new();
+        if Node::find_path(&self.nodes, id, &mut path) {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    fn node(&self, path: &[usize]) -> &Node {
+        let mut node = &self.nodes[path[0]];
+        for n in &path[1..] {
+            node = &node.children.as_ref().unwrap()[*n];
+        }
+        node
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> &mut Node {
+        let mut node = &mut self.nodes[path[0]];
+        for n in &path[1..] {
+            node = &mut node.children.as_mut().unwrap()[*n];
+        }
+        node
+    }
+
+    // Loads children on first expand. Returns false if there is nothing to expand.
+    fn expand_path(&mut self, path: &[usize]) -> bool {
+        let model = self.model.clone();
+        let node = self.node_mut(path);
+        if !node.has_children || node.expanded {
+            return false;
+        }
+        if node.children.is_none() {
+            let mut model = model.borrow_mut();
+            let children = model
+                .children(Some(node.id))
+                .into_iter()
+                .map(|id| Node::new(&*model, id))
+                .collect();
+            node.children = Some(children);
+        }
+        node.expanded = true;
+        true
+    }
+
+    fn update_rows(&mut self) {
+        fn add_rows(nodes: &[Node], path: &mut Vec<usize>, rows: &mut Vec<Row>) {
+            for (n, node) in nodes.iter().enumerate() {
+                path.push(n);
+                rows.push(Row {
+                    path: path.clone(),
+                    id: node.id,
+                    has_children: node.has_children,
+                    expanded: node.expanded,
+                });
+                if node.expanded {
+                    if let Some(children) = &node.children {
+                        add_rows(children, path, rows);
+                    }
+                }
+                path.pop();
+            }
+        }
+        let mut rows = Vec::new();
+        add_rows(&self.nodes, &mut Vec::new(), &mut rows);
+        self.rows = rows;
+        // Selected node could be hidden inside collapsed parent
+        if let Some(selected) = self.selected {
+            if self.row_of(selected).is_none() {
+                self.selected = self
+                    .find(selected)
+                    .and_then(|path| self.rows.iter().rev().find(|r| path.starts_with(&r.path)))
+                    .map(|r| r.id);
+                if let Some(ancestor) = self.selected {
+                    self.fire(self.on_selected_handlers.clone(), ancestor);
+                }
+            }
+        }
+        self.layout();
+    }
+
+    fn row_of(&self, node: NodeId) -> Option<usize> {
+        self.rows.iter().position(|r| r.id == node)
+    }
+
+    fn layout(&mut self) {
+        let visible = if self.row_height > 0. {
+            (self.rect.h / self.row_height) as usize
+        } else {
+            0
+        };
+        self.scrollbar.set_range(self.rows.len(), visible);
+        self.scrollbar.set_rect(Rect::new(
+            self.rect.x + self.rect.w - ScrollBar::WIDTH,
+            self.rect.y,
+            ScrollBar::WIDTH,
+            self.rect.h,
+        ));
+    }
+
+    // Part of the row right of the expand toggle, where the model draws
+    fn node_rect(&self, row: usize) -> Rect {
+        let mut rect = self.row_rect(row);
+        let dx = (self.rows[row].depth() + 1) as f32 * self.indent;
+        rect.x += dx;
+        rect.w -= dx;
+        rect
+    }
+
+    // Frame, row backgrounds, expand toggles and the scrollbar, the model
+    // draws the nodes
+    fn paint_rows(&self, painter: &mut Painter) {
+        painter.stroke_rect(self.rect, 1., graphics::WHITE);
+        for row in self.scrollbar.rows() {
+            let rect = self.row_rect(row);
+            let selected = self.selected == Some(self.rows[row].id);
+            painter.fill_rect(
+                rect,
+                if selected {
+                    HIGHLIGHT_COLOR
+                } else {
+                    graphics::WHITE
+                },
+            );
+            if selected && self.focused {
+                painter.stroke_rect(rect, 2., graphics::BLACK);
+            }
+            if self.rows[row].has_children {
+                let t = self.toggle_rect(row);
+                let s = (t.w.min(t.h) / 3.).max(1.);
+                let (cx, cy) = (t.x + t.w / 2., t.y + t.h / 2.);
+                let points = if self.rows[row].expanded {
+                    [
+                        [cx - s, cy - s / 2.],
+                        [cx + s, cy - s / 2.],
+                        [cx, cy + s / 2.],
+                    ]
+                } else {
+                    [
+                        [cx - s / 2., cy - s],
+                        [cx + s / 2., cy],
+                        [cx - s / 2., cy + s],
+                    ]
+                };
+                painter.fill_polygon(&points, graphics::BLACK);
+            }
+        }
+        self.scrollbar.paint(painter);
+    }
+
+    fn row_rect(&self, row: usize) -> Rect {
+        let mut rect = self.rect;
+        if self.scrollbar.is_needed() {
+            rect.w -= ScrollBar::WIDTH;
+        }
+        let n = row - self.scrollbar.first();
+        Rect::new(
+            rect.x,
+            rect.y + n as f32 * self.row_height,
+            rect.w,
+            self.row_height,
+        )
+    }
+
+    fn toggle_rect(&self, row: usize) -> Rect {
+        let rect = self.row_rect(row);
+        let x = rect.x + self.rows[row].depth() as f32 * self.indent;
+        Rect::new(x, rect.y, self.indent, rect.h)
+    }
+
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        let rows = self.scrollbar.rows();
+        rows.clone().find(|r| self.row_rect(*r).contains([x, y]))
+    }
+
+    fn select(&mut self, row: usize) {
+        let node = self.rows[row].id;
+        self.scrollbar.scroll_to(row);
+        if self.selected != Some(node) {
+            self.selected = Some(node);
+            self.fire(self.on_selected_handlers.clone(), node);
+        }
+    }
+
+    fn expand(&mut self, row: usize) {
+        let path = self.rows[row].path.clone();
+        if self.expand_path(&path) {
+            self.update_rows();
+            self.fire(self.on_expanded_handlers.clone(), self.rows[row].id);
+        }
+    }
+
+    fn collapse(&mut self, row: usize) {
+        if self.rows[row].expanded {
+            let path = self.rows[row].path.clone();
+            self.node_mut(&path).expanded = false;
+            self.update_rows();
+            self.fire(self.on_collapsed_handlers.clone(), self.rows[row].id);
+        }
+    }
+
+    fn toggle(&mut self, row: usize) {
+        if self.rows[row].expanded {
+            self.collapse(row)
+        } else {
+            self.expand(row)
+        }
+    }
+
+    fn activate(&mut self, row: usize) {
+        self.fire(self.on_activated_handlers.clone(), self.rows[row].id);
+    }
+
+    fn step(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let last = self.rows.len() as isize - 1;
+        let row = match self.selected.and_then(|s| self.row_of(s)) {
+            Some(current) => (current as isize + delta).max(0).min(last),
+            None => 0,
+        };
+        self.select(row as usize);
+    }
+
+    fn key_down(&mut self, keycode: KeyCode) {
+        let page = self.scrollbar.visible().max(1) as isize;
+        let count = self.rows.len() as isize;
+        let current = self.selected.and_then(|s| self.row_of(s));
+        match (keycode, current) {
+            (KeyCode::Up, _) => self.step(-1),
+            (KeyCode::Down, _) => self.step(1),
+            (KeyCode::PageUp, _) => self.step(-page),
+            (KeyCode::PageDown, _) => self.step(page),
+            (KeyCode::Home, _) => self.step(-count),
+            (KeyCode::End, _) => self.step(count),
+            (KeyCode::Left, Some(row)) => {
+                if self.rows[row].expanded {
+                    self.collapse(row)
+                } else if self.rows[row].depth() > 0 {
+                    let parent = &self.rows[row].path[..self.rows[row].depth()];
+                    if let Some(parent_row) = self.rows.iter().position(|r| r.path == parent) {
+                        self.select(parent_row)
+                    }
+                }
+            }
+            (KeyCode::Right, Some(row)) => {
+                if !self.rows[row].expanded {
+                    self.expand(row)
+                } else if row + 1 < self.rows.len()
+                    && self.rows[row + 1].depth() > self.rows[row].depth()
+                {
+                    self.select(row + 1)
+                }
+            }
+            (KeyCode::Space, Some(row)) => self.toggle(row),
+            (KeyCode::Return, Some(row)) | (KeyCode::NumpadEnter, Some(row)) => self.activate(row),
+            _ => {}
+        }
+    }
+
+    fn click(&mut self, x: f32, y: f32, now: Duration) {
+        if let Some(row) = self.row_at(x, y) {
+            let node = self.rows[row].id;
+            if self.toggle_rect(row).contains([x, y]) {
+                self.toggle(row);
+                self.last_click = None;
+                return;
+            }
+            self.select(row);
+            match self.last_click {
+                Some((last, time))
+                    if last == node
+                        && now.checked_sub(time).unwrap_or_default() < self.double_click_time =>
+                {
+                    self.activate(row);
+                    self.last_click = None;
+                }
+                _ => self.last_click = Some((node, now)),
+            }
+        }
+    }
+}
+
+impl IEvents for TreeView<'_> {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        let mut painter = Painter::new([0., 0.]);
+        self.paint_rows(&mut painter);
+        painter.render(ctx)?;
+        let model = self.model.borrow();
+        for row in self.scrollbar.rows() {
+            model.draw_node(ctx, self.rows[row].id, self.node_rect(row))?;
+        }
+        Ok(())
+    }
+
+    fn paint_into(&mut self, _ctx: &mut dyn IContext, painter: &mut Painter) {
+        self.paint_rows(painter);
+        let model = self.model.borrow();
+        for row in self.scrollbar.rows() {
+            model.paint_node(painter, self.rows[row].id, self.node_rect(row));
+        }
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        if !self.rect.contains([x, y]) {
+            self.focused = false;
+            return;
+        }
+        self.focused = true;
+        if button == MouseButton::Left && !self.scrollbar.mouse_down(x, y) {
+            self.click(x, y, ctx.time_since_start())
+        }
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        self.scrollbar.mouse_up();
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut dyn IContext, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.hovered = self.rect.contains([x, y]);
+        self.scrollbar.mouse_motion(y);
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, y: f32) {
+        if self.hovered {
+            self.scrollbar.scroll_by((-y * 3.) as isize);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.focused {
+            self.key_down(keycode)
+        }
+    }
+}
+
+impl ILayout for TreeView<'_> {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.layout();
+    }
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for TreeView<'a> {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        self.pending_handlers.drain(..).collect()
+    }
+}
+
+impl<'a> IOverlays<'a> for TreeView<'a> {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+pub struct Builder<'a> {
+    tree_view: TreeView<'a>,
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Self {
+            tree_view: TreeView::new(),
+        }
+    }
+
+    pub fn build(self) -> Rc<RefCell<TreeView<'a>>> {
+        let rc = Rc::new(RefCell::new(self.tree_view));
+        rc.borrow_mut().rcself = Some(Rc::downgrade(&rc));
+        rc
+    }
+
+    pub fn set_model(mut self, model: Rc<RefCell<dyn ITreeModel + 'a>>) -> Self {
+        self.tree_view.set_model(model);
+        self
+    }
+
+    pub fn set_row_height(mut self, row_height: f32) -> Self {
+        self.tree_view.set_row_height(row_height);
+        self
+    }
+
+    pub fn set_indent(mut self, indent: f32) -> Self {
+        self.tree_view.set_indent(indent);
+        self
+    }
+
+    pub fn on_expanded(mut self, handler: impl Fn(Rc<RefCell<TreeView<'a>>>, NodeId) + 'a) -> Self {
+        self.tree_view.on_expanded(handler);
+        self
+    }
+
+    pub fn on_collapsed(
+        mut self,
+        handler: impl Fn(Rc<RefCell<TreeView<'a>>>, NodeId) + 'a,
+    ) -> Self {
+        self.tree_view.on_collapsed(handler);
+        self
+    }
+
+    pub fn on_selected(mut self, handler: impl Fn(Rc<RefCell<TreeView<'a>>>, NodeId) + 'a) -> Self {
+        self.tree_view.on_selected(handler);
+        self
+    }
+
+    pub fn on_activated(
+        mut self,
+        handler: impl Fn(Rc<RefCell<TreeView<'a>>>, NodeId) + 'a,
+    ) -> Self {
+        self.tree_view.on_activated(handler);
+        self
+    }
+}
@@ -1,10 +1,21 @@
-use crate::gui::{ILayout, Widget};
-use ggez::event::EventHandler;
-use ggez::graphics::Rect;
+use crate::gui::button::{TBackend, TFrontend};
+use crate::gui::capture::{CaptureRequest, CaptureRequests, PointerCapture};
+use crate::gui::drag_drop::{DragDrop, DropTarget};
+use crate::gui::menu::Menu;
+use crate::gui::navigation::{self, Direction, Navigation};
+use crate::gui::painter::Painter;
+use crate::gui::shortcuts::{label_mnemonic, Conflict, KeyChord, Scope, ShortcutId, Shortcuts};
+use crate::gui::toasts::{Level, ToastId, Toasts};
+use crate::gui::tooltips::Tooltips;
+use crate::gui::touch::{Touch, TouchEvent, TouchPhase, OUTSIDE};
+use crate::gui::{collect_hovered_child, IActions, IContext, IEvents, ILayout, Widget};
+use ggez::event::{Axis, Button, EventHandler, GamepadId, KeyCode, KeyMods};
+use ggez::graphics::{self, Rect};
 use ggez::input::mouse::MouseButton;
 use ggez::{Context, GameResult};
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
 
 struct Window<'a> {
     widget: Rc<RefCell<dyn Widget<'a> + 'a>>,
@@ -14,6 +25,24 @@ struct Window<'a> {
 
 pub struct WindowManager<'a> {
     windows: Vec<Window<'a>>,
+    overlays: Vec<Rc<RefCell<dyn Widget<'a> + 'a>>>,
+    tooltips: Tooltips<'a>,
+    toasts: Toasts<'a>,
+    drag_drop: Rc<RefCell<DragDrop<'a>>>,
+    shortcuts: Shortcuts<'a>,
+    navigation: Navigation<'a>,
+    touch: Touch<'a>,
+    // Windows under the mouse press and the widget which took the pointer
+    // by `capture_pointer`
+    capture: PointerCapture,
+    captured: Option<Weak<RefCell<dyn Widget<'a> + 'a>>>,
+    capture_requests: Rc<RefCell<CaptureRequests<'a>>>,
+    keyboard_navigation: bool,
+    // Point of the last mouse press or the center of the widget focused
+    // by navigation, widgets under it have focus
+    focus: Option<[f32; 2]>,
+    // Last pointer position, wheel events scroll what is under it
+    pointer: [f32; 2],
     rect: Rect,
 }
 
@@ -21,6 +50,19 @@ impl<'a> WindowManager<'a> {
     pub fn new() -> Self {
         Self {
             windows: Vec::new(),
+            overlays: Vec::new(),
+            tooltips: Tooltips::new(),
+            toasts: Toasts::new(),
+            drag_drop: Rc::new(RefCell::new(DragDrop::new())),
+            shortcuts: Shortcuts::new(),
+            navigation: Navigation::new(),
+            touch: Touch::new(),
+            capture: PointerCapture::new(),
+            captured: None,
+            capture_requests: Rc::new(RefCell::new(CaptureRequests::new())),
+            keyboard_navigation: false,
+            focus: None,
+            pointer: [0., 0.],
             rect: Rect::zero(),
         }
     }
@@ -41,11 +83,409 @@ impl<'a> WindowManager<'a> {
             full_screen,
         });
     }
+
+    /// Text shown near the pointer when it rests on the widget for
+    /// the tooltip delay
+    pub fn set_tooltip<S: Into<String>>(
+        &mut self,
+        widget: Rc<RefCell<impl Widget<'a> + 'a>>,
+        text: S,
+    ) {
+        self.tooltips.set_tooltip(widget, text)
+    }
+
+    pub fn remove_tooltip(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        self.tooltips.remove_tooltip(widget)
+    }
+
+    pub fn set_tooltip_delay(&mut self, delay: Duration) {
+        self.tooltips.set_delay(delay)
+    }
+
+    pub fn tooltips(&self) -> &Tooltips<'a> {
+        &self.tooltips
+    }
+
+    /// Shows a transient notification in the screen corner
+    pub fn notify<S: Into<String>>(
+        &mut self,
+        text: S,
+        level: Level,
+        duration: Duration,
+    ) -> ToastId {
+        self.toasts.notify(text, level, duration)
+    }
+
+    /// Adds button which dismisses the notification and calls the handler
+    pub fn add_notification_action<S: Into<String>>(
+        &mut self,
+        id: ToastId,
+        label: S,
+        handler: impl Fn(ToastId) + 'a,
+    ) {
+        self.toasts.add_action(id, label, handler)
+    }
+
+    pub fn dismiss_notification(&mut self, id: ToastId) {
+        self.toasts.dismiss(id)
+    }
+
+    pub fn toasts(&self) -> &Toasts<'a> {
+        &self.toasts
+    }
+
+    pub fn toasts_mut(&mut self) -> &mut Toasts<'a> {
+        &mut self.toasts
+    }
+
+    /// Shared drag and drop state. Widgets keep the handle to start drags.
+    pub fn drag_drop(&self) -> Rc<RefCell<DragDrop<'a>>> {
+        self.drag_drop.clone()
+    }
+
+    /// Callbacks called when a drag passes over the widget or is dropped on it
+    pub fn set_drop_target(
+        &mut self,
+        widget: Rc<RefCell<impl Widget<'a> + 'a>>,
+        target: DropTarget<'a>,
+    ) {
+        self.drag_drop.borrow_mut().set_drop_target(widget, target)
+    }
+
+    pub fn remove_drop_target(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        self.drag_drop.borrow_mut().remove_drop_target(widget)
+    }
+
+    /// Runs the action when the chord is pressed in the scope. Fails if the
+    /// chord is already taken in the same scope.
+    pub fn add_shortcut(
+        &mut self,
+        chord: KeyChord,
+        scope: Scope<'a>,
+        action: impl Fn() + 'a,
+    ) -> Result<ShortcutId, Conflict> {
+        self.shortcuts.add(chord, scope, action)
+    }
+
+    pub fn remove_shortcut(&mut self, id: ShortcutId) {
+        self.shortcuts.remove(id)
+    }
+
+    /// Registers Alt with the letter marked by '&' in the button label as
+    /// a shortcut which clicks the button. Returns `None` if the label has
+    /// no mnemonic; a later change of the label isn't followed.
+    pub fn add_mnemonic<BE: TBackend<'a> + 'a>(
+        &mut self,
+        button: &Rc<RefCell<impl TFrontend<'a, BE>>>,
+        scope: Scope<'a>,
+    ) -> Result<Option<ShortcutId>, Conflict> {
+        let backend = button.borrow().backend();
+        let chord = match label_mnemonic(&backend.borrow().get_label()) {
+            Some(chord) => chord,
+            None => return Ok(None),
+        };
+        let backend = Rc::downgrade(&backend);
+        let action = move || {
+            if let Some(backend) = backend.upgrade() {
+                backend.borrow_mut().click();
+            }
+        };
+        self.shortcuts.add(chord, scope, action).map(Some)
+    }
+
+    /// Registers accelerators of the menu items, submenus included, as
+    /// shortcuts which activate the items. Disabled items are skipped when
+    /// the shortcut is pressed. Nothing is registered if one of the
+    /// accelerators conflicts.
+    pub fn add_accelerators(
+        &mut self,
+        menu: &Rc<RefCell<Menu<'a>>>,
+        scope: Scope<'a>,
+    ) -> Result<Vec<ShortcutId>, Conflict> {
+        let mut ids = Vec::new();
+        for (chord, menu, index) in menu.borrow().accelerators() {
+            let menu = Rc::downgrade(&menu);
+            let action = move || {
+                if let Some(menu) = menu.upgrade() {
+                    menu.borrow_mut().activate(index);
+                }
+            };
+            match self.shortcuts.add(chord, scope.clone(), action) {
+                Ok(id) => ids.push(id),
+                Err(conflict) => {
+                    for id in ids {
+                        self.shortcuts.remove(id);
+                    }
+                    return Err(conflict);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    pub fn shortcuts(&self) -> &Shortcuts<'a> {
+        &self.shortcuts
+    }
+
+    /// Widget which can get focus by the arrow keys or gamepad
+    pub fn add_focusable(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        self.navigation.add_focusable(widget)
+    }
+
+    pub fn remove_focusable(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        self.navigation.remove_focusable(widget)
+    }
+
+    pub fn set_focus(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        self.navigation.set_focus(widget);
+        self.focus_navigated();
+    }
+
+    /// Called when back is pressed with no popup open and no drag in progress
+    pub fn on_back(&mut self, handler: impl Fn() + 'a) -> Rc<dyn Fn() + 'a> {
+        self.navigation.on_back(handler)
+    }
+
+    /// Arrow keys move the focus between focusable widgets, Enter and Space
+    /// click the focused one. Off by default since widgets like lists use
+    /// arrow keys themselves. Gamepad always navigates.
+    pub fn set_keyboard_navigation(&mut self, enabled: bool) {
+        self.keyboard_navigation = enabled;
+    }
+
+    pub fn is_keyboard_navigation(&self) -> bool {
+        self.keyboard_navigation
+    }
+
+    pub fn navigation(&self) -> &Navigation<'a> {
+        &self.navigation
+    }
+
+    pub fn navigation_mut(&mut self) -> &mut Navigation<'a> {
+        &mut self.navigation
+    }
+
+    /// Handler gets the scale change and the center of a two-finger pinch
+    /// over the widget
+    pub fn set_pinch_handler(
+        &mut self,
+        widget: Rc<RefCell<impl Widget<'a> + 'a>>,
+        handler: impl Fn(f32, f32, f32) + 'a,
+    ) {
+        self.touch.set_pinch_handler(widget, handler)
+    }
+
+    pub fn remove_pinch_handler(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        self.touch.remove_pinch_handler(widget)
+    }
+
+    pub fn touch(&self) -> &Touch<'a> {
+        &self.touch
+    }
+
+    fn dispatch_touch(&mut self, ctx: &mut dyn IContext, events: Vec<TouchEvent>) {
+        for e in events {
+            match e {
+                TouchEvent::Hover(x, y) | TouchEvent::Move(x, y) => {
+                    IEvents::mouse_motion_event(self, ctx, x, y, 0., 0.)
+                }
+                TouchEvent::Down(x, y) => {
+                    IEvents::mouse_button_down_event(self, ctx, MouseButton::Left, x, y)
+                }
+                TouchEvent::Up(x, y) => {
+                    IEvents::mouse_button_up_event(self, ctx, MouseButton::Left, x, y)
+                }
+                TouchEvent::Wheel { x, y, dx, dy } => {
+                    IEvents::mouse_motion_event(self, ctx, x, y, 0., 0.);
+                    IEvents::mouse_wheel_event(self, ctx, dx, dy);
+                }
+            }
+        }
+    }
+
+    /// Sends pointer motion and the release to the widget alone until the
+    /// pressed button is released, even outside of its rect. Other windows
+    /// under the press get the release outside of any widget, so they don't
+    /// click.
+    pub fn capture_pointer(&mut self, widget: Rc<RefCell<impl Widget<'a> + 'a>>) {
+        let widget: Rc<RefCell<dyn Widget<'a> + 'a>> = widget;
+        self.captured = Some(Rc::downgrade(&widget));
+    }
+
+    /// Ends the capture without a click: the captured widgets get the
+    /// release outside of any widget
+    pub fn release_pointer(&mut self, ctx: &mut dyn IContext) {
+        let button = self.capture.button().unwrap_or(MouseButton::Left);
+        let [x, y] = OUTSIDE;
+        self.end_capture(ctx, button, x, y);
+    }
+
+    /// Shared handle for widgets to take the pointer and give it back
+    /// without access to the window manager
+    pub fn capture_requests(&self) -> Rc<RefCell<CaptureRequests<'a>>> {
+        self.capture_requests.clone()
+    }
+
+    fn apply_capture_requests(&mut self, ctx: &mut dyn IContext) {
+        let request = self.capture_requests.borrow_mut().take();
+        match request {
+            Some(CaptureRequest::Capture(widget)) => self.captured = Some(widget),
+            Some(CaptureRequest::Release) => self.release_pointer(ctx),
+            None => {}
+        }
+    }
+
+    // The captured widget gets the release once: through its window if it
+    // was under the press, so containers on the way end their captures too,
+    // or directly otherwise. Other windows under the press get the release
+    // outside of any widget.
+    fn end_capture(&mut self, ctx: &mut dyn IContext, button: MouseButton, x: f32, y: f32) {
+        let widget = self.captured.take().and_then(|w| w.upgrade());
+        let window = widget.as_ref().and_then(|w| self.captured_window(w));
+        if let (Some(widget), None) = (widget, window) {
+            widget.borrow_mut().mouse_button_up_event(ctx, button, x, y);
+        }
+        if self.capture.is_captured() {
+            for (index, w) in self.windows.iter().enumerate() {
+                if self.capture.routes_to(index) {
+                    let [x, y] = if window == Some(index) {
+                        [x, y]
+                    } else {
+                        OUTSIDE
+                    };
+                    w.widget
+                        .borrow_mut()
+                        .mouse_button_up_event(ctx, button, x, y);
+                }
+            }
+            self.capture.cancel();
+        }
+    }
+
+    // Window under the press which has the widget under the press point
+    fn captured_window(&self, widget: &Rc<RefCell<dyn Widget<'a> + 'a>>) -> Option<usize> {
+        let [x, y] = self.capture.point()?;
+        self.windows
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.capture.routes_to(*index))
+            .find(|(_, w)| {
+                let mut hovered = vec![w.widget.clone()];
+                w.widget.borrow().collect_hovered(x, y, &mut hovered);
+                hovered
+                    .iter()
+                    .any(|h| h.as_ptr() as *const () == widget.as_ptr() as *const ())
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Widget which took the pointer by `capture_pointer`
+    pub fn pointer_capture(&self) -> Option<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.captured.as_ref().and_then(|w| w.upgrade())
+    }
+
+    pub fn is_pointer_captured(&self) -> bool {
+        self.pointer_capture().is_some() || self.capture.is_captured()
+    }
+
+    fn focus_navigated(&mut self) {
+        if let Some(widget) = self.navigation.focused() {
+            let r = widget.borrow().get_rect();
+            self.focus = Some([r.x + r.w / 2., r.y + r.h / 2.]);
+        }
+    }
+
+    // Open popups take the navigation as keys, the same way as they take
+    // the keyboard
+    fn navigate(&mut self, ctx: &mut dyn IContext, direction: Direction) {
+        if self.overlays.is_empty() {
+            let windows = &self.windows;
+            // Widgets of hidden pages keep their rects, so a widget counts
+            // only if it gets the pointer at its center
+            self.navigation.navigate(direction, |widget| {
+                let r = widget.borrow().get_rect();
+                let (x, y) = (r.x + r.w / 2., r.y + r.h / 2.);
+                let mut hovered = Vec::new();
+                collect_hovered_child(windows.iter().map(|w| &w.widget), x, y, &mut hovered);
+                hovered
+                    .iter()
+                    .any(|h| h.as_ptr() as *const () == widget.as_ptr() as *const ())
+            });
+            self.focus_navigated();
+        } else {
+            self.send_key(ctx, direction.key(), KeyMods::NONE, false);
+        }
+    }
+
+    // Clicks the middle of the focused widget
+    fn confirm(&mut self, ctx: &mut dyn IContext) {
+        if !self.overlays.is_empty() {
+            self.send_key(ctx, KeyCode::Return, KeyMods::NONE, false);
+            return;
+        }
+        self.navigation.set_active(true);
+        if let Some(widget) = self.navigation.focused() {
+            let r = widget.borrow().get_rect();
+            let (x, y) = (r.x + r.w / 2., r.y + r.h / 2.);
+            self.focus = Some([x, y]);
+            let mut widget = widget.borrow_mut();
+            widget.mouse_button_down_event(ctx, MouseButton::Left, x, y);
+            widget.mouse_button_up_event(ctx, MouseButton::Left, x, y);
+        }
+    }
+
+    // Cancels the drag, closes the popup or fires `on_back`, the first
+    // of them which applies
+    fn back(&mut self, ctx: &mut dyn IContext) {
+        if self.drag_drop.borrow().is_dragging() {
+            self.drag_drop.borrow_mut().cancel_drag();
+        } else if !self.overlays.is_empty() {
+            self.send_key(ctx, KeyCode::Escape, KeyMods::NONE, false);
+        } else {
+            self.navigation.back();
+        }
+    }
+
+    fn send_key(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        for o in &self.overlays {
+            o.borrow_mut().key_down_event(ctx, keycode, keymods, repeat);
+        }
+        for w in &mut self.windows {
+            w.widget
+                .borrow_mut()
+                .key_down_event(ctx, keycode, keymods, repeat);
+        }
+    }
+
+    // Widgets which get the pointer: the topmost overlay or window under it
+    // and their descendants, outermost first
+    fn hovered(&self, x: f32, y: f32) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        let mut hovered = Vec::new();
+        let windows = self.windows.iter().map(|w| &w.widget);
+        if self.overlay_contains(x, y) {
+            collect_hovered_child(self.overlays.iter(), x, y, &mut hovered);
+        } else {
+            collect_hovered_child(windows, x, y, &mut hovered);
+        }
+        hovered
+    }
+
+    fn overlay_contains(&self, x: f32, y: f32) -> bool {
+        self.overlays
+            .iter()
+            .any(|o| o.borrow().get_rect().contains([x, y]))
+    }
 }
 
-impl EventHandler for WindowManager<'_> {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        let (width, height) = ggez::graphics::drawable_size(ctx);
+impl IEvents for WindowManager<'_> {
+    fn update(&mut self, ctx: &mut dyn IContext) -> GameResult {
+        let (width, height) = ctx.drawable_size();
         for w in &mut self.windows {
             if w.full_screen {
                 w.widget
@@ -55,10 +495,43 @@ impl EventHandler for WindowManager<'_> {
                 w.widget.borrow_mut().set_rect(w.rect.clone());
             }
             w.widget.borrow_mut().update(ctx)?;
-            for e in w.widget.borrow_mut().collect_fired() {
+            // Handlers may borrow the window widget
+            let fired = w.widget.borrow_mut().collect_fired();
+            for e in fired {
                 (*e)()
             }
         }
+        self.overlays = self
+            .windows
+            .iter()
+            .flat_map(|w| w.widget.borrow_mut().collect_overlays())
+            .collect();
+        let hovered = match self.tooltips.pointer() {
+            Some([x, y]) => self.hovered(x, y),
+            None => Vec::new(),
+        };
+        self.tooltips.update(ctx.time_since_start(), &hovered);
+        self.toasts
+            .update(ctx.time_since_start(), Rect::new(0., 0., width, height));
+        for e in self.toasts.collect_fired() {
+            (*e)()
+        }
+        for e in self.shortcuts.collect_fired() {
+            (*e)()
+        }
+        let events = self.touch.update(ctx.time_since_start());
+        self.dispatch_touch(ctx, events);
+        for e in self.touch.collect_fired() {
+            (*e)()
+        }
+        for e in self.navigation.collect_fired() {
+            (*e)()
+        }
+        let fired = self.drag_drop.borrow_mut().collect_fired();
+        for e in fired {
+            (*e)()
+        }
+        self.apply_capture_requests(ctx);
         Ok(())
     }
 
@@ -66,10 +539,55 @@ impl EventHandler for WindowManager<'_> {
         for w in &mut self.windows {
             w.widget.borrow_mut().draw(ctx)?
         }
-        Ok(())
+        for o in &self.overlays {
+            o.borrow_mut().draw(ctx)?
+        }
+        let (width, height) = graphics::drawable_size(ctx);
+        self.toasts.draw(ctx)?;
+        self.navigation.draw(ctx)?;
+        self.tooltips.draw(ctx, Rect::new(0., 0., width, height))?;
+        self.drag_drop.borrow().draw(ctx)
     }
 
-    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+    /// Records windows, overlays and toasts. Tooltips, the focus frame and
+    /// the drag image are drawn only by `draw`.
+    fn paint_into(&mut self, ctx: &mut dyn IContext, painter: &mut Painter) {
+        for w in &mut self.windows {
+            w.widget.borrow_mut().paint_into(ctx, painter)
+        }
+        for o in &self.overlays {
+            o.borrow_mut().paint_into(ctx, painter)
+        }
+        self.toasts.paint_into(painter);
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.apply_capture_requests(ctx);
+        self.tooltips.pointer_pressed();
+        self.focus = Some([x, y]);
+        self.pointer = [x, y];
+        self.navigation.set_active(false);
+        if self.toasts.pointer_pressed(x, y) {
+            return;
+        }
+        for o in &self.overlays {
+            o.borrow_mut().mouse_button_down_event(ctx, button, x, y);
+        }
+        if self.overlay_contains(x, y) {
+            return;
+        }
+        let rects: Vec<_> = self
+            .windows
+            .iter()
+            .map(|w| w.widget.borrow().get_rect())
+            .collect();
+        self.capture.press(button, x, y, rects);
         for w in &mut self.windows {
             w.widget
                 .borrow_mut()
@@ -77,13 +595,219 @@ impl EventHandler for WindowManager<'_> {
         }
     }
 
-    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.apply_capture_requests(ctx);
+        if button == MouseButton::Left {
+            let blocked = self.overlay_contains(x, y);
+            self.drag_drop.borrow_mut().pointer_released(x, y, blocked);
+        }
+        if let Some(widget) = self.pointer_capture() {
+            let held = self.capture.button();
+            if held.is_none() || held == Some(button) {
+                self.end_capture(ctx, button, x, y);
+            } else {
+                widget.borrow_mut().mouse_button_up_event(ctx, button, x, y);
+            }
+            return;
+        }
+        let on_toast = self.toasts.contains(x, y);
+        if !on_toast {
+            for o in &self.overlays {
+                o.borrow_mut().mouse_button_up_event(ctx, button, x, y);
+            }
+        }
+        // Windows under the press get the release wherever it is
+        if self.capture.is_captured() {
+            for (index, w) in self.windows.iter().enumerate() {
+                if self.capture.routes_to(index) {
+                    w.widget
+                        .borrow_mut()
+                        .mouse_button_up_event(ctx, button, x, y);
+                }
+            }
+            self.capture.release(button);
+            return;
+        }
+        if on_toast || self.overlay_contains(x, y) {
+            return;
+        }
         for w in &mut self.windows {
             w.widget
                 .borrow_mut()
                 .mouse_button_up_event(ctx, button, x, y);
         }
     }
+
+    fn mouse_motion_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32, dx: f32, dy: f32) {
+        self.apply_capture_requests(ctx);
+        self.tooltips.pointer_moved(x, y);
+        self.pointer = [x, y];
+        let blocked = self.overlay_contains(x, y);
+        self.drag_drop.borrow_mut().pointer_moved(x, y, blocked);
+        for o in &self.overlays {
+            o.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy);
+        }
+        if let Some(widget) = self.pointer_capture() {
+            widget.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy);
+            return;
+        }
+        for (index, w) in self.windows.iter().enumerate() {
+            if self.capture.routes_to(index) {
+                w.widget.borrow_mut().mouse_motion_event(ctx, x, y, dx, dy);
+            }
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut dyn IContext, x: f32, y: f32) {
+        let [px, py] = self.pointer;
+        // Only the topmost popup under the pointer scrolls
+        let hovered = self
+            .overlays
+            .iter()
+            .rev()
+            .find(|o| o.borrow().get_rect().contains([px, py]));
+        if let Some(o) = hovered {
+            o.borrow_mut().mouse_wheel_event(ctx, x, y);
+            return;
+        }
+        for w in &mut self.windows {
+            w.widget.borrow_mut().mouse_wheel_event(ctx, x, y);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        if keycode == KeyCode::Escape && self.drag_drop.borrow().is_dragging() {
+            self.drag_drop.borrow_mut().cancel_drag();
+            return;
+        }
+        if self.shortcuts.key_pressed(keycode, keymods, self.focus) {
+            return;
+        }
+        if self.keyboard_navigation && self.overlays.is_empty() && keymods == KeyMods::NONE {
+            if let Some(direction) = Direction::from_key(keycode) {
+                self.navigate(ctx, direction);
+                return;
+            }
+            if keycode == KeyCode::Return || keycode == KeyCode::Space {
+                if !repeat {
+                    self.confirm(ctx);
+                }
+                return;
+            }
+            if keycode == KeyCode::Escape && self.navigation.back() {
+                return;
+            }
+        }
+        self.send_key(ctx, keycode, keymods, repeat);
+    }
+
+    fn key_up_event(&mut self, ctx: &mut dyn IContext, keycode: KeyCode, keymods: KeyMods) {
+        for o in &self.overlays {
+            o.borrow_mut().key_up_event(ctx, keycode, keymods);
+        }
+        for w in &mut self.windows {
+            w.widget.borrow_mut().key_up_event(ctx, keycode, keymods);
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut dyn IContext, character: char) {
+        for o in &self.overlays {
+            o.borrow_mut().text_input_event(ctx, character);
+        }
+        for w in &mut self.windows {
+            w.widget.borrow_mut().text_input_event(ctx, character);
+        }
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut dyn IContext, btn: Button) {
+        if let Some(direction) = Direction::from_button(btn) {
+            self.navigate(ctx, direction);
+        } else if navigation::is_confirm_button(btn) {
+            self.confirm(ctx);
+        } else if navigation::is_back_button(btn) {
+            self.back(ctx);
+        }
+    }
+
+    fn gamepad_axis_event(&mut self, ctx: &mut dyn IContext, axis: Axis, value: f32) {
+        if let Some(direction) = self.navigation.axis_moved(axis, value) {
+            self.navigate(ctx, direction);
+        }
+    }
+
+    fn touch_event(&mut self, ctx: &mut dyn IContext, id: u64, phase: TouchPhase, x: f32, y: f32) {
+        let events = self.touch.touch(id, phase, x, y, ctx.time_since_start());
+        self.dispatch_touch(ctx, events);
+    }
+}
+
+/// Lets the application pass ggez events straight to the window manager
+impl EventHandler for WindowManager<'_> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        IEvents::update(self, ctx)
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        IEvents::draw(self, ctx)
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        IEvents::mouse_button_down_event(self, ctx, button, x, y)
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        IEvents::mouse_button_up_event(self, ctx, button, x, y)
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        IEvents::mouse_motion_event(self, ctx, x, y, dx, dy)
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        IEvents::mouse_wheel_event(self, ctx, x, y)
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        IEvents::key_down_event(self, ctx, keycode, keymods, repeat)
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods) {
+        IEvents::key_up_event(self, ctx, keycode, keymods)
+    }
+
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) {
+        IEvents::text_input_event(self, ctx, character)
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        IEvents::resize_event(self, ctx, width, height)
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
+        IEvents::gamepad_button_down_event(self, ctx, btn)
+    }
+
+    fn gamepad_axis_event(&mut self, ctx: &mut Context, axis: Axis, value: f32, _id: GamepadId) {
+        IEvents::gamepad_axis_event(self, ctx, axis, value)
+    }
 }
 
 impl ILayout for WindowManager<'_> {
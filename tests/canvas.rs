@@ -0,0 +1,71 @@
+use ggez::graphics::{self, Rect};
+use ggez::input::mouse::MouseButton;
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::painter::Painter;
+
+#[test]
+fn stroke_lasts_until_its_button_is_released() {
+    let moves = Rc::new(RefCell::new(Vec::new()));
+    let ups = Rc::new(RefCell::new(Vec::new()));
+    let canvas = {
+        let moves = moves.clone();
+        let ups = ups.clone();
+        gui::canvas()
+            .on_mouse_move(move |_, x, y| moves.borrow_mut().push([x, y]))
+            .on_mouse_up(move |_, button, _, _| ups.borrow_mut().push(button))
+            .build()
+    };
+    let mut harness = Harness::new(100., 100.);
+    harness
+        .window_manager()
+        .add_window(canvas, Rect::new(10., 10., 40., 40.), false);
+    harness.frame().unwrap();
+
+    harness.move_to(20., 20.);
+    harness.press(MouseButton::Left);
+    harness.press(MouseButton::Right);
+    harness.release(MouseButton::Right);
+    harness.move_to(80., 80.);
+    harness.frame().unwrap();
+    // Moves outside are still reported while the left button is held
+    assert_eq!(moves.borrow().last(), Some(&[70., 70.]));
+
+    harness.release(MouseButton::Left);
+    harness.move_to(90., 90.);
+    harness.frame().unwrap();
+    assert_eq!(moves.borrow().last(), Some(&[70., 70.]));
+    assert_eq!(*ups.borrow(), vec![MouseButton::Right, MouseButton::Left]);
+}
+
+#[test]
+fn degenerate_shapes_are_detected() {
+    let mut painter = Painter::new([0., 0.]);
+    painter
+        .line([5., 5.], [5., 5.], 1., graphics::WHITE)
+        .polyline(&[[0., 0.], [0., 0.], [4., 0.]], 1., graphics::WHITE)
+        .line([0., 0.], [4., 0.], 0., graphics::WHITE)
+        .fill_rect(Rect::new(0., 0., 0., 10.), graphics::WHITE)
+        .stroke_rect(Rect::new(0., 0., 10., 10.), 1., graphics::WHITE)
+        .fill_circle([5., 5.], 0., graphics::WHITE)
+        .stroke_circle([5., 5.], 3., 1., graphics::WHITE)
+        .fill_polygon(&[[0., 0.], [1., 1.], [2., 2.]], graphics::WHITE)
+        .fill_polygon(&[[0., 0.], [4., 0.], [0., 4.]], graphics::WHITE)
+        .text(
+            Rect::new(0., 0., 0., 0.),
+            "a",
+            graphics::Align::Left,
+            graphics::WHITE,
+        );
+    let degenerate: Vec<_> = painter
+        .commands()
+        .iter()
+        .map(|c| c.is_degenerate())
+        .collect();
+    assert_eq!(
+        degenerate,
+        vec![true, false, true, true, false, true, false, true, false, false]
+    );
+}
@@ -0,0 +1,284 @@
+use ggez::graphics::Rect;
+use ggez::input::mouse::MouseButton;
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui::capture::PointerCapture;
+use yorool_gui::gui::harness::{FakeContext, Harness};
+use yorool_gui::gui::touch::OUTSIDE;
+use yorool_gui::gui::{self, IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+
+// Widget which records the pointer events it gets
+#[derive(Default)]
+struct Probe {
+    rect: Rect,
+    presses: usize,
+    releases: Vec<[f32; 2]>,
+    motions: usize,
+}
+
+impl Probe {
+    fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self::default()))
+    }
+}
+
+impl IEvents for Probe {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, _ctx: &mut Context) -> GameResult {
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        self.presses += 1;
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.releases.push([x, y]);
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        _x: f32,
+        _y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) {
+        self.motions += 1;
+    }
+}
+
+impl ILayout for Probe {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Probe {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a> IOverlays<'a> for Probe {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+fn probes_in_row(harness: &mut Harness) -> (Rc<RefCell<Probe>>, Rc<RefCell<Probe>>) {
+    let (left, right) = (Probe::new(), Probe::new());
+    harness.add_window(
+        gui::row()
+            .add_widget(left.clone())
+            .add_widget(right.clone())
+            .build(),
+    );
+    harness.frame().unwrap();
+    (left, right)
+}
+
+#[test]
+fn capture_routes_to_pressed() {
+    let rects = vec![Rect::new(0., 0., 10., 10.), Rect::new(10., 0., 10., 10.)];
+    let mut c = PointerCapture::new();
+    assert!(c.routes_to(0) && c.routes_to(1));
+    assert_eq!(c.point(), None);
+    c.press(MouseButton::Left, 5., 5., rects.clone());
+    assert!(c.routes_to(0) && !c.routes_to(1));
+    assert_eq!(c.point(), Some([5., 5.]));
+    c.press(MouseButton::Right, 15., 5., rects.clone());
+    assert!(c.routes_to(0) && !c.routes_to(1));
+    c.release(MouseButton::Right);
+    assert!(c.is_captured());
+    c.release(MouseButton::Left);
+    assert!(!c.is_captured() && c.routes_to(1));
+    c.press(MouseButton::Left, 50., 5., rects);
+    assert!(!c.routes_to(0) && !c.routes_to(1));
+}
+
+#[test]
+fn captured_widget_gets_one_release() {
+    let mut harness = Harness::new(200., 100.);
+    let (left, right) = probes_in_row(&mut harness);
+    harness.move_to(50., 50.);
+    harness.press(MouseButton::Left);
+    harness.window_manager().capture_pointer(left.clone());
+    let motions = right.borrow().motions;
+    harness.move_to(150., 50.);
+    harness.release(MouseButton::Left);
+    assert_eq!(left.borrow().presses, 1);
+    assert_eq!(left.borrow().releases, vec![[150., 50.]]);
+    assert!(right.borrow().releases.is_empty());
+    assert_eq!(right.borrow().motions, motions);
+    assert!(!harness.window_manager().is_pointer_captured());
+
+    // Next click goes to both children again
+    harness.click_at(150., 50.);
+    assert_eq!(left.borrow().releases.len(), 1);
+    assert_eq!(right.borrow().releases, vec![[150., 50.]]);
+}
+
+#[test]
+fn widget_captured_away_from_press() {
+    let mut harness = Harness::new(200., 100.);
+    let (left, right) = probes_in_row(&mut harness);
+    harness.move_to(150., 50.);
+    harness.press(MouseButton::Left);
+    harness.window_manager().capture_pointer(left.clone());
+    harness.move_to(60., 50.);
+    harness.release(MouseButton::Left);
+    // The window under the press gets the release outside of any widget
+    assert_eq!(left.borrow().releases, vec![[60., 50.]]);
+    assert_eq!(right.borrow().releases, vec![OUTSIDE]);
+}
+
+#[test]
+fn release_pointer_sends_release_outside() {
+    let mut harness = Harness::new(200., 100.);
+    let (left, right) = probes_in_row(&mut harness);
+    harness.move_to(50., 50.);
+    harness.press(MouseButton::Left);
+    harness.window_manager().capture_pointer(left.clone());
+    let mut ctx = FakeContext::new(200., 100.);
+    harness.window_manager().release_pointer(&mut ctx);
+    assert_eq!(left.borrow().releases, vec![OUTSIDE]);
+    assert!(right.borrow().releases.is_empty());
+    assert!(!harness.window_manager().is_pointer_captured());
+}
+
+#[test]
+fn capture_requests_take_and_give_back_pointer() {
+    let mut harness = Harness::new(200., 100.);
+    let (left, right) = probes_in_row(&mut harness);
+    let requests = harness.window_manager().capture_requests();
+    harness.move_to(150., 50.);
+    harness.press(MouseButton::Left);
+    requests.borrow_mut().capture(left.clone());
+    assert!(requests.borrow().is_pending());
+    let motions = right.borrow().motions;
+    harness.move_to(160., 50.);
+    assert!(!requests.borrow().is_pending());
+    assert!(harness.window_manager().is_pointer_captured());
+    assert_eq!(right.borrow().motions, motions);
+
+    requests.borrow_mut().release();
+    harness.frame().unwrap();
+    assert_eq!(left.borrow().releases, vec![OUTSIDE]);
+    assert_eq!(right.borrow().releases, vec![OUTSIDE]);
+    assert!(!harness.window_manager().is_pointer_captured());
+}
+
+#[test]
+fn tabs_route_release_to_pressed_page() {
+    let page = Probe::new();
+    let tabs = gui::tabs().add_tab("Page", page.clone()).build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(tabs);
+    harness.frame().unwrap();
+    // Press on the strip of titles, release on the page
+    harness.move_to(50., 10.);
+    harness.press(MouseButton::Left);
+    let motions = page.borrow().motions;
+    harness.move_to(50., 60.);
+    harness.release(MouseButton::Left);
+    assert_eq!(page.borrow().motions, motions);
+    assert!(page.borrow().releases.is_empty());
+
+    // Press on the page, release on the strip
+    harness.move_to(50., 60.);
+    harness.press(MouseButton::Left);
+    harness.move_to(50., 10.);
+    harness.release(MouseButton::Left);
+    assert_eq!(page.borrow().releases, vec![[50., 10.]]);
+}
+
+#[test]
+fn group_box_routes_release_to_pressed_content() {
+    let content = Probe::new();
+    let group_box = gui::group_box()
+        .set_title("Group")
+        .set_content(content.clone())
+        .build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(group_box.clone());
+    harness.frame().unwrap();
+    let content_rect = content.borrow().get_rect();
+    let inside = [content_rect.x + 10., content_rect.y + 10.];
+    // Press on the header, release on the content
+    harness.move_to(50., 5.);
+    harness.press(MouseButton::Left);
+    harness.move_to(inside[0], inside[1]);
+    harness.release(MouseButton::Left);
+    assert!(content.borrow().releases.is_empty());
+    assert!(!group_box.borrow().is_collapsed());
+
+    harness.press(MouseButton::Left);
+    harness.move_to(50., 5.);
+    harness.release(MouseButton::Left);
+    assert_eq!(content.borrow().releases, vec![[50., 5.]]);
+    assert!(!group_box.borrow().is_collapsed());
+}
+
+#[test]
+fn wrappers_route_release_to_pressed_widget() {
+    let wrapped = Probe::new();
+    let area = gui::gesture_area(wrapped.clone());
+    let menu = gui::menu().build();
+    let context_menu = gui::context_menu(wrapped.clone(), menu);
+    let mut harness = Harness::new(200., 100.);
+    let rect = Rect::new(0., 0., 100., 100.);
+    area.borrow_mut().set_rect(rect);
+    context_menu.borrow_mut().set_rect(rect);
+    let ctx = harness.context();
+    let button = MouseButton::Left;
+
+    area.borrow_mut()
+        .mouse_button_down_event(ctx, button, 150., 50.);
+    area.borrow_mut().mouse_motion_event(ctx, 50., 50., 0., 0.);
+    area.borrow_mut()
+        .mouse_button_up_event(ctx, button, 50., 50.);
+    context_menu
+        .borrow_mut()
+        .mouse_button_down_event(ctx, button, 150., 50.);
+    context_menu
+        .borrow_mut()
+        .mouse_motion_event(ctx, 50., 50., 0., 0.);
+    context_menu
+        .borrow_mut()
+        .mouse_button_up_event(ctx, button, 50., 50.);
+    assert_eq!(wrapped.borrow().motions, 0);
+    assert!(wrapped.borrow().releases.is_empty());
+
+    area.borrow_mut()
+        .mouse_button_down_event(ctx, button, 50., 50.);
+    area.borrow_mut()
+        .mouse_button_up_event(ctx, button, 150., 50.);
+    context_menu
+        .borrow_mut()
+        .mouse_button_down_event(ctx, button, 50., 50.);
+    context_menu
+        .borrow_mut()
+        .mouse_button_up_event(ctx, button, 150., 50.);
+    assert_eq!(wrapped.borrow().releases, vec![[150., 50.], [150., 50.]]);
+}
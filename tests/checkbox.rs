@@ -0,0 +1,21 @@
+use ggez::input::mouse::MouseButton;
+use yorool_gui::gui::checkbox::CheckboxBuilder;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::ICheckbox;
+
+#[test]
+fn only_left_button_toggles() {
+    let checkbox = CheckboxBuilder::new().build();
+    let mut harness = Harness::new(100., 100.);
+    harness.add_window(checkbox.clone());
+    harness.frame().unwrap();
+    harness.move_to(50., 50.);
+    harness.press(MouseButton::Left);
+    harness.release(MouseButton::Right);
+    assert!(!checkbox.borrow().get_state());
+    harness.release(MouseButton::Left);
+    assert!(checkbox.borrow().get_state());
+    harness.press(MouseButton::Right);
+    harness.release(MouseButton::Right);
+    assert!(checkbox.borrow().get_state());
+}
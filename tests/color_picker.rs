@@ -0,0 +1,82 @@
+use ggez::graphics::Color;
+use yorool_gui::gui::color_picker::{hsv_to_rgb, parse_color, rgb_to_hsv, to_hex};
+
+fn assert_close(a: (f32, f32, f32), b: (f32, f32, f32)) {
+    let close = |x: f32, y: f32| (x - y).abs() < 1e-4;
+    assert!(
+        close(a.0, b.0) && close(a.1, b.1) && close(a.2, b.2),
+        "{:?} != {:?}",
+        a,
+        b
+    );
+}
+
+#[test]
+fn hsv_round_trip() {
+    for &(r, g, b) in &[
+        (1., 0., 0.),
+        (0.2, 0.6, 0.4),
+        (0.9, 0.1, 0.7),
+        (0.3, 0.3, 0.8),
+        (1., 1., 0.),
+    ] {
+        let (h, s, v) = rgb_to_hsv(r, g, b);
+        assert_close(hsv_to_rgb(h, s, v), (r, g, b));
+    }
+    assert_close(rgb_to_hsv(0., 0., 1.), (240., 1., 1.));
+    assert_close(hsv_to_rgb(120., 0.5, 0.8), (0.4, 0.8, 0.4));
+}
+
+#[test]
+fn hue_wraps_around() {
+    assert_close(hsv_to_rgb(360., 1., 1.), (1., 0., 0.));
+    assert_close(hsv_to_rgb(-120., 1., 1.), hsv_to_rgb(240., 1., 1.));
+    // Magenta-red hues stay below 360
+    let (h, _, _) = rgb_to_hsv(1., 0., 0.2);
+    assert!(h > 300. && h < 360.);
+}
+
+#[test]
+fn grays_have_no_hue() {
+    assert_close(rgb_to_hsv(0.5, 0.5, 0.5), (0., 0., 0.5));
+    assert_close(rgb_to_hsv(0., 0., 0.), (0., 0., 0.));
+    assert_close(hsv_to_rgb(200., 0., 0.25), (0.25, 0.25, 0.25));
+}
+
+#[test]
+fn hex_formats() {
+    assert_eq!(to_hex(Color::from_rgb(255, 128, 0)), "#ff8000");
+    assert_eq!(to_hex(Color::from_rgba(1, 2, 3, 4)), "#01020304");
+    let color = Color::from_rgba(18, 52, 86, 120);
+    assert_eq!(parse_color(&to_hex(color)), Some(color));
+}
+
+#[test]
+fn parses_colors() {
+    assert_eq!(parse_color("#ff8000"), Some(Color::from_rgb(255, 128, 0)));
+    assert_eq!(parse_color(" FF8000 "), Some(Color::from_rgb(255, 128, 0)));
+    assert_eq!(parse_color("#f80"), Some(Color::from_rgb(255, 136, 0)));
+    assert_eq!(
+        parse_color("#ff800040"),
+        Some(Color::from_rgba(255, 128, 0, 64))
+    );
+    assert_eq!(
+        parse_color("255, 128, 0"),
+        Some(Color::from_rgb(255, 128, 0))
+    );
+    assert_eq!(
+        parse_color("rgba(255 128 0 64)"),
+        Some(Color::from_rgba(255, 128, 0, 64))
+    );
+}
+
+#[test]
+fn rejects_bad_colors() {
+    assert_eq!(parse_color("#ff80"), None);
+    assert_eq!(parse_color("#gg8000"), None);
+    assert_eq!(parse_color("#ff800"), None);
+    assert_eq!(parse_color("256, 0, 0"), None);
+    assert_eq!(parse_color("1, 2"), None);
+    assert_eq!(parse_color("rgb()"), None);
+    assert_eq!(parse_color("#é12"), None);
+}
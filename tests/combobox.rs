@@ -0,0 +1,107 @@
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::combobox::ComboBox;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::IOverlays;
+
+fn combobox<'a>(
+    harness: &mut Harness<'a>,
+    rect: Rect,
+    items: &[&str],
+) -> Rc<RefCell<ComboBox<'a>>> {
+    let mut builder = gui::combobox();
+    for item in items {
+        builder = builder.add_item(*item);
+    }
+    let combobox = builder.build();
+    harness
+        .window_manager()
+        .add_window(combobox.clone(), rect, false);
+    harness.frame().unwrap();
+    combobox
+}
+
+fn popup_rect(combobox: &Rc<RefCell<ComboBox>>) -> Rect {
+    let overlays = combobox.borrow_mut().collect_overlays();
+    assert_eq!(overlays.len(), 1);
+    let rect = overlays[0].borrow().get_rect();
+    rect
+}
+
+#[test]
+fn popup_opens_below() {
+    let mut harness = Harness::new(200., 200.);
+    let combobox = combobox(&mut harness, Rect::new(10., 10., 80., 30.), &["a", "b"]);
+    harness.click_at(50., 25.);
+    assert!(combobox.borrow().is_open());
+    assert_eq!(popup_rect(&combobox), Rect::new(10., 40., 80., 60.));
+}
+
+#[test]
+fn popup_stays_on_screen() {
+    let mut harness = Harness::new(200., 200.);
+    let items = ["a", "b", "c", "d"];
+    let combobox = combobox(&mut harness, Rect::new(150., 170., 80., 30.), &items);
+    harness.click_at(160., 185.);
+    // No room below, so the popup opens above and moves left
+    assert_eq!(popup_rect(&combobox), Rect::new(120., 50., 80., 120.));
+
+    // Resized screen moves the open popup
+    harness.context().set_drawable_size(180., 200.);
+    harness.frame().unwrap();
+    assert_eq!(popup_rect(&combobox).x, 100.);
+
+    // Too tall for either side, so it is clamped to the screen
+    let combobox = combobox_with_max(&mut harness, 10);
+    harness.click_at(20., 110.);
+    assert_eq!(popup_rect(&combobox).y, 0.);
+}
+
+fn combobox_with_max<'a>(harness: &mut Harness<'a>, count: usize) -> Rc<RefCell<ComboBox<'a>>> {
+    let items: Vec<_> = (0..count).map(|n| n.to_string()).collect();
+    let items: Vec<_> = items.iter().map(String::as_str).collect();
+    let combobox = combobox(harness, Rect::new(0., 100., 50., 30.), &items);
+    combobox.borrow_mut().set_max_visible(count);
+    combobox
+}
+
+#[test]
+fn keys_choose_and_search() {
+    let selected = Rc::new(RefCell::new(Vec::new()));
+    let combobox = {
+        let selected = selected.clone();
+        gui::combobox()
+            .add_item("apple")
+            .add_item("banana")
+            .add_item("blueberry")
+            .add_item("cherry")
+            .on_selected(move |_, index| selected.borrow_mut().push(index))
+            .build()
+    };
+    let mut harness = Harness::new(200., 200.);
+    harness
+        .window_manager()
+        .add_window(combobox.clone(), Rect::new(0., 0., 100., 30.), false);
+    harness.frame().unwrap();
+    // Click opens the popup and focuses the combobox
+    harness.click_at(50., 15.);
+    harness.press_key(KeyCode::Down, KeyMods::NONE);
+    harness.press_key(KeyCode::Down, KeyMods::NONE);
+    harness.press_key(KeyCode::Return, KeyMods::NONE);
+    harness.frame().unwrap();
+    assert!(!combobox.borrow().is_open());
+    assert_eq!(combobox.borrow().get_selected(), Some(1));
+
+    // Repeated letter cycles through items starting with it
+    harness.type_text("b");
+    assert_eq!(combobox.borrow().get_selected(), Some(2));
+    harness.advance(Duration::from_millis(1500)).unwrap();
+    harness.type_text("ch");
+    assert_eq!(combobox.borrow().get_selected(), Some(3));
+    harness.frame().unwrap();
+    assert_eq!(*selected.borrow(), vec![1, 2, 3]);
+}
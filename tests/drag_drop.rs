@@ -0,0 +1,116 @@
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::drag_drop::{DragDrop, DropTarget, Payload, Preview};
+use yorool_gui::gui::{IActions, ILayout, Widget};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn widget<'a>(rect: Rect) -> Rc<RefCell<dyn Widget<'a> + 'a>> {
+    let button = gui::button().build();
+    button.borrow_mut().set_rect(rect);
+    button
+}
+
+// Target which logs its callbacks with the name
+fn target<'a>(log: &Log, name: &str) -> DropTarget<'a> {
+    let event = |kind: &'static str| {
+        let log = log.clone();
+        let name = name.to_string();
+        move |_: &Payload, x: f32, y: f32| {
+            log.borrow_mut()
+                .push(format!("{} {} {} {}", kind, name, x, y))
+        }
+    };
+    DropTarget::new()
+        .on_enter(event("enter"))
+        .on_over(event("over"))
+        .on_leave(event("leave"))
+        .on_drop(event("drop"))
+}
+
+fn take(drag_drop: &mut DragDrop, log: &Log) -> Vec<String> {
+    for e in drag_drop.collect_fired() {
+        (*e)()
+    }
+    log.borrow_mut().drain(..).collect()
+}
+
+#[test]
+fn innermost_accepting_target_gets_the_drag() {
+    let log = Log::default();
+    let outer = widget(Rect::new(0., 0., 100., 100.));
+    let inner = widget(Rect::new(10., 10., 30., 30.));
+    let numbers = widget(Rect::new(15., 15., 10., 10.));
+    let mut drag_drop = DragDrop::new();
+    // Targets are kept weakly, so the widgets have to outlive the drag
+    drag_drop.set_drop_target(outer.clone(), target(&log, "outer"));
+    drag_drop.set_drop_target(inner.clone(), target(&log, "inner"));
+    drag_drop.set_drop_target(numbers.clone(), target(&log, "numbers").accept::<i32>());
+
+    drag_drop.pointer_moved(50., 50., false);
+    drag_drop.start_drag(Payload::new("text"), Preview::None);
+    assert_eq!(
+        take(&mut drag_drop, &log),
+        vec!["enter outer 50 50", "over outer 50 50"]
+    );
+
+    drag_drop.pointer_moved(20., 20., false);
+    assert_eq!(
+        take(&mut drag_drop, &log),
+        vec!["leave outer 20 20", "enter inner 20 20", "over inner 20 20"]
+    );
+    assert!(Rc::ptr_eq(&drag_drop.target().unwrap(), &inner));
+
+    // Overlays block the targets below them
+    drag_drop.pointer_moved(20., 20., true);
+    assert_eq!(take(&mut drag_drop, &log), vec!["leave inner 20 20"]);
+    assert!(drag_drop.target().is_none());
+
+    assert!(drag_drop.pointer_released(30., 30., false));
+    assert_eq!(
+        take(&mut drag_drop, &log),
+        vec!["enter inner 30 30", "over inner 30 30", "drop inner 30 30"]
+    );
+    assert!(!drag_drop.is_dragging());
+    assert!(!drag_drop.pointer_released(30., 30., false));
+}
+
+#[test]
+fn cancel_leaves_the_target() {
+    let log = Log::default();
+    let outer = widget(Rect::new(0., 0., 100., 100.));
+    let mut drag_drop = DragDrop::new();
+    drag_drop.set_drop_target(outer.clone(), target(&log, "outer"));
+    drag_drop.pointer_moved(50., 50., false);
+    drag_drop.start_drag(Payload::new(1), Preview::None);
+    take(&mut drag_drop, &log);
+
+    drag_drop.cancel_drag();
+    assert_eq!(take(&mut drag_drop, &log), vec!["leave outer 50 50"]);
+    assert!(!drag_drop.pointer_released(50., 50., false));
+    assert!(take(&mut drag_drop, &log).is_empty());
+}
+
+#[test]
+fn removing_other_target_keeps_the_hovered_one() {
+    let log = Log::default();
+    let first = widget(Rect::new(0., 0., 50., 50.));
+    let second = widget(Rect::new(50., 0., 50., 50.));
+    let mut drag_drop = DragDrop::new();
+    drag_drop.set_drop_target(first.clone(), target(&log, "first"));
+    drag_drop.set_drop_target(second.clone(), target(&log, "second"));
+    drag_drop.pointer_moved(70., 20., false);
+    drag_drop.start_drag(Payload::new(1), Preview::None);
+    take(&mut drag_drop, &log);
+
+    drag_drop.remove_drop_target(first);
+    assert!(take(&mut drag_drop, &log).is_empty());
+    assert!(Rc::ptr_eq(&drag_drop.target().unwrap(), &second));
+
+    drag_drop.remove_drop_target(second);
+    assert_eq!(take(&mut drag_drop, &log), vec!["leave second 70 20"]);
+    drag_drop.pointer_released(70., 20., false);
+    assert!(take(&mut drag_drop, &log).is_empty());
+}
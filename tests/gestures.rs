@@ -0,0 +1,150 @@
+use ggez::input::mouse::MouseButton;
+use std::time::Duration;
+use yorool_gui::gui::gestures::{Gesture, GestureRecognizer, GestureSettings};
+
+const LEFT: MouseButton = MouseButton::Left;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn click(r: &mut GestureRecognizer, x: f32, y: f32, at: Duration) -> Vec<Gesture> {
+    r.mouse_down(LEFT, x, y, at);
+    r.mouse_up(LEFT, x, y, at + ms(50))
+}
+
+#[test]
+fn press_and_release_is_click() {
+    let mut r = GestureRecognizer::new();
+    assert_eq!(
+        click(&mut r, 10., 10., ms(0)),
+        vec![Gesture::Click {
+            button: LEFT,
+            x: 10.,
+            y: 10.
+        }]
+    );
+    r.mouse_down(MouseButton::Right, 20., 20., ms(1000));
+    assert_eq!(
+        r.mouse_up(MouseButton::Right, 20., 20., ms(1100)),
+        vec![Gesture::RightClick { x: 20., y: 20. }]
+    );
+    // Release of a button which wasn't pressed first is ignored
+    r.mouse_down(LEFT, 0., 0., ms(2000));
+    assert!(r.mouse_up(MouseButton::Middle, 0., 0., ms(2050)).is_empty());
+    assert!(r.is_pressed());
+}
+
+#[test]
+fn double_click_needs_time_and_place() {
+    let mut r = GestureRecognizer::new();
+    let double = vec![Gesture::DoubleClick {
+        button: LEFT,
+        x: 12.,
+        y: 10.,
+    }];
+    click(&mut r, 10., 10., ms(0));
+    assert_eq!(click(&mut r, 12., 10., ms(300)), double);
+    // The third click starts over
+    assert!(matches!(
+        click(&mut r, 12., 10., ms(600))[..],
+        [Gesture::Click { .. }]
+    ));
+
+    // Too late
+    let mut r = GestureRecognizer::new();
+    click(&mut r, 10., 10., ms(0));
+    assert!(matches!(
+        click(&mut r, 10., 10., ms(500))[..],
+        [Gesture::Click { .. }]
+    ));
+
+    // Too far
+    let mut r = GestureRecognizer::new();
+    click(&mut r, 10., 10., ms(0));
+    assert!(matches!(
+        click(&mut r, 15., 10., ms(100))[..],
+        [Gesture::Click { .. }]
+    ));
+
+    // Custom time
+    let mut r = GestureRecognizer::new();
+    r.set_settings(GestureSettings {
+        double_click_time: ms(1000),
+        ..GestureSettings::default()
+    });
+    click(&mut r, 12., 10., ms(0));
+    assert_eq!(click(&mut r, 12., 10., ms(800)), double);
+}
+
+#[test]
+fn long_press_replaces_click() {
+    let mut r = GestureRecognizer::new();
+    r.mouse_down(LEFT, 5., 5., ms(0));
+    assert!(r.update(ms(599)).is_empty());
+    assert_eq!(r.update(ms(600)), vec![Gesture::LongPress { x: 5., y: 5. }]);
+    assert!(r.update(ms(900)).is_empty());
+    assert!(r.mouse_up(LEFT, 5., 5., ms(1000)).is_empty());
+
+    // The right button has no long press
+    r.mouse_down(MouseButton::Right, 5., 5., ms(2000));
+    assert!(r.update(ms(3000)).is_empty());
+}
+
+#[test]
+fn drag_starts_past_threshold() {
+    let mut r = GestureRecognizer::new();
+    r.mouse_down(LEFT, 10., 10., ms(0));
+    assert!(r.mouse_motion(13., 10.).is_empty());
+    assert!(r.mouse_motion(14., 10.).is_empty());
+    assert!(!r.is_dragging());
+    assert_eq!(
+        r.mouse_motion(15., 10.),
+        vec![
+            Gesture::DragStart {
+                button: LEFT,
+                x: 10.,
+                y: 10.
+            },
+            Gesture::DragMove {
+                button: LEFT,
+                x: 15.,
+                y: 10.,
+                dx: 5.,
+                dy: 0.
+            }
+        ]
+    );
+    // Held long after the drag started, it is no long press
+    assert!(r.update(ms(1000)).is_empty());
+    assert_eq!(
+        r.mouse_up(LEFT, 10., 10., ms(1100)),
+        vec![Gesture::DragEnd {
+            button: LEFT,
+            x: 10.,
+            y: 10.
+        }]
+    );
+    // A click after the drag is a single click
+    assert!(matches!(
+        click(&mut r, 10., 10., ms(1200))[..],
+        [Gesture::Click { .. }]
+    ));
+}
+
+#[test]
+fn cancel_ends_drag() {
+    let mut r = GestureRecognizer::new();
+    r.mouse_down(LEFT, 0., 0., ms(0));
+    r.mouse_motion(20., 0.);
+    assert_eq!(
+        r.cancel(),
+        vec![Gesture::DragEnd {
+            button: LEFT,
+            x: 20.,
+            y: 0.
+        }]
+    );
+    assert!(!r.is_pressed());
+    assert!(r.mouse_up(LEFT, 20., 0., ms(100)).is_empty());
+}
@@ -0,0 +1,55 @@
+use ggez::graphics::Rect;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::{ICheckbox, ILayout};
+
+#[test]
+fn accordion_collapse_reflows_parent_ribbon() {
+    let first = gui::group_box()
+        .set_title("First")
+        .set_content(gui::button().build())
+        .set_content_height(50.)
+        .build();
+    let second = gui::group_box()
+        .set_title("Second")
+        .set_content(gui::button().build())
+        .set_content_height(50.)
+        .set_collapsed(true)
+        .build();
+    let _accordion = gui::accordion()
+        .add_widget(first.clone())
+        .add_widget(second.clone())
+        .build();
+    let sections = gui::column()
+        .add_widget(first.clone())
+        .add_widget(second.clone())
+        .build();
+    let below = gui::button().build();
+    let window = gui::column()
+        .add_widget(sections.clone())
+        .add_widget(below.clone())
+        .build();
+    let mut harness = Harness::new(100., 200.);
+    harness.add_window(window.clone());
+    harness.frame().unwrap();
+    assert_eq!(second.borrow().get_rect().y, 74.);
+    assert_eq!(below.borrow().get_rect(), Rect::new(0., 98., 100., 102.));
+
+    // Expanding the second section collapses the first one. The accordion
+    // handler runs after the update, so the parent lays out a frame later
+    harness.click_at(50., 80.);
+    harness.frames(2).unwrap();
+    assert!(first.borrow().is_collapsed());
+    assert!(second.borrow().get_state());
+    assert_eq!(second.borrow().get_rect().y, 24.);
+    assert_eq!(below.borrow().get_rect(), Rect::new(0., 98., 100., 102.));
+
+    // Collapsing it leaves none expanded and the rest moves up
+    harness.click_at(50., 30.);
+    harness.frame().unwrap();
+    assert!(!first.borrow().get_state() && !second.borrow().get_state());
+    assert_eq!(below.borrow().get_rect(), Rect::new(0., 48., 100., 152.));
+    // The change went up to the window, which has no parent to report it to
+    assert!(window.borrow_mut().take_layout_changed());
+    assert!(!sections.borrow_mut().take_layout_changed());
+}
@@ -0,0 +1,108 @@
+use ggez::event::{KeyCode, KeyMods};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::demo::RadioPanel;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::IContext;
+
+fn radio_panel(harness: &mut Harness<'static>) -> RadioPanel<'static> {
+    let panel = RadioPanel::new(harness.window_manager());
+    harness.set_id("add", panel.add_button());
+    harness.set_id("remove", panel.remove_button());
+    harness.frame().unwrap();
+    panel
+}
+
+fn states(panel: &RadioPanel) -> Vec<bool> {
+    let group = panel.radio_group();
+    let group = group.borrow();
+    group
+        .radios()
+        .iter()
+        .map(|r| r.borrow().get_state())
+        .collect()
+}
+
+#[test]
+fn click_add_adds_radio() {
+    let mut harness = Harness::new(400., 300.);
+    let panel = radio_panel(&mut harness);
+    assert_eq!(panel.radio_group().borrow().radios().len(), 3);
+    assert!(harness.click_widget("add"));
+    harness.frame().unwrap();
+    assert_eq!(panel.radio_group().borrow().radios().len(), 4);
+
+    assert!(harness.click_widget("remove"));
+    assert!(harness.click_widget("remove"));
+    harness.frame().unwrap();
+    assert_eq!(panel.radio_group().borrow().radios().len(), 2);
+    assert!(!harness.click_widget("missing"));
+}
+
+#[test]
+fn click_at_checks_radio() {
+    let mut harness = Harness::new(400., 300.);
+    let panel = radio_panel(&mut harness);
+    // Radios share the top half of the column equally
+    harness.click_at(200., 75.);
+    harness.frame().unwrap();
+    assert_eq!(states(&panel), vec![false, true, false]);
+    harness.click_at(50., 75.);
+    harness.frame().unwrap();
+    assert_eq!(states(&panel), vec![true, false, false]);
+}
+
+#[test]
+fn handlers_run_on_frame() {
+    let clicks = Rc::new(RefCell::new(0));
+    let button = {
+        let clicks = clicks.clone();
+        gui::button()
+            .on_click(move |_| *clicks.borrow_mut() += 1)
+            .build()
+    };
+    let mut harness = Harness::new(100., 100.);
+    harness.add_window(button.clone());
+    harness.set_id("button", button);
+    harness.frame().unwrap();
+    harness.click_widget("button");
+    assert_eq!(*clicks.borrow(), 0);
+    harness.frame().unwrap();
+    assert_eq!(*clicks.borrow(), 1);
+
+    // Two frames have run, a second is 60 of them
+    harness.frames(58).unwrap();
+    let now = harness.context().time_since_start();
+    assert!(now > Duration::from_millis(999) && now < Duration::from_millis(1001));
+}
+
+#[test]
+fn type_text_and_press_key() {
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let area = {
+        let changes = changes.clone();
+        gui::text_area()
+            .on_changed(move |area| changes.borrow_mut().push(area.borrow().get_text()))
+            .build()
+    };
+    let mut harness = Harness::new(400., 300.);
+    harness.add_window(area.clone());
+    harness.set_id("area", area.clone());
+    harness.frame().unwrap();
+    harness.click_widget("area");
+    harness.type_text("hello\nworld");
+    harness.press_key(KeyCode::Back, KeyMods::NONE);
+    assert!(changes.borrow().is_empty());
+    harness.frame().unwrap();
+    assert_eq!(area.borrow().get_text(), "hello\nworl");
+    assert_eq!(changes.borrow().last().unwrap(), "hello\nworl");
+
+    // Modifiers are held only while the key is down
+    harness.press_key(KeyCode::A, KeyMods::CTRL);
+    harness.type_text("a");
+    harness.frame().unwrap();
+    assert_eq!(area.borrow().get_text(), "a");
+    assert_eq!(changes.borrow().last().unwrap(), "a");
+}
@@ -0,0 +1,52 @@
+use ggez::graphics::Rect;
+use yorool_gui::gui::image::{Fit, ImageData};
+
+// 2x1 RGBA image: opaque red and half transparent blue
+const RED_BLUE_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0xf4, 0x22, 0x7f,
+    0x8a, 0x00, 0x00, 0x00, 0x0e, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8, 0xcf, 0xc0, 0x00,
+    0x42, 0x0d, 0x00, 0x0f, 0x7a, 0x03, 0x7e, 0x6a, 0x81, 0x31, 0xe1, 0x00, 0x00, 0x00, 0x00, 0x49,
+    0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+#[test]
+fn png_is_decoded_to_rgba() {
+    let data = ImageData::from_png_bytes(RED_BLUE_PNG).unwrap();
+    assert_eq!((data.width(), data.height()), (2, 1));
+    assert_eq!(data.rgba(), &[255, 0, 0, 255, 0, 0, 255, 128][..]);
+}
+
+#[test]
+fn invalid_png_is_error() {
+    assert!(ImageData::from_png_bytes(b"not a png").is_err());
+    assert!(ImageData::from_png_bytes(&RED_BLUE_PNG[..40]).is_err());
+}
+
+#[test]
+fn place_keeps_aspect_ratio() {
+    let rect = Rect::new(10., 20., 100., 100.);
+    let full = Rect::new(0., 0., 1., 1.);
+    assert_eq!(Fit::Stretch.place(200., 100., rect), (full, rect));
+    assert_eq!(
+        Fit::Contain.place(200., 100., rect),
+        (full, Rect::new(10., 45., 100., 50.))
+    );
+    assert_eq!(
+        Fit::Cover.place(200., 100., rect),
+        (Rect::new(0.25, 0., 0.5, 1.), rect)
+    );
+}
+
+#[test]
+fn place_center_crops_larger_side() {
+    let rect = Rect::new(0., 0., 100., 100.);
+    assert_eq!(
+        Fit::Center.place(50., 200., rect),
+        (Rect::new(0., 0.25, 1., 0.5), Rect::new(25., 0., 50., 100.))
+    );
+    assert_eq!(
+        Fit::Center.place(0., 10., rect),
+        (Rect::new(0., 0., 1., 1.), Rect::new(0., 0., 0., 0.))
+    );
+}
@@ -0,0 +1,88 @@
+use ggez::graphics::Rect;
+use ggez::input::mouse::MouseButton;
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::menu::{Menu, MenuItem};
+use yorool_gui::gui::Widget;
+
+fn popup_rects(widget: &Rc<RefCell<impl Widget<'static>>>) -> Vec<Rect> {
+    let popups = widget.borrow_mut().collect_overlays();
+    popups.iter().map(|p| p.borrow().get_rect()).collect()
+}
+
+// Three items, 100 wide and 90 high
+fn file_menu(submenu: Option<Rc<RefCell<Menu<'static>>>>) -> Rc<RefCell<Menu<'static>>> {
+    let builder = gui::menu()
+        .add_item(MenuItem::action("Open"))
+        .add_item(MenuItem::action("Save"));
+    match submenu {
+        Some(submenu) => builder.add_item(MenuItem::submenu("More", submenu)),
+        None => builder.add_item(MenuItem::action("Quit")),
+    }
+    .build()
+}
+
+#[test]
+fn context_menu_flips_at_screen_edges() {
+    let submenu = file_menu(None);
+    let context_menu = gui::context_menu(gui::button().build(), file_menu(Some(submenu)));
+    let mut harness = Harness::new(300., 200.);
+    harness.add_window(context_menu.clone());
+    harness.frame().unwrap();
+    harness.move_to(20., 20.);
+    harness.press(MouseButton::Right);
+    harness.release(MouseButton::Right);
+    harness.frame().unwrap();
+    assert_eq!(
+        popup_rects(&context_menu),
+        vec![Rect::new(22., 22., 100., 90.)]
+    );
+    harness.click_at(200., 190.);
+    assert!(!context_menu.borrow().is_open());
+
+    // Opens left of and above the point which is near the corner
+    harness.move_to(250., 150.);
+    harness.press(MouseButton::Right);
+    harness.release(MouseButton::Right);
+    harness.frame().unwrap();
+    assert_eq!(
+        popup_rects(&context_menu),
+        vec![Rect::new(148., 58., 100., 90.)]
+    );
+
+    // Submenu doesn't fit on the right, so it opens on the left
+    harness.move_to(200., 135.);
+    let rects = popup_rects(&context_menu);
+    assert_eq!(rects.len(), 2);
+    assert_eq!(rects[1], Rect::new(48., 58., 100., 90.));
+}
+
+#[test]
+fn menu_bar_activates_item() {
+    let activated = Rc::new(RefCell::new(Vec::new()));
+    let menu = {
+        let activated = activated.clone();
+        gui::menu()
+            .add_item(
+                MenuItem::action("Open")
+                    .on_activated(move |_, index| activated.borrow_mut().push(index)),
+            )
+            .build()
+    };
+    let bar = gui::menu_bar().add_menu("File", menu).build();
+    let mut harness = Harness::new(300., 200.);
+    harness
+        .window_manager()
+        .add_window(bar.clone(), Rect::new(0., 0., 300., 30.), false);
+    harness.frame().unwrap();
+    harness.click_at(10., 15.);
+    assert!(bar.borrow().is_open());
+    harness.frame().unwrap();
+    assert_eq!(popup_rects(&bar), vec![Rect::new(0., 30., 100., 30.)]);
+    harness.click_at(20., 45.);
+    harness.frame().unwrap();
+    assert_eq!(*activated.borrow(), vec![0]);
+    assert!(!bar.borrow().is_open());
+}
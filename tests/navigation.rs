@@ -0,0 +1,137 @@
+use ggez::event::{Axis, Button};
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::navigation::{nearest, Direction, Navigation};
+use yorool_gui::gui::{ILayout, Widget};
+
+#[test]
+fn nearest_prefers_same_row() {
+    let from = Rect::new(0., 0., 10., 10.);
+    let rects = [
+        Rect::new(20., 30., 10., 10.),
+        Rect::new(40., 0., 10., 10.),
+        Rect::new(-20., 0., 10., 10.),
+    ];
+    assert_eq!(nearest(from, Direction::Right, &rects), Some(1));
+    assert_eq!(nearest(from, Direction::Left, &rects), Some(2));
+    assert_eq!(nearest(from, Direction::Down, &rects), Some(0));
+    assert_eq!(nearest(from, Direction::Up, &rects), None);
+}
+
+#[test]
+fn nearest_ignores_broken_rects() {
+    let from = Rect::new(0., 0., 10., 10.);
+    let rects = [
+        Rect::new(f32::NAN, 0., 10., 10.),
+        Rect::new(f32::INFINITY, 0., 10., 10.),
+        Rect::new(20., 0., f32::NAN, 10.),
+        Rect::new(40., 0., 10., 10.),
+    ];
+    assert_eq!(nearest(from, Direction::Right, &rects), Some(3));
+    assert_eq!(nearest(from, Direction::Left, &rects), None);
+}
+
+#[test]
+fn stick_moves_once_per_push() {
+    let mut navigation = Navigation::new();
+    assert_eq!(
+        navigation.axis_moved(Axis::LeftStickX, 0.6),
+        Some(Direction::Right)
+    );
+    assert_eq!(navigation.axis_moved(Axis::LeftStickX, 0.9), None);
+    // Between the thresholds the stick still counts as pushed
+    assert_eq!(navigation.axis_moved(Axis::LeftStickX, 0.3), None);
+    assert_eq!(navigation.axis_moved(Axis::LeftStickX, 0.6), None);
+    assert_eq!(navigation.axis_moved(Axis::LeftStickX, 0.1), None);
+    assert_eq!(
+        navigation.axis_moved(Axis::LeftStickX, 0.6),
+        Some(Direction::Right)
+    );
+    // Pushing the other way doesn't need the release
+    assert_eq!(
+        navigation.axis_moved(Axis::LeftStickX, -0.7),
+        Some(Direction::Left)
+    );
+    // Y axis points up, axes are tracked separately
+    assert_eq!(
+        navigation.axis_moved(Axis::LeftStickY, 0.8),
+        Some(Direction::Up)
+    );
+    assert_eq!(navigation.axis_moved(Axis::RightStickX, 1.), None);
+}
+
+fn button<'a>(rect: Rect) -> Rc<RefCell<dyn Widget<'a> + 'a>> {
+    let button = gui::button().build();
+    button.borrow_mut().set_rect(rect);
+    button
+}
+
+#[test]
+fn navigate_skips_unreachable_widgets() {
+    let left = button(Rect::new(0., 0., 10., 10.));
+    let hidden = button(Rect::new(20., 0., 10., 10.));
+    let right = button(Rect::new(40., 0., 10., 10.));
+    let mut navigation = Navigation::new();
+    for w in &[&hidden, &left, &right] {
+        navigation.add_focusable((*w).clone());
+    }
+    let hidden_ptr = Rc::as_ptr(&hidden) as *const ();
+    let reachable = |w: &Rc<RefCell<dyn Widget>>| Rc::as_ptr(w) as *const () != hidden_ptr;
+    // Without focus the first reachable one is focused
+    assert!(navigation.navigate(Direction::Right, reachable));
+    assert!(Rc::ptr_eq(&navigation.focused().unwrap(), &left));
+    assert!(navigation.navigate(Direction::Right, reachable));
+    assert!(Rc::ptr_eq(&navigation.focused().unwrap(), &right));
+    assert!(!navigation.navigate(Direction::Right, reachable));
+    assert!(navigation.navigate(Direction::Left, |_| true));
+    assert!(Rc::ptr_eq(&navigation.focused().unwrap(), &hidden));
+}
+
+#[test]
+fn gamepad_skips_hidden_pages() {
+    let clicks = Rc::new(RefCell::new(Vec::new()));
+    let button = |name: &'static str| {
+        let clicks = clicks.clone();
+        gui::button()
+            .on_click(move |_| clicks.borrow_mut().push(name))
+            .build()
+    };
+    let (first, second, hidden) = (button("first"), button("second"), button("hidden"));
+    let tabs = gui::tabs()
+        .add_tab(
+            "Shown",
+            gui::row()
+                .add_widget(first.clone())
+                .add_widget(second.clone())
+                .build(),
+        )
+        .add_tab("Hidden", gui::row().add_widget(hidden.clone()).build())
+        .set_active(1)
+        .build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(tabs.clone());
+    harness.frame().unwrap();
+    // The page which was shown keeps its rect
+    tabs.borrow_mut().set_active(0);
+    let wm = harness.window_manager();
+    wm.add_focusable(first.clone());
+    wm.add_focusable(second.clone());
+    wm.add_focusable(hidden.clone());
+    wm.set_focus(first);
+    harness.frame().unwrap();
+
+    // The button of the hidden page is nearer
+    harness.gamepad_button(Button::DPadRight);
+    harness.gamepad_button(Button::South);
+    harness.frame().unwrap();
+    assert_eq!(*clicks.borrow(), vec!["second"]);
+
+    harness.gamepad_axis(Axis::LeftStickX, -1.);
+    harness.gamepad_button(Button::South);
+    harness.frame().unwrap();
+    assert_eq!(*clicks.borrow(), vec!["second", "first"]);
+    assert!(harness.window_manager().navigation().is_active());
+}
@@ -0,0 +1,29 @@
+use yorool_gui::gui;
+use yorool_gui::gui::progress_bar::{TBackend, TFrontend};
+use yorool_gui::gui::ILabel;
+
+#[test]
+fn value_is_clamped() {
+    let bar = gui::progress_bar().set_value(0.5).build();
+    let backend = bar.borrow().backend();
+    let mut backend = backend.borrow_mut();
+    assert_eq!(backend.get_value(), 0.5);
+    backend.set_value(3.);
+    assert_eq!(backend.get_value(), 1.);
+    backend.set_value(-1.);
+    assert_eq!(backend.get_value(), 0.);
+    backend.set_value(f32::NAN);
+    assert_eq!(backend.get_value(), 0.);
+}
+
+#[test]
+fn text_is_percent_or_label() {
+    let bar = gui::progress_bar().set_value(0.426).build();
+    let backend = bar.borrow().backend();
+    let mut backend = backend.borrow_mut();
+    assert_eq!(backend.text().as_deref(), Some("43%"));
+    backend.set_label("Loading".to_string());
+    assert_eq!(backend.text().as_deref(), Some("Loading"));
+    backend.set_text_visible(false);
+    assert_eq!(backend.text(), None);
+}
@@ -0,0 +1,219 @@
+use ggez::event::{Axis, Button, KeyCode, KeyMods, MouseButton};
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::{FakeContext, Harness};
+use yorool_gui::gui::recording::{
+    format_records, parse_records, Player, Record, RecordedEvent, Recorder,
+};
+use yorool_gui::gui::shortcuts::{KeyChord, Scope};
+use yorool_gui::gui::touch::TouchPhase;
+use yorool_gui::gui::window_manager::WindowManager;
+use yorool_gui::gui::{IEvents, ILayout};
+
+fn records(events: Vec<RecordedEvent>) -> Vec<Record> {
+    events
+        .into_iter()
+        .enumerate()
+        .map(|(i, event)| Record {
+            time: Duration::from_micros(i as u64 * 1001),
+            event,
+        })
+        .collect()
+}
+
+#[test]
+fn events_round_trip() {
+    let records = records(vec![
+        RecordedEvent::Frame(Duration::from_micros(16666)),
+        RecordedEvent::Resize(640., 480.5),
+        RecordedEvent::Mods(KeyMods::CTRL | KeyMods::SHIFT),
+        RecordedEvent::Mods(KeyMods::NONE),
+        RecordedEvent::MouseDown(MouseButton::Other(4), 1.25, -3.),
+        RecordedEvent::MouseUp(MouseButton::Right, 0.1, 0.2),
+        RecordedEvent::MouseMotion(1., 2., -0.333, 4.),
+        RecordedEvent::MouseWheel(0., -1.),
+        RecordedEvent::KeyDown(KeyChord::new(KeyCode::NumpadEnter, KeyMods::ALT), true),
+        RecordedEvent::KeyUp(KeyChord::new(KeyCode::S, KeyMods::CTRL)),
+        RecordedEvent::TextInput(' '),
+        RecordedEvent::TextInput('\n'),
+        RecordedEvent::TextInput('ж'),
+        RecordedEvent::TextInput('+'),
+        RecordedEvent::Touch(3, TouchPhase::Started, 10., 20.5),
+        RecordedEvent::Touch(3, TouchPhase::Cancelled, -1_000_000., 0.),
+        RecordedEvent::GamepadButton(Button::DPadLeft),
+        RecordedEvent::GamepadButton(Button::Unknown),
+        RecordedEvent::GamepadAxis(Axis::LeftStickY, -0.75),
+    ]);
+    let text = format_records(&records);
+    assert_eq!(parse_records(&text).unwrap(), records);
+    assert!(parse_records("12 jump 1").is_err());
+    assert!(parse_records("12 key_down none NoSuchKey").is_err());
+    assert!(parse_records("12 touch 1 lifted 0 0").is_err());
+    assert!(parse_records("12 pad_button Jump").is_err());
+    assert_eq!(parse_records("# note\n\n5 text a\n").unwrap().len(), 1);
+}
+
+#[test]
+fn keys_without_shortcut_names_round_trip() {
+    let keycodes = [
+        KeyCode::LShift,
+        KeyCode::RShift,
+        KeyCode::LControl,
+        KeyCode::LAlt,
+        KeyCode::LWin,
+        KeyCode::Numpad0,
+        KeyCode::Numpad9,
+        KeyCode::Add,
+        KeyCode::LBracket,
+        KeyCode::RBracket,
+        KeyCode::Semicolon,
+        KeyCode::Apostrophe,
+        KeyCode::Grave,
+        KeyCode::Backslash,
+        KeyCode::F24,
+        KeyCode::OEM102,
+        KeyCode::Cut,
+    ];
+    let all_mods = KeyMods::CTRL | KeyMods::SHIFT | KeyMods::ALT | KeyMods::LOGO;
+    let events = keycodes
+        .iter()
+        .flat_map(|k| {
+            vec![
+                RecordedEvent::KeyDown(KeyChord::new(*k, KeyMods::NONE), false),
+                RecordedEvent::KeyDown(KeyChord::new(*k, all_mods), true),
+                RecordedEvent::KeyUp(KeyChord::new(*k, KeyMods::SHIFT)),
+            ]
+        })
+        .collect();
+    let records = records(events);
+    assert_eq!(parse_records(&format_records(&records)).unwrap(), records);
+}
+
+#[test]
+fn record_and_replay() {
+    let area = gui::text_area().build();
+    let brackets = Rc::new(RefCell::new(0));
+    let mut wm = WindowManager::new();
+    wm.set_rect(Rect::new(0., 0., 400., 300.));
+    wm.add_window(area.clone(), Rect::new(0., 0., 400., 300.), true);
+    let chord = KeyChord::new(KeyCode::LBracket, KeyMods::CTRL);
+    {
+        let brackets = brackets.clone();
+        wm.add_shortcut(chord, Scope::Global, move || *brackets.borrow_mut() += 1)
+            .unwrap();
+    }
+    let mut recorder = Recorder::new(wm);
+    let mut ctx = FakeContext::new(400., 300.);
+    let frame = |recorder: &mut Recorder, ctx: &mut FakeContext| {
+        ctx.advance(Duration::from_millis(16));
+        IEvents::update(recorder, ctx).unwrap();
+    };
+    frame(&mut recorder, &mut ctx);
+    IEvents::mouse_motion_event(&mut recorder, &mut ctx, 50., 50., 50., 50.);
+    IEvents::mouse_button_down_event(&mut recorder, &mut ctx, MouseButton::Left, 50., 50.);
+    IEvents::mouse_button_up_event(&mut recorder, &mut ctx, MouseButton::Left, 50., 50.);
+    frame(&mut recorder, &mut ctx);
+    for c in "abc def".chars() {
+        IEvents::text_input_event(&mut recorder, &mut ctx, c);
+        frame(&mut recorder, &mut ctx);
+    }
+    ctx.set_keymods(KeyMods::CTRL);
+    IEvents::key_down_event(
+        &mut recorder,
+        &mut ctx,
+        KeyCode::LControl,
+        KeyMods::CTRL,
+        false,
+    );
+    IEvents::key_down_event(&mut recorder, &mut ctx, KeyCode::Back, KeyMods::CTRL, false);
+    IEvents::key_up_event(&mut recorder, &mut ctx, KeyCode::Back, KeyMods::CTRL);
+    IEvents::key_down_event(
+        &mut recorder,
+        &mut ctx,
+        KeyCode::LBracket,
+        KeyMods::CTRL,
+        false,
+    );
+    IEvents::key_up_event(&mut recorder, &mut ctx, KeyCode::LBracket, KeyMods::CTRL);
+    IEvents::key_up_event(&mut recorder, &mut ctx, KeyCode::LControl, KeyMods::NONE);
+    ctx.set_keymods(KeyMods::NONE);
+    frame(&mut recorder, &mut ctx);
+    let expected = area.borrow().get_text();
+    assert_eq!(expected, "abc ");
+    assert_eq!(*brackets.borrow(), 1);
+    let text = format_records(recorder.records());
+
+    // Replaying twice gives the same result
+    for _ in 0..2 {
+        let area = gui::text_area().build();
+        let brackets = Rc::new(RefCell::new(0));
+        let mut harness = Harness::new(100., 100.);
+        harness.add_window(area.clone());
+        {
+            let brackets = brackets.clone();
+            harness
+                .window_manager()
+                .add_shortcut(chord, Scope::Global, move || *brackets.borrow_mut() += 1)
+                .unwrap();
+        }
+        let mut player = Player::parse(&text).unwrap();
+        harness.play(&mut player).unwrap();
+        assert!(player.is_finished());
+        assert_eq!(area.borrow().get_text(), expected);
+        assert_eq!(*brackets.borrow(), 1);
+        assert_eq!(harness.pointer(), [50., 50.]);
+    }
+}
+
+#[test]
+fn touch_and_gamepad_replay() {
+    let clicks = Rc::new(RefCell::new(Vec::new()));
+    let window = |clicks: &Rc<RefCell<Vec<&'static str>>>| {
+        let button = |name: &'static str| {
+            let clicks = clicks.clone();
+            gui::button()
+                .on_click(move |_| clicks.borrow_mut().push(name))
+                .build()
+        };
+        let (left, right) = (button("left"), button("right"));
+        let mut wm = WindowManager::new();
+        let row = gui::row()
+            .add_widget(left.clone())
+            .add_widget(right.clone())
+            .build();
+        wm.add_window(row, Rect::new(0., 0., 200., 100.), true);
+        wm.set_focus(left);
+        wm
+    };
+    let mut recorder = Recorder::new(window(&clicks));
+    let mut ctx = FakeContext::new(200., 100.);
+    let frame = |recorder: &mut Recorder, ctx: &mut FakeContext| {
+        ctx.advance(Duration::from_millis(16));
+        IEvents::update(recorder, ctx).unwrap();
+    };
+    frame(&mut recorder, &mut ctx);
+    IEvents::touch_event(&mut recorder, &mut ctx, 7, TouchPhase::Started, 150., 50.);
+    frame(&mut recorder, &mut ctx);
+    IEvents::touch_event(&mut recorder, &mut ctx, 7, TouchPhase::Ended, 150., 50.);
+    frame(&mut recorder, &mut ctx);
+    IEvents::gamepad_axis_event(&mut recorder, &mut ctx, Axis::LeftStickX, 0.9);
+    IEvents::gamepad_axis_event(&mut recorder, &mut ctx, Axis::LeftStickX, 0.);
+    IEvents::gamepad_button_down_event(&mut recorder, &mut ctx, Button::DPadLeft);
+    IEvents::gamepad_button_down_event(&mut recorder, &mut ctx, Button::South);
+    frame(&mut recorder, &mut ctx);
+    let expected = clicks.borrow().clone();
+    assert_eq!(expected, vec!["right", "left"]);
+
+    let text = format_records(recorder.records());
+    let clicks = Rc::new(RefCell::new(Vec::new()));
+    let mut wm = window(&clicks);
+    let mut ctx = FakeContext::new(200., 100.);
+    Player::parse(&text)
+        .unwrap()
+        .play(&mut wm, &mut ctx)
+        .unwrap();
+    assert_eq!(*clicks.borrow(), expected);
+}
@@ -0,0 +1,17 @@
+use ggez::graphics::Rect;
+use yorool_gui::gui;
+use yorool_gui::gui::ILayout;
+
+#[test]
+fn inserted_widget_is_laid_out_at_once() {
+    let first = gui::button().build();
+    let second = gui::button().build();
+    let row = gui::row().add_widget(first.clone()).build();
+    row.borrow_mut().set_rect(Rect::new(0., 0., 200., 50.));
+    assert_eq!(first.borrow().get_rect(), Rect::new(0., 0., 200., 50.));
+
+    row.borrow_mut().insert_widget(0, second.clone());
+    assert_eq!(second.borrow().get_rect(), Rect::new(0., 0., 100., 50.));
+    assert_eq!(first.borrow().get_rect(), Rect::new(100., 0., 100., 50.));
+    assert!(row.borrow_mut().take_layout_changed());
+}
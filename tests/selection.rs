@@ -0,0 +1,28 @@
+use ggez::event::{KeyCode, KeyMods};
+use yorool_gui::gui::selection::{Selection, SelectionMode};
+
+#[test]
+fn click_ranges_and_truncate() {
+    let mut s = Selection::new(SelectionMode::Multi);
+    assert!(s.click(3, KeyMods::NONE));
+    assert!(s.click(6, KeyMods::SHIFT));
+    assert_eq!(s.selected(), vec![3, 4, 5, 6]);
+    assert!(s.click(9, KeyMods::CTRL));
+    assert_eq!(s.selected(), vec![3, 4, 5, 6, 9]);
+    assert!(s.truncate(6));
+    assert_eq!(s.selected(), vec![3, 4, 5]);
+}
+
+#[test]
+fn keys_without_current_row() {
+    let start = |keycode| {
+        let mut s = Selection::new(SelectionMode::Single);
+        s.key_down(keycode, KeyMods::NONE, 100, 10);
+        s.current()
+    };
+    assert_eq!(start(KeyCode::Down), Some(0));
+    assert_eq!(start(KeyCode::Up), Some(0));
+    assert_eq!(start(KeyCode::Home), Some(0));
+    assert_eq!(start(KeyCode::End), Some(99));
+    assert_eq!(start(KeyCode::PageDown), Some(9));
+}
@@ -0,0 +1,134 @@
+use ggez::event::{KeyCode, KeyMods};
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::menu::MenuItem;
+use yorool_gui::gui::shortcuts::{label_mnemonic, mnemonic_chord, parse_mnemonic, KeyChord, Scope};
+use yorool_gui::gui::Widget;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn press(harness: &mut Harness, keycode: KeyCode, keymods: KeyMods) {
+    harness.press_key(keycode, keymods);
+    // Shortcut actions run on the next update and handlers they fire
+    // on the one after it
+    harness.frames(2).unwrap();
+}
+
+#[test]
+fn mnemonic_parsing() {
+    assert_eq!(parse_mnemonic("&Save"), ("Save".to_string(), Some(0)));
+    assert_eq!(parse_mnemonic("Save &As"), ("Save As".to_string(), Some(5)));
+    assert_eq!(parse_mnemonic("R&&D"), ("R&D".to_string(), None));
+    assert_eq!(parse_mnemonic("a&"), ("a".to_string(), None));
+    let alt = |keycode| Some(KeyChord::new(keycode, KeyMods::ALT));
+    assert_eq!(mnemonic_chord('s'), alt(KeyCode::S));
+    assert_eq!(mnemonic_chord('3'), alt(KeyCode::Key3));
+    assert_eq!(mnemonic_chord('-'), None);
+    assert_eq!(label_mnemonic("Save &As"), alt(KeyCode::A));
+    assert_eq!(label_mnemonic("Save"), None);
+}
+
+#[test]
+fn mnemonics_follow_scopes() {
+    let log: Log = Rc::new(RefCell::new(Vec::new()));
+    let button = |label: &str, name: &'static str| {
+        let log = log.clone();
+        gui::button()
+            .set_label(label)
+            .on_click(move |_| log.borrow_mut().push(name.to_string()))
+            .build()
+    };
+    let save = button("&Save", "save");
+    let stop = button("&Stop", "stop");
+    let row = gui::row()
+        .add_widget(save.clone())
+        .add_widget(stop.clone())
+        .build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(row);
+    let wm = harness.window_manager();
+    for b in &[&save, &stop] {
+        let widget: Rc<RefCell<dyn Widget>> = (*b).clone();
+        assert!(wm
+            .add_mnemonic(*b, Scope::subtree(widget))
+            .unwrap()
+            .is_some());
+    }
+    let plain = gui::button().set_label("Plain").build();
+    assert_eq!(wm.add_mnemonic(&plain, Scope::Global), Ok(None));
+    harness.frame().unwrap();
+
+    // No focus, so none of the scopes is active
+    press(&mut harness, KeyCode::S, KeyMods::ALT);
+    assert!(log.borrow().is_empty());
+
+    harness.click_at(50., 50.);
+    press(&mut harness, KeyCode::S, KeyMods::ALT);
+    assert_eq!(*log.borrow(), vec!["save", "save"]);
+
+    log.borrow_mut().clear();
+    harness.click_at(150., 50.);
+    press(&mut harness, KeyCode::S, KeyMods::ALT);
+    assert_eq!(*log.borrow(), vec!["stop", "stop"]);
+}
+
+#[test]
+fn mnemonic_conflicts_with_shortcut() {
+    let button = gui::button().set_label("&Quit").build();
+    let mut harness = Harness::new(100., 100.);
+    let wm = harness.window_manager();
+    let quit = KeyChord::new(KeyCode::Q, KeyMods::ALT);
+    let existing = wm.add_shortcut(quit, Scope::Global, || {}).unwrap();
+    let conflict = wm.add_mnemonic(&button, Scope::Global).unwrap_err();
+    assert_eq!(conflict.existing, existing);
+}
+
+#[test]
+fn menu_accelerators_are_shortcuts() {
+    let log: Log = Rc::new(RefCell::new(Vec::new()));
+    let item = |label: &str, accelerator: &str| {
+        let log = log.clone();
+        let name = label.to_string();
+        MenuItem::action(label)
+            .set_accelerator(accelerator)
+            .on_activated(move |_, _| log.borrow_mut().push(name.clone()))
+    };
+    let export = gui::menu().add_item(item("PNG", "Ctrl+E")).build();
+    let file = gui::menu()
+        .add_item(item("Save", "Ctrl+S"))
+        .add_item(MenuItem::submenu("Export", export))
+        .add_item(item("Print", "Ctrl+P").set_enabled(false))
+        .build();
+    let bar = gui::menu_bar().add_menu("File", file.clone()).build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(bar);
+    let ids = harness
+        .window_manager()
+        .add_accelerators(&file, Scope::Global)
+        .unwrap();
+    assert_eq!(ids.len(), 3);
+    harness.frame().unwrap();
+
+    press(&mut harness, KeyCode::S, KeyMods::CTRL);
+    press(&mut harness, KeyCode::E, KeyMods::CTRL);
+    press(&mut harness, KeyCode::P, KeyMods::CTRL);
+    assert_eq!(*log.borrow(), vec!["Save", "PNG"]);
+}
+
+#[test]
+fn conflicting_accelerators_register_nothing() {
+    let menu = gui::menu()
+        .add_item(MenuItem::action("Quit").set_accelerator("Ctrl+Q"))
+        .add_item(MenuItem::action("Save").set_accelerator("Ctrl+S"))
+        .build();
+    let mut harness = Harness::new(100., 100.);
+    let wm = harness.window_manager();
+    let save = KeyChord::parse("Ctrl+S").unwrap();
+    let existing = wm.add_shortcut(save, Scope::Global, || {}).unwrap();
+    let conflict = wm.add_accelerators(&menu, Scope::Global).unwrap_err();
+    assert_eq!(conflict.existing, existing);
+    let quit = KeyChord::parse("Ctrl+Q").unwrap();
+    assert_eq!(wm.shortcuts().find(quit, &Scope::Global), None);
+}
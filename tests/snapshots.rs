@@ -0,0 +1,52 @@
+use ggez::input::mouse::MouseButton;
+use std::path::PathBuf;
+use yorool_gui::gui;
+use yorool_gui::gui::checkbox::CheckboxBuilder;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::raster::assert_snapshot;
+use yorool_gui::gui::{ICheckbox, ILayout};
+
+fn golden(name: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect()
+}
+
+#[test]
+fn button_margins() {
+    let button = gui::button().set_label("Ok").build();
+    let mut harness = Harness::new(80., 40.);
+    harness.add_window(button);
+    harness.frame().unwrap();
+    assert_snapshot(&harness.render(), golden("button.png"), 0);
+
+    // Pressed button moves by the press offset and keeps the label centered
+    harness.move_to(40., 20.);
+    harness.press(MouseButton::Left);
+    assert_snapshot(&harness.render(), golden("button_pressed.png"), 0);
+}
+
+#[test]
+fn checkbox_fill() {
+    let checkbox = CheckboxBuilder::new().build();
+    let mut harness = Harness::new(30., 30.);
+    harness.add_window(checkbox.clone());
+    harness.frame().unwrap();
+    assert_snapshot(&harness.render(), golden("checkbox.png"), 0);
+    checkbox.borrow_mut().set_state(true);
+    assert_snapshot(&harness.render(), golden("checkbox_checked.png"), 0);
+}
+
+#[test]
+fn containers_paint_children() {
+    let checkbox = CheckboxBuilder::new().build();
+    let tabs = gui::tabs().add_tab("Page", checkbox.clone()).build();
+    let mut harness = Harness::new(100., 100.);
+    harness.add_window(gui::gesture_area(tabs));
+    harness.frame().unwrap();
+    let rect = checkbox.borrow().get_rect();
+    let (x, y) = ((rect.x + rect.w / 2.) as u32, (rect.y + rect.h / 2.) as u32);
+    assert_eq!(harness.render().pixel(x, y), [0; 4]);
+    checkbox.borrow_mut().set_state(true);
+    assert_eq!(harness.render().pixel(x, y), [255; 4]);
+}
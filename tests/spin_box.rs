@@ -0,0 +1,63 @@
+use yorool_gui::gui;
+
+#[test]
+fn parse_strips_suffix_and_accepts_comma() {
+    let spin_box = gui::spin_box().set_precision(2).set_suffix(" px").build();
+    let spin_box = spin_box.borrow();
+    assert_eq!(spin_box.parse("12.5 px"), Some(12.5));
+    assert_eq!(spin_box.parse(" 12.5px "), Some(12.5));
+    assert_eq!(spin_box.parse("12,25"), Some(12.25));
+    assert_eq!(spin_box.parse("-3"), Some(-3.));
+    assert_eq!(spin_box.parse("px"), None);
+    assert_eq!(spin_box.parse("abc"), None);
+    assert_eq!(spin_box.parse("inf"), None);
+    assert_eq!(spin_box.parse("NaN"), None);
+}
+
+#[test]
+fn values_are_clamped_and_rounded() {
+    let spin_box = gui::spin_box()
+        .set_range(-10., 10.)
+        .set_precision(1)
+        .build();
+    let mut spin_box = spin_box.borrow_mut();
+    assert_eq!(spin_box.parse("1.26"), Some(1.3));
+    assert_eq!(spin_box.parse("25"), Some(10.));
+    assert_eq!(spin_box.parse("-25"), Some(-10.));
+    spin_box.set_value(2.34);
+    assert_eq!(spin_box.get_value(), 2.3);
+
+    // Narrowing the range clamps the value
+    spin_box.set_range(5., 0.);
+    assert_eq!(spin_box.get_range(), (0., 5.));
+    assert_eq!(spin_box.get_value(), 2.3);
+    spin_box.set_range(4., 5.);
+    assert_eq!(spin_box.get_value(), 4.);
+
+    // Integer input rounds to whole numbers
+    spin_box.set_precision(0).set_value(4.6);
+    assert_eq!(spin_box.get_int(), 5);
+}
+
+#[test]
+fn format_uses_precision_and_suffix() {
+    let spin_box = gui::spin_box()
+        .set_precision(2)
+        .set_suffix("%")
+        .set_value(7.)
+        .build();
+    assert_eq!(spin_box.borrow().format(), "7.00%");
+    spin_box.borrow_mut().set_precision(0).set_value(7.5);
+    assert_eq!(spin_box.borrow().format(), "8%");
+}
+
+#[test]
+fn large_precision_is_limited() {
+    let spin_box = gui::spin_box().set_precision(400).set_value(0.1).build();
+    assert_eq!(spin_box.borrow().get_value(), 0.1);
+    assert_eq!(spin_box.borrow().format(), "0.100000000000000");
+
+    // Huge values are kept instead of overflowing when rounded
+    spin_box.borrow_mut().set_value(1e300);
+    assert_eq!(spin_box.borrow().get_value(), 1e300);
+}
@@ -0,0 +1,43 @@
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::spinner::{TBackend, TFrontend};
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn advance_wraps_phase() {
+    let spinner = gui::spinner().set_period(ms(200)).build();
+    let backend = spinner.borrow().backend();
+    let mut backend = backend.borrow_mut();
+    backend.advance(ms(50));
+    assert!((backend.phase() - 0.25).abs() < 1e-4);
+    backend.advance(ms(200));
+    assert!((backend.phase() - 0.25).abs() < 1e-4);
+    backend.advance(ms(100));
+    assert!((backend.phase() - 0.75).abs() < 1e-4);
+}
+
+#[test]
+fn stopped_or_zero_period_does_not_advance() {
+    let spinner = gui::spinner().set_running(false).build();
+    let backend = spinner.borrow().backend();
+    backend.borrow_mut().advance(ms(250));
+    assert_eq!(backend.borrow().phase(), 0.);
+    backend.borrow_mut().set_running(true);
+    backend.borrow_mut().set_period(ms(0));
+    backend.borrow_mut().advance(ms(250));
+    assert_eq!(backend.borrow().phase(), 0.);
+}
+
+#[test]
+fn update_advances_by_frame_time() {
+    let spinner = gui::spinner().set_period(ms(1000)).build();
+    let mut harness = Harness::new(100., 100.);
+    harness.add_window(spinner.clone());
+    harness.advance(ms(500)).unwrap();
+    let phase = spinner.borrow().backend().borrow().phase();
+    assert!((phase - 0.5).abs() < 0.02, "phase {}", phase);
+}
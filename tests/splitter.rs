@@ -0,0 +1,66 @@
+use ggez::graphics::Rect;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::splitter::Side;
+use yorool_gui::gui::ILayout;
+
+#[test]
+fn double_click_on_divider_collapses() {
+    let splitter = gui::splitter().build();
+    let mut harness = Harness::new(206., 100.);
+    harness.add_window(splitter.clone());
+    harness.frame().unwrap();
+    assert_eq!(splitter.borrow().first_size(), 100.);
+
+    harness.click_at(103., 50.);
+    harness.advance(Duration::from_millis(100)).unwrap();
+    harness.click_at(103., 50.);
+    assert_eq!(splitter.borrow().get_collapsed(), Some(Side::First));
+    assert_eq!(splitter.borrow().first_size(), 0.);
+
+    // Clicks too far apart don't count
+    harness.advance(Duration::from_millis(100)).unwrap();
+    harness.click_at(3., 50.);
+    harness.advance(Duration::from_millis(500)).unwrap();
+    harness.click_at(3., 50.);
+    assert_eq!(splitter.borrow().get_collapsed(), Some(Side::First));
+}
+
+#[test]
+fn click_after_drag_does_not_collapse() {
+    let splitter = gui::splitter().build();
+    let mut harness = Harness::new(206., 100.);
+    harness.add_window(splitter.clone());
+    harness.frame().unwrap();
+
+    harness.drag([103., 50.], [53., 50.], 4);
+    assert_eq!(splitter.borrow().first_size(), 50.);
+    harness.advance(Duration::from_millis(100)).unwrap();
+    harness.click_at(53., 50.);
+    assert_eq!(splitter.borrow().get_collapsed(), None);
+
+    // The click alone starts a new double-click
+    harness.advance(Duration::from_millis(100)).unwrap();
+    harness.click_at(53., 50.);
+    assert_eq!(splitter.borrow().get_collapsed(), Some(Side::First));
+}
+
+#[test]
+fn ratio_survives_resize_and_respects_min_sizes() {
+    let splitter = gui::splitter().set_min_sizes(50., 30.).build();
+    let mut harness = Harness::new(206., 100.);
+    harness.add_window(splitter.clone());
+    harness.frame().unwrap();
+
+    harness.drag([103., 50.], [10., 50.], 2);
+    assert_eq!(splitter.borrow().first_size(), 50.);
+    harness.drag([53., 50.], [200., 50.], 2);
+    assert_eq!(splitter.borrow().first_size(), 170.);
+
+    splitter.borrow_mut().set_ratio(0.25);
+    splitter
+        .borrow_mut()
+        .set_rect(Rect::new(0., 0., 406., 100.));
+    assert_eq!(splitter.borrow().first_size(), 100.);
+}
@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::table::{ITableModel, SortOrder};
+
+struct Letters(Vec<&'static str>);
+
+impl ITableModel for Letters {
+    fn row_count(&self) -> usize {
+        self.0.len()
+    }
+    fn column_count(&self) -> usize {
+        1
+    }
+    fn column_title(&self, _column: usize) -> String {
+        "Letter".to_string()
+    }
+    fn cell_text(&self, row: usize, _column: usize) -> String {
+        self.0[row].to_string()
+    }
+}
+
+#[test]
+fn sort_keeps_model_rows_selected() {
+    let model = Rc::new(RefCell::new(Letters(vec!["b", "a", "c"])));
+    let table = gui::table().set_model(model).build();
+    table.borrow_mut().selection_mut().select_only(0);
+    table.borrow_mut().set_sort(Some((0, SortOrder::Ascending)));
+    let order: Vec<_> = (0..3).map(|r| table.borrow().model_row(r)).collect();
+    assert_eq!(order, vec![1, 0, 2]);
+    assert_eq!(table.borrow().selected_rows(), vec![0]);
+    assert_eq!(table.borrow().selection().selected(), vec![1]);
+}
+
+#[test]
+fn shrinking_model_drops_removed_model_rows() {
+    let model = Rc::new(RefCell::new(Letters(vec!["a", "b", "c"])));
+    let changed = Rc::new(RefCell::new(0));
+    let table = {
+        let changed = changed.clone();
+        gui::table()
+            .set_model(model.clone())
+            .on_selection_changed(move |_| *changed.borrow_mut() += 1)
+            .build()
+    };
+    let mut harness = Harness::new(200., 200.);
+    harness.add_window(table.clone());
+    table
+        .borrow_mut()
+        .set_sort(Some((0, SortOrder::Descending)));
+    // "a" is the last displayed row
+    table.borrow_mut().selection_mut().select_only(2);
+    assert_eq!(table.borrow().selected_rows(), vec![0]);
+
+    model.borrow_mut().0.pop();
+    harness.frame().unwrap();
+    assert_eq!(table.borrow().selected_rows(), vec![0]);
+    assert_eq!(table.borrow().selection().selected(), vec![1]);
+    assert_eq!(*changed.borrow(), 0);
+
+    model.borrow_mut().0.clear();
+    harness.frame().unwrap();
+    harness.frame().unwrap();
+    assert!(table.borrow().selected_rows().is_empty());
+    assert_eq!(*changed.borrow(), 1);
+}
+
+// Each row has the cells of the column titles, so that reading a cell
+// past the columns panics
+struct Grid {
+    titles: Vec<&'static str>,
+    rows: usize,
+}
+
+impl ITableModel for Grid {
+    fn row_count(&self) -> usize {
+        self.rows
+    }
+    fn column_count(&self) -> usize {
+        self.titles.len()
+    }
+    fn column_title(&self, column: usize) -> String {
+        self.titles[column].to_string()
+    }
+    fn cell_text(&self, row: usize, column: usize) -> String {
+        format!("{}{}", self.titles[column], row)
+    }
+}
+
+#[test]
+fn shrinking_model_drops_columns() {
+    let model = Rc::new(RefCell::new(Grid {
+        titles: vec!["a", "b", "c"],
+        rows: 2,
+    }));
+    let table = gui::table()
+        .set_model(model.clone())
+        .set_column_width(5, 40.)
+        .build();
+    let mut harness = Harness::new(600., 200.);
+    harness.add_window(table.clone());
+    harness.frame().unwrap();
+    harness.render();
+
+    model.borrow_mut().titles.truncate(1);
+    harness.frame().unwrap();
+    harness.render();
+    // Clicks on the header right of the last column don't sort
+    harness.click_at(250., 10.);
+    assert_eq!(table.borrow().get_sort(), None);
+    harness.click_at(50., 10.);
+    assert_eq!(table.borrow().get_sort(), Some((0, SortOrder::Ascending)));
+}
+
+#[test]
+fn column_widths_survive_set_model() {
+    let model = Rc::new(RefCell::new(Grid {
+        titles: vec!["a", "b"],
+        rows: 1,
+    }));
+    let table = gui::table()
+        .set_column_width(1, 150.)
+        .set_model(model.clone())
+        .build();
+    assert_eq!(table.borrow().column_width(1), 150.);
+    table.borrow_mut().set_model(model);
+    assert_eq!(table.borrow().column_width(1), 150.);
+}
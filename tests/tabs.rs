@@ -0,0 +1,112 @@
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::{IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+
+// Page which records the keys it gets
+#[derive(Default)]
+struct Page {
+    rect: Rect,
+    keys: Vec<KeyCode>,
+}
+
+impl Page {
+    fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self::default()))
+    }
+}
+
+impl IEvents for Page {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, _ctx: &mut Context) -> GameResult {
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut dyn IContext,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        self.keys.push(keycode);
+    }
+}
+
+impl ILayout for Page {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Page {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a> IOverlays<'a> for Page {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        Vec::new()
+    }
+}
+
+#[test]
+fn click_on_strip_switches_page() {
+    let changed = Rc::new(RefCell::new(Vec::new()));
+    let (first, second) = (Page::new(), Page::new());
+    let tabs = {
+        let changed = changed.clone();
+        gui::tabs()
+            .add_tab("First", first.clone())
+            .add_tab("Second", second.clone())
+            .on_tab_changed(move |_, index| changed.borrow_mut().push(index))
+            .build()
+    };
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(tabs.clone());
+    harness.frame().unwrap();
+    // Only the active page is laid out
+    assert!(first.borrow().rect.h > 0.);
+    harness.click_at(150., 10.);
+    harness.frame().unwrap();
+    assert_eq!(tabs.borrow().get_active(), 1);
+    assert_eq!(*changed.borrow(), vec![1]);
+    assert!(second.borrow().rect.h > 0.);
+}
+
+#[test]
+fn arrow_switching_page_is_not_forwarded() {
+    let (first, second) = (Page::new(), Page::new());
+    let tabs = gui::tabs()
+        .add_tab("First", first.clone())
+        .add_tab("Second", second.clone())
+        .build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(tabs.clone());
+    harness.frame().unwrap();
+    harness.click_at(50., 10.);
+    harness.press_key(KeyCode::Right, KeyMods::NONE);
+    assert_eq!(tabs.borrow().get_active(), 1);
+    assert!(first.borrow().keys.is_empty());
+    assert!(second.borrow().keys.is_empty());
+
+    // At the last page the arrow goes to the page
+    harness.press_key(KeyCode::Right, KeyMods::NONE);
+    harness.press_key(KeyCode::Down, KeyMods::NONE);
+    assert_eq!(second.borrow().keys, vec![KeyCode::Right, KeyCode::Down]);
+    harness.press_key(KeyCode::Left, KeyMods::NONE);
+    assert_eq!(tabs.borrow().get_active(), 0);
+    assert!(first.borrow().keys.is_empty());
+}
@@ -0,0 +1,102 @@
+use yorool_gui::gui::text_area::wrap_line;
+use yorool_gui::gui::text_buffer::{Position, TextBuffer};
+
+fn typed(text: &str) -> TextBuffer {
+    let mut buffer = TextBuffer::new();
+    for c in text.chars() {
+        if c == '\n' {
+            buffer.new_line();
+        } else {
+            buffer.type_char(c);
+        }
+    }
+    buffer
+}
+
+#[test]
+fn typing_is_undone_by_words() {
+    let mut buffer = typed("hello world\nok");
+    assert_eq!(buffer.text(), "hello world\nok");
+    assert!(buffer.undo());
+    assert_eq!(buffer.text(), "hello world\n");
+    assert!(buffer.undo());
+    assert_eq!(buffer.text(), "hello world");
+    assert!(buffer.undo());
+    assert_eq!(buffer.text(), "hello");
+    assert!(buffer.redo());
+    assert_eq!(buffer.text(), "hello world");
+    assert!(buffer.can_redo());
+}
+
+#[test]
+fn backspaces_are_undone_together() {
+    let mut buffer = typed("hello world");
+    buffer.backspace(false);
+    buffer.backspace(false);
+    assert_eq!(buffer.text(), "hello wor");
+    assert!(buffer.undo());
+    assert_eq!(buffer.text(), "hello world");
+    assert_eq!(buffer.caret(), Position::new(0, 11));
+}
+
+#[test]
+fn word_motion_and_deletion() {
+    let mut buffer = typed("hello world");
+    assert_eq!(buffer.word_left_of(buffer.caret()), Position::new(0, 6));
+    assert_eq!(
+        buffer.word_left_of(Position::new(0, 6)),
+        Position::new(0, 0)
+    );
+    assert_eq!(
+        buffer.word_right_of(Position::new(0, 0)),
+        Position::new(0, 6)
+    );
+    buffer.backspace(true);
+    assert_eq!(buffer.text(), "hello ");
+}
+
+#[test]
+fn tab_indents_selected_lines() {
+    let mut buffer = TextBuffer::new();
+    buffer.set_text("a\nb\nc");
+    buffer.set_caret(Position::new(0, 1), false);
+    buffer.set_caret(Position::new(2, 0), true);
+    buffer.tab();
+    assert_eq!(buffer.text(), "    a\n    b\nc");
+    assert_eq!(buffer.selected_text(), "\n    b\n");
+    buffer.unindent();
+    assert_eq!(buffer.text(), "a\nb\nc");
+    assert!(buffer.undo());
+    assert_eq!(buffer.text(), "    a\n    b\nc");
+    assert!(buffer.undo());
+    assert_eq!(buffer.text(), "a\nb\nc");
+}
+
+#[test]
+fn tab_without_selection_pads_to_tab_stop() {
+    let mut buffer = TextBuffer::new();
+    buffer.set_text("a\nb\nc");
+    buffer.set_caret(Position::new(1, 1), false);
+    buffer.tab();
+    assert_eq!(buffer.text(), "a\nb   \nc");
+    assert_eq!(buffer.caret(), Position::new(1, 4));
+}
+
+#[test]
+fn insert_replaces_selection() {
+    let mut buffer = typed("abc");
+    buffer.select_all();
+    buffer.insert("ü\nx");
+    assert_eq!(buffer.text(), "ü\nx");
+    buffer.set_caret(Position::new(0, 1), false);
+    buffer.delete(false);
+    assert_eq!(buffer.text(), "üx");
+    assert_eq!(buffer.slice(Position::new(0, 1), Position::new(0, 2)), "x");
+}
+
+#[test]
+fn wrap_breaks_after_spaces() {
+    let rows = wrap_line("aaa bbb cccccccc", 5., |_| 1.);
+    assert_eq!(rows, vec![(0, 4), (4, 8), (8, 13), (13, 16)]);
+    assert_eq!(wrap_line("", 5., |_| 1.), vec![(0, 0)]);
+}
@@ -0,0 +1,121 @@
+use ggez::graphics::Rect;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::toasts::Level;
+
+fn seconds(s: f32) -> Duration {
+    Duration::from_secs_f32(s)
+}
+
+#[test]
+fn toasts_stack_and_expire() {
+    let mut harness = Harness::new(400., 300.);
+    let wm = harness.window_manager();
+    let first = wm.notify("First", Level::Info, seconds(1.));
+    let second = wm.notify("Two\nlines", Level::Error, seconds(2.));
+    harness.frame().unwrap();
+    let toasts = harness.window_manager().toasts();
+    assert_eq!(toasts.ids(), vec![first, second]);
+    // The oldest toast is in the bottom right corner
+    assert_eq!(
+        toasts.get_rect(first),
+        Some(Rect::new(110., 258., 280., 32.))
+    );
+    assert_eq!(
+        toasts.get_rect(second),
+        Some(Rect::new(110., 196., 280., 52.))
+    );
+
+    harness.advance(seconds(1.)).unwrap();
+    let toasts = harness.window_manager().toasts();
+    assert!(!toasts.is_shown(first));
+    assert_eq!(toasts.get_rect(second).unwrap().y, 238.);
+    harness.advance(seconds(1.)).unwrap();
+    assert!(harness.window_manager().toasts().ids().is_empty());
+}
+
+#[test]
+fn toasts_fade_out() {
+    let mut harness = Harness::new(400., 300.);
+    let id = harness
+        .window_manager()
+        .notify("Saved", Level::Success, seconds(1.));
+    harness.frame().unwrap();
+    assert_eq!(harness.window_manager().toasts().opacity(id), 1.);
+    harness.advance(seconds(0.6)).unwrap();
+    assert_eq!(harness.window_manager().toasts().opacity(id), 1.);
+    harness.advance(seconds(0.25)).unwrap();
+    let opacity = harness.window_manager().toasts().opacity(id);
+    assert!(opacity > 0. && opacity < 0.5, "{}", opacity);
+    harness.advance(seconds(0.2)).unwrap();
+    assert_eq!(harness.window_manager().toasts().opacity(id), 0.);
+}
+
+#[test]
+fn clicks_dismiss_toasts_and_fire_actions() {
+    let presses = Rc::new(RefCell::new(0));
+    let canvas = {
+        let presses = presses.clone();
+        gui::canvas()
+            .on_mouse_down(move |_, _, _, _| *presses.borrow_mut() += 1)
+            .build()
+    };
+    let mut harness = Harness::new(400., 300.);
+    harness.add_window(canvas);
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let wm = harness.window_manager();
+    let plain = wm.notify("Plain", Level::Info, seconds(10.));
+    let undo = wm.notify("Deleted", Level::Warning, seconds(10.));
+    {
+        let fired = fired.clone();
+        wm.add_notification_action(undo, "Undo", move |id| fired.borrow_mut().push(id));
+    }
+    harness.frame().unwrap();
+
+    // Click on the text only dismisses the toast
+    let rect = harness.window_manager().toasts().get_rect(undo).unwrap();
+    harness.click_at(rect.x + 20., rect.y + 10.);
+    harness.frame().unwrap();
+    assert_eq!(harness.window_manager().toasts().ids(), vec![plain]);
+    assert!(fired.borrow().is_empty());
+
+    let undo = harness
+        .window_manager()
+        .notify("Deleted", Level::Warning, seconds(10.));
+    {
+        let fired = fired.clone();
+        harness
+            .window_manager()
+            .add_notification_action(undo, "Undo", move |id| fired.borrow_mut().push(id));
+    }
+    harness.frame().unwrap();
+    let rect = harness.window_manager().toasts().get_rect(undo).unwrap();
+    // The action button is in the bottom right corner of the toast
+    harness.click_at(rect.x + rect.w - 20., rect.y + rect.h - 10.);
+    harness.frame().unwrap();
+    assert_eq!(*fired.borrow(), vec![undo]);
+    assert_eq!(harness.window_manager().toasts().ids(), vec![plain]);
+    // Toasts take the clicks from the windows below
+    assert_eq!(*presses.borrow(), 0);
+    harness.click_at(10., 10.);
+    harness.frame().unwrap();
+    assert_eq!(*presses.borrow(), 1);
+}
+
+#[test]
+fn toasts_are_painted() {
+    let mut harness = Harness::new(400., 300.);
+    let id = harness
+        .window_manager()
+        .notify("Error", Level::Error, seconds(1.));
+    harness.frame().unwrap();
+    let rect = harness.window_manager().toasts().get_rect(id).unwrap();
+    let raster = harness.render();
+    // Level stripe at the left edge of the toast
+    let (x, y) = (rect.x as u32 + 2, (rect.y + rect.h / 2.) as u32);
+    assert_eq!(raster.pixel(x, y), [210, 50, 50, 255]);
+    assert_eq!(raster.pixel(x + 20, y - 10), [255, 255, 255, 255]);
+}
@@ -0,0 +1,70 @@
+use ggez::graphics::Rect;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+
+fn shown(harness: &mut Harness) -> Option<String> {
+    let tooltips = harness.window_manager().tooltips();
+    tooltips.shown().map(|(text, _)| text.to_string())
+}
+
+#[test]
+fn shown_after_delay_for_innermost_widget() {
+    let inner = gui::button().set_label("Inner").build();
+    let outer = gui::row().add_widget(inner.clone()).build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(outer.clone());
+    harness.window_manager().set_tooltip(outer, "outer");
+    harness.window_manager().set_tooltip(inner, "inner");
+    harness.frame().unwrap();
+    harness.move_to(50., 50.);
+    harness.advance(Duration::from_millis(300)).unwrap();
+    assert_eq!(shown(&mut harness), None);
+    harness.advance(Duration::from_millis(300)).unwrap();
+    assert_eq!(shown(&mut harness).as_deref(), Some("inner"));
+    harness.press(ggez::input::mouse::MouseButton::Left);
+    harness.frame().unwrap();
+    assert_eq!(shown(&mut harness), None);
+}
+
+#[test]
+fn hidden_tab_pages_have_no_tooltips() {
+    let first = gui::button().set_label("First").build();
+    let second = gui::button().set_label("Second").build();
+    let tabs = gui::tabs()
+        .add_tab("1", first.clone())
+        .add_tab("2", second.clone())
+        .build();
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(tabs.clone());
+    harness.window_manager().set_tooltip(first, "first");
+    harness.window_manager().set_tooltip(second, "second");
+    harness.frame().unwrap();
+    harness.move_to(100., 80.);
+    harness.advance(Duration::from_secs(1)).unwrap();
+    assert_eq!(shown(&mut harness).as_deref(), Some("first"));
+
+    tabs.borrow_mut().set_active(1);
+    harness.frame().unwrap();
+    assert_eq!(shown(&mut harness), None);
+    harness.advance(Duration::from_secs(1)).unwrap();
+    assert_eq!(shown(&mut harness).as_deref(), Some("second"));
+}
+
+#[test]
+fn covered_windows_have_no_tooltips() {
+    let below = gui::button().set_label("Below").build();
+    let above = gui::button().set_label("Above").build();
+    let mut harness = Harness::new(200., 100.);
+    let wm = harness.window_manager();
+    wm.add_window(below.clone(), Rect::new(0., 0., 200., 100.), false);
+    wm.add_window(above, Rect::new(50., 25., 100., 50.), false);
+    wm.set_tooltip(below, "below");
+    harness.frame().unwrap();
+    harness.move_to(100., 50.);
+    harness.advance(Duration::from_secs(1)).unwrap();
+    assert_eq!(shown(&mut harness), None);
+    harness.move_to(10., 10.);
+    harness.advance(Duration::from_secs(1)).unwrap();
+    assert_eq!(shown(&mut harness).as_deref(), Some("below"));
+}
@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::touch::{Touch, TouchEvent, TouchPhase};
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn first_finger_maps_to_pointer() {
+    let mut touch = Touch::new();
+    assert_eq!(
+        touch.touch(1, TouchPhase::Started, 5., 6., ms(0)),
+        vec![TouchEvent::Hover(5., 6.), TouchEvent::Down(5., 6.)]
+    );
+    assert_eq!(
+        touch.touch(1, TouchPhase::Moved, 7., 6., ms(10)),
+        vec![TouchEvent::Move(7., 6.)]
+    );
+    let up = touch.touch(1, TouchPhase::Ended, 7., 6., ms(20));
+    assert_eq!(up[0], TouchEvent::Up(7., 6.));
+    assert_eq!(touch.touch_count(), 0);
+    // Unknown finger is ignored
+    assert!(touch.touch(9, TouchPhase::Moved, 0., 0., ms(30)).is_empty());
+}
+
+#[test]
+fn time_going_back_does_not_panic() {
+    let mut touch = Touch::new();
+    touch.update(ms(100));
+    touch.touch(1, TouchPhase::Started, 0., 0., ms(50));
+    touch.touch(2, TouchPhase::Started, 10., 0., ms(50));
+    touch.touch(1, TouchPhase::Moved, 0., 10., ms(40));
+    touch.update(ms(30));
+}
+
+#[test]
+fn tap_clicks_button() {
+    let clicks = Rc::new(RefCell::new(0));
+    let button = {
+        let clicks = clicks.clone();
+        gui::button()
+            .on_click(move |_| *clicks.borrow_mut() += 1)
+            .build()
+    };
+    let mut harness = Harness::new(100., 100.);
+    harness.add_window(button);
+    harness.frame().unwrap();
+    harness.touch(1, TouchPhase::Started, 50., 50.);
+    harness.frame().unwrap();
+    harness.touch(1, TouchPhase::Ended, 50., 50.);
+    harness.frame().unwrap();
+    assert_eq!(*clicks.borrow(), 1);
+    assert_eq!(harness.window_manager().touch().touch_count(), 0);
+
+    // Second finger cancels the press, nothing is clicked
+    harness.touch(1, TouchPhase::Started, 50., 50.);
+    harness.touch(2, TouchPhase::Started, 60., 50.);
+    harness.touch(1, TouchPhase::Ended, 50., 50.);
+    harness.touch(2, TouchPhase::Ended, 60., 50.);
+    harness.frame().unwrap();
+    assert_eq!(*clicks.borrow(), 1);
+}
+
+#[test]
+fn pinch_calls_handler_of_widget_under_fingers() {
+    let canvas = gui::canvas().build();
+    let scales = Rc::new(RefCell::new(Vec::new()));
+    let mut harness = Harness::new(500., 500.);
+    harness.add_window(canvas.clone());
+    {
+        let scales = scales.clone();
+        harness
+            .window_manager()
+            .set_pinch_handler(canvas, move |scale, _, _| scales.borrow_mut().push(scale));
+    }
+    harness.frame().unwrap();
+    harness.touch(1, TouchPhase::Started, 200., 250.);
+    harness.touch(2, TouchPhase::Started, 300., 250.);
+    // Spreading both fingers to twice the distance
+    harness.touch(1, TouchPhase::Moved, 150., 250.);
+    harness.touch(2, TouchPhase::Moved, 350., 250.);
+    harness.frame().unwrap();
+    let product: f32 = scales.borrow().iter().product();
+    assert!((product - 2.).abs() < 1e-4, "{:?}", scales);
+}
+
+#[test]
+fn pan_scrolls_and_keeps_scrolling() {
+    let items: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+    let list = gui::list_view()
+        .set_model(Rc::new(RefCell::new(items)))
+        .build();
+    let mut harness = Harness::new(200., 400.);
+    harness.add_window(list.clone());
+    harness.frame().unwrap();
+    harness.touch(1, TouchPhase::Started, 50., 350.);
+    harness.touch(2, TouchPhase::Started, 150., 350.);
+    // Both fingers move up 300px, 20px per frame
+    for step in 1..=15 {
+        harness.frame().unwrap();
+        let y = 350. - step as f32 * 20.;
+        harness.touch(1, TouchPhase::Moved, 50., y);
+        harness.touch(2, TouchPhase::Moved, 150., y);
+    }
+    harness.touch(1, TouchPhase::Ended, 50., 50.);
+    harness.touch(2, TouchPhase::Ended, 150., 50.);
+    let panned = list.borrow().first_visible();
+    assert!(panned > 0);
+    assert!(harness.window_manager().touch().is_scrolling());
+
+    harness.advance(ms(500)).unwrap();
+    let kinetic = list.borrow().first_visible();
+    assert!(kinetic > panned, "{} > {}", kinetic, panned);
+    harness.advance(ms(5000)).unwrap();
+    assert!(!harness.window_manager().touch().is_scrolling());
+    let stopped = list.borrow().first_visible();
+    harness.advance(ms(500)).unwrap();
+    assert_eq!(list.borrow().first_visible(), stopped);
+}
+
+#[test]
+fn fingers_held_still_stop_the_pan() {
+    let mut touch = Touch::new();
+    touch.touch(1, TouchPhase::Started, 50., 350., ms(0));
+    touch.touch(2, TouchPhase::Started, 150., 350., ms(0));
+    for step in 1..=5 {
+        touch.update(ms(step * 16));
+        let y = 350. - step as f32 * 20.;
+        touch.touch(1, TouchPhase::Moved, 50., y, ms(step * 16));
+        touch.touch(2, TouchPhase::Moved, 150., y, ms(step * 16));
+    }
+    // A frame passes without a move before the fingers are lifted
+    touch.update(ms(96));
+    touch.update(ms(112));
+    touch.touch(1, TouchPhase::Ended, 50., 250., ms(120));
+    touch.touch(2, TouchPhase::Ended, 150., 250., ms(120));
+    assert!(!touch.is_scrolling());
+
+    // The same pan lifted right after the move keeps scrolling
+    touch.touch(1, TouchPhase::Started, 50., 350., ms(200));
+    touch.touch(2, TouchPhase::Started, 150., 350., ms(200));
+    for step in 1..=5 {
+        touch.update(ms(200 + step * 16));
+        let y = 350. - step as f32 * 20.;
+        touch.touch(1, TouchPhase::Moved, 50., y, ms(200 + step * 16));
+        touch.touch(2, TouchPhase::Moved, 150., y, ms(200 + step * 16));
+    }
+    touch.update(ms(296));
+    touch.touch(1, TouchPhase::Ended, 50., 250., ms(300));
+    touch.touch(2, TouchPhase::Ended, 150., 250., ms(300));
+    assert!(touch.is_scrolling());
+}
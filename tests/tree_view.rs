@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use yorool_gui::gui;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::tree_view::{ITreeModel, NodeId};
+use yorool_gui::gui::IActions;
+
+// Nodes below 100 have two children: n * 10 and n * 10 + 1
+struct Numbers {
+    loads: usize,
+}
+
+impl ITreeModel for Numbers {
+    fn children(&mut self, parent: Option<NodeId>) -> Vec<NodeId> {
+        self.loads += 1;
+        match parent {
+            None => vec![1, 2],
+            Some(p) if p < 100 => vec![p * 10, p * 10 + 1],
+            _ => vec![],
+        }
+    }
+    fn has_children(&self, node: NodeId) -> bool {
+        node < 100
+    }
+    fn node_label(&self, node: NodeId) -> String {
+        node.to_string()
+    }
+}
+
+#[test]
+fn children_load_lazily() {
+    let model = Rc::new(RefCell::new(Numbers { loads: 0 }));
+    let tree = gui::tree_view().set_model(model.clone()).build();
+    assert_eq!(model.borrow().loads, 1);
+    tree.borrow_mut().set_expanded(1, true);
+    assert_eq!(model.borrow().loads, 2);
+    tree.borrow_mut().set_expanded(1, false);
+    tree.borrow_mut().set_expanded(1, true);
+    assert_eq!(model.borrow().loads, 2);
+}
+
+#[test]
+fn collapse_moves_selection_and_fires_on_selected() {
+    let model = Rc::new(RefCell::new(Numbers { loads: 0 }));
+    let selected = Rc::new(RefCell::new(Vec::new()));
+    let tree = {
+        let selected = selected.clone();
+        gui::tree_view()
+            .set_model(model)
+            .on_selected(move |_, node| selected.borrow_mut().push(node))
+            .build()
+    };
+    tree.borrow_mut().set_expanded(1, true);
+    tree.borrow_mut().set_expanded(10, true);
+    tree.borrow_mut().set_selected(Some(101));
+    for h in tree.borrow_mut().collect_fired() {
+        h()
+    }
+    selected.borrow_mut().clear();
+
+    tree.borrow_mut().set_expanded(1, false);
+    assert_eq!(tree.borrow().get_selected(), Some(1));
+    let fired = tree.borrow_mut().collect_fired();
+    for h in fired {
+        h()
+    }
+    assert_eq!(*selected.borrow(), vec![1]);
+}
+
+#[test]
+fn double_click_uses_gesture_threshold() {
+    let model = Rc::new(RefCell::new(Numbers { loads: 0 }));
+    let activated = Rc::new(RefCell::new(Vec::new()));
+    let tree = {
+        let activated = activated.clone();
+        gui::tree_view()
+            .set_model(model)
+            .on_activated(move |_, node| activated.borrow_mut().push(node))
+            .build()
+    };
+    let mut harness = Harness::new(200., 300.);
+    harness.add_window(tree.clone());
+    harness.frame().unwrap();
+    harness.click_at(150., 45.);
+    harness.advance(Duration::from_millis(450)).unwrap();
+    harness.click_at(150., 45.);
+    harness.frame().unwrap();
+    assert!(activated.borrow().is_empty());
+    harness.advance(Duration::from_millis(200)).unwrap();
+    harness.click_at(150., 45.);
+    harness.frame().unwrap();
+    assert_eq!(*activated.borrow(), vec![2]);
+
+    tree.borrow_mut()
+        .set_double_click_time(Duration::from_millis(1000));
+    harness.click_at(150., 15.);
+    harness.advance(Duration::from_millis(600)).unwrap();
+    harness.click_at(150., 15.);
+    harness.frame().unwrap();
+    assert_eq!(*activated.borrow(), vec![2, 1]);
+}
+
+#[test]
+fn set_expanded_fires_only_moved_selection() {
+    let model = Rc::new(RefCell::new(Numbers { loads: 0 }));
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let tree = {
+        let (expanded, collapsed, selected) = (events.clone(), events.clone(), events.clone());
+        gui::tree_view()
+            .set_model(model)
+            .on_expanded(move |_, node| expanded.borrow_mut().push(("expanded", node)))
+            .on_collapsed(move |_, node| collapsed.borrow_mut().push(("collapsed", node)))
+            .on_selected(move |_, node| selected.borrow_mut().push(("selected", node)))
+            .build()
+    };
+    let fire = |tree: &Rc<RefCell<gui::tree_view::TreeView>>| {
+        let fired = tree.borrow_mut().collect_fired();
+        for h in fired {
+            h()
+        }
+    };
+    tree.borrow_mut().set_expanded(2, true);
+    tree.borrow_mut().set_selected(Some(1));
+    tree.borrow_mut().set_expanded(2, false);
+    fire(&tree);
+    assert!(events.borrow().is_empty());
+
+    tree.borrow_mut().set_expanded(2, true);
+    tree.borrow_mut().set_selected(Some(21));
+    tree.borrow_mut().set_expanded(2, false);
+    fire(&tree);
+    assert_eq!(*events.borrow(), vec![("selected", 2)]);
+}
@@ -0,0 +1,90 @@
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+use yorool_gui::gui::harness::Harness;
+use yorool_gui::gui::{IActions, IContext, IEvents, ILayout, IOverlays, Widget};
+
+// Widget which counts wheel events and shows the popups as overlays
+#[derive(Default)]
+struct Layer {
+    rect: Rect,
+    wheels: usize,
+    popups: Vec<Rc<RefCell<Layer>>>,
+}
+
+impl Layer {
+    fn new(rect: Rect) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            rect,
+            ..Self::default()
+        }))
+    }
+}
+
+impl IEvents for Layer {
+    fn update(&mut self, _ctx: &mut dyn IContext) -> GameResult {
+        Ok(())
+    }
+
+    fn draw(&mut self, _ctx: &mut Context) -> GameResult {
+        Ok(())
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut dyn IContext, _x: f32, _y: f32) {
+        self.wheels += 1;
+    }
+}
+
+impl ILayout for Layer {
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn get_rect(&self) -> Rect {
+        self.rect
+    }
+}
+
+impl<'a> IActions<'a> for Layer {
+    fn collect_fired(&mut self) -> Vec<Rc<dyn Fn() + 'a>> {
+        Vec::new()
+    }
+}
+
+impl<'a> IOverlays<'a> for Layer {
+    fn collect_overlays(&mut self) -> Vec<Rc<RefCell<dyn Widget<'a> + 'a>>> {
+        self.popups
+            .iter()
+            .map(|p| p.clone() as Rc<RefCell<dyn Widget>>)
+            .collect()
+    }
+}
+
+#[test]
+fn wheel_goes_to_popup_under_pointer() {
+    let window = Layer::new(Rect::zero());
+    let left = Layer::new(Rect::new(0., 0., 60., 60.));
+    let right = Layer::new(Rect::new(50., 0., 60., 60.));
+    window.borrow_mut().popups = vec![left.clone(), right.clone()];
+    let mut harness = Harness::new(200., 100.);
+    harness.add_window(window.clone());
+    harness.frame().unwrap();
+
+    harness.move_to(20., 20.);
+    harness.scroll(0., 1.);
+    assert_eq!(left.borrow().wheels, 1);
+    assert_eq!(right.borrow().wheels, 0);
+
+    // The later popup is above where they overlap
+    harness.move_to(55., 20.);
+    harness.scroll(0., 1.);
+    assert_eq!(left.borrow().wheels, 1);
+    assert_eq!(right.borrow().wheels, 1);
+    assert_eq!(window.borrow().wheels, 0);
+
+    harness.move_to(150., 80.);
+    harness.scroll(0., 1.);
+    assert_eq!((left.borrow().wheels, right.borrow().wheels), (1, 1));
+    assert_eq!(window.borrow().wheels, 1);
+}